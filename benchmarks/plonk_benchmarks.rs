@@ -7,7 +7,6 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use merlin::Transcript;
 use plonk::commitment_scheme::kzg10::PublicParameters;
 use plonk::constraint_system::standard::{proof::Proof, Composer, StandardComposer};
-use plonk::fft::EvaluationDomain;
 
 mod serde_benches {
     use super::*;
@@ -24,16 +23,20 @@ mod serde_benches {
             composer.add_dummy_constraints();
         }
 
-        let (ck, _) = public_parameters
-            .trim(2 * composer.circuit_size().next_power_of_two())
-            .unwrap();
-        let domain = EvaluationDomain::new(composer.circuit_size()).unwrap();
+        let (ck, _) = public_parameters.trim_for_circuit(&composer).unwrap();
         let mut transcript = Transcript::new(b"12381");
 
         // Preprocess circuit
-        let preprocessed_circuit = composer.preprocess(&ck, &mut transcript, &domain);
-
-        let proof = composer.prove(&ck, &preprocessed_circuit, &mut transcript);
+        let (preprocessed_circuit, _) = composer.preprocess(&ck, &mut transcript).unwrap();
+
+        let proof = composer
+            .prove(
+                &ck,
+                &preprocessed_circuit,
+                &mut transcript,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
         let proof_ser_data = bincode::serialize(&proof).unwrap();
 
         c.bench_with_input(
@@ -26,7 +26,7 @@
 //!
 //! On this quick example we will see how to generate a Trusted Setup, represented
 //! by plonk's `PublicParameters` data structure to then be able to generate
-//! `Proof`s and `PreProcessedCircuit`s as well as verify these `Proof`s.
+//! `Proof`s and `ProverCircuitKey`s as well as verify these `Proof`s.
 
 extern crate bincode;
 extern crate plonk;
@@ -42,12 +42,13 @@ fn main() -> () {
     // This `size` is usually set as the next_power_of_two of the CIRCUIT_SIZE
     // that we are expecting to have.
     //
-    // Don't worry, you can first compose your circuit and obtain the size that
-    // it will have. And then, generate the `PublicParameters` using as `max_size`
-    // in the `setup` function params `composer.circuit_size().next_power_of_two()`.
+    // Don't worry, you can first compose your circuit and obtain the degree that
+    // it requires. And then, generate the `PublicParameters` using as `max_size`
+    // in the `setup` function params `composer.required_srs_degree()`, which is
+    // slightly above `composer.circuit_size().next_power_of_two()`.
     //
-    // For this example we will asume that our circuit_size is between 2^10 & 2^11
-    // gates, so we will basically use `max_size` as `2^11`.
+    // For this example we will asume that our circuit_size is between 2^9 & 2^10
+    // gates, so `max_size` as `2^11` is more than enough.
 
     let public_params = PublicParameters::setup(1 << 11, &mut rand::thread_rng()).unwrap();
 
@@ -8,18 +8,18 @@
 //! Inputs.
 //!
 //! The second step to be able to generate the `Proof` is to buid a
-//! `PreProcessedCircuit` structure, which is a pure definition of the
+//! `ProverCircuitKey` structure, which is a pure definition of the
 //! logic of the circuit we've build with the help of the `StandardComposer`
 //! previously.
 //! This, is achieved by ordering to our `StandardComposer` to preprocess
 //! the logic that we've implemented with it returning to us a
-//! `PreProcessedCircuit` struct which holds the info of the wire-selector
+//! `ProverCircuitKey` struct which holds the info of the wire-selector
 //! polynomials (the info of the polynomials that describe the operations that
 //! we perform in our circuit).
 //!
 //!
 //! We will show in this file how we can construct circuits using PLONKs'
-//! `StandardComposer` and then, how to obtain a `PreProcessedCircuit`
+//! `StandardComposer` and then, how to obtain a `ProverCircuitKey`
 //! struct which holds the description of the circuit we've designed
 //! to then be able to generate a `Proof` with it.
 //!
@@ -50,7 +50,6 @@ use bls12_381::Scalar;
 use merlin::Transcript;
 use plonk::commitment_scheme::kzg10::PublicParameters;
 use plonk::constraint_system::StandardComposer;
-use plonk::fft::EvaluationDomain;
 use std::fs;
 
 fn main() {
@@ -98,8 +97,8 @@ fn main() {
         // q_c. If we would like to add Constants as part of the circuit description
         // (they're not going to change), we can add them here on q_c.
         Scalar::zero(),
-        // Public Inputs
-        Scalar::zero(),
+        // Public Inputs (we don't use any)
+        None,
     );
 
     // We do the same for `C + D`. This time we will use a width 4 gate just to show how we
@@ -118,8 +117,8 @@ fn main() {
         // q_c. If we would like to add Constants as part of the circuit description
         // (they're not going to change), we can add them here on q_c.
         Scalar::zero(),
-        // Public Inputs
-        Scalar::zero(),
+        // Public Inputs (we don't use any)
+        None,
     );
 
     // Now, time to XOR both results!!
@@ -135,7 +134,7 @@ fn main() {
     //
     // Anyway, if you're not sure of what you're doing, we recommend to use 254 bits to be sure that
     // you're not missing anything.
-    let ab_xor_cd = composer.logic_xor_gate(a_plus_b, c_plus_d, 10usize);
+    let (ab_xor_cd, _) = composer.logic_xor_gate(a_plus_b, c_plus_d, 10usize);
 
     // Now that we've XOR'ed our values, it's time to check if the result is
     // really what we expect it to be, a boolean value.
@@ -154,7 +153,7 @@ fn main() {
     //
     // We can use the `constraint_to_constant` gate which will add a constraint that states that
     // a `Variable` is equal in value to a given `Scalar` which will be added to the circuit description.
-    composer.constrain_to_constant(ab_xor_cd, one, Scalar::zero());
+    composer.constrain_to_constant(ab_xor_cd, one, None);
     // Another way will be to use an `add` gate to perform the subtraction between our variable
     // and One to ensure that the result is 0.
     composer.add_gate(
@@ -174,7 +173,7 @@ fn main() {
         // `q_c` -> Stores our One subtracting to the variable in order to do `ab_xor_cd - One`
         -one,
         // Public Inputs -> We don't use any public inputs.
-        Scalar::zero(),
+        None,
     );
 
     // It can happen, that we will not always want to constraint the number to be One, and instead,
@@ -198,7 +197,10 @@ fn main() {
     // Using this way, we need to know that it is applying the following constraint:
     // `ab_xor_cd - q_c + PI = 0`. So we need to give the negative sign to the public inputs
     // to then force the gate to do `ab_xor_cd - q_c + (-PI) = 0
-    composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), -one);
+    //
+    // Public Inputs are optional on every gate. Whenever we provide one, the composer records the
+    // gate that holds it so the verifier only needs to know the value.
+    composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), Some(-one));
 
     // We can also use the same approach as before and go for an addition gate that subtracts the variable
     // to the Public Inputs.
@@ -224,7 +226,7 @@ fn main() {
         // Public Inputs -> Since we want to be able to change the values to which we constraint our inputs to without
         // them being circuit descriptors, we add them as Public Inputs and with a negative sign to perform the
         // subtraction.
-        -one,
+        Some(-one),
     );
 
     // Since we have polynomials inside of our Composer that don't have any coeff != 0 such as q_range,
@@ -239,34 +241,26 @@ fn main() {
     // We've now finished building our circuit. So what we do now?
     // We need to preprocessit.
     //
-    // Preprocessing the circuit is a step required by the protocol which gives to us a `PreProcessedCircuit`.
+    // Preprocessing the circuit is a step required by the protocol which gives to us a `ProverCircuitKey`.
     // It is a data structure that holds the commitments to the selector and sigma polynomials.
     //
-    // By doing this, we can see the `PreProcessedCircuit` as a "circuit-shape descriptor"
+    // By doing this, we can see the `ProverCircuitKey` as a "circuit-shape descriptor"
     // since it only stores the commitments that describe the operations that we will perform
     // innside the circuit.
     //
-    // Once we have this `PreProcessedCircuit` we can build as many proofs as we want of the same type but with
+    // Once we have this `ProverCircuitKey` we can build as many proofs as we want of the same type but with
     // different values having stored all of the circuit logic "compiled" in some way.
     //
     // This will save us time since it's no longer needed to compile again all of the circuit logic every time we
     // want to create a new `Proof` of the same type. We can simply set new values for the input variables and that's it.
     //
-    // To do the preprocessing, we will also need three more things.
+    // To do the preprocessing, we will also need two more things.
     //
     // 1. A `merlin::Transcript` which will allow Prover and Verifier to perform the fiat-Shamir heuristics without having
     // a direct communication between themseleves.
     // That means that both need to initialize the Transcript with the same randomness seed.
     let mut prover_transcript = Transcript::new(b"End-To-End-Example");
-    // 2. The `EvaluationDomain` on which we are working and performing our evaluations.
-    // It's not needed to understand what it is, if you want to get the `EvaluationDomain` on which your
-    // composer is working, you just need to do the following:
-    //
-    // This will give us the order of the circuit that we've built (The number of cates/constraints that our circuit has).
-    let circ_size = composer.circuit_size();
-    // The `EvaluationDomain` is built according to the `circuit_size` of our Composer. To generate it we simply do:
-    let eval_domain = EvaluationDomain::new(circ_size).unwrap();
-    // 3. The Commitment Key `ProverKey` which will allow us to compute the commitments and basically "hide" our secret values.
+    // 2. The Commitment Key `ProverKey` which will allow us to compute the commitments and basically "hide" our secret values.
     // It is derived from the Trusted Setup `PublicParameters`.
     //
     // What we will do now is basically get the previously generated `PublicParameters` (the testing ones) and derive from them
@@ -277,16 +271,28 @@ fn main() {
         .expect("File not found.\n Run example `0_setup_srs` first please");
     let pub_params: PublicParameters = bincode::deserialize(&ser_pub_params).unwrap();
     // Derive the `ProverKey` from the `PublicParameters`.
-    let (prover_key, verifier_key) = pub_params
-        .trim(composer.circuit_size().next_power_of_two())
-        .unwrap();
+    //
+    // Note that the blinded polynomials the prover commits to have a slightly
+    // higher degree than the circuit size. `trim_for_circuit` works out the degree
+    // that our circuit needs for us.
+    let (prover_key, verifier_key) = pub_params.trim_for_circuit(&composer).unwrap();
 
     // Now we can finally preprocess the circuit that we've built.
-    let pre_processed_circ = composer.preprocess(&prover_key, &mut prover_transcript, &eval_domain);
+    //
+    // Preprocessing gives us two keys: the `ProverCircuitKey`, which holds everything the prover needs,
+    // and the much smaller `VerifierCircuitKey`, which only holds the commitments that the verifier needs.
+    // Both of them also store the `EvaluationDomain` derived from the size of the circuit, so the prover
+    // and the verifier always work over the same one.
+    //
+    // Preprocessing fails if, for instance, the `ProverKey` is too small to commit to the
+    // polynomials of the circuit.
+    let (pre_processed_circ, verifier_circuit_key) = composer
+        .preprocess(&prover_key, &mut prover_transcript)
+        .unwrap();
 
-    // We could now store our `PreProcessedCircuit` serialized with `bincode`.
+    // We could now store our `ProverCircuitKey` serialized with `bincode`.
     // let ser_prep_cir = bincode::serialize(&pre_processed_circ).unwrap();
-    // We can store the `PreProcessedCircuit` serialized in a file for later usage.
+    // We can store the `ProverCircuitKey` serialized in a file for later usage.
     //
     //fs::write("preprocessed_circ.bin", &ser_prep_cir).expect("Unable to write file");
 
@@ -297,20 +303,29 @@ fn main() {
     // that we've loaded into our `Composer`.
     //
     // We clone the transcript since we don't want to modify it to allow then the verifier to re-use it.
-    let proof = composer.prove(
-        &prover_key,
-        &pre_processed_circ,
-        &mut prover_transcript.clone(),
-    );
+    // The prover also needs a source of randomness to blind the polynomials it commits to.
+    let proof = composer
+        .prove(
+            &prover_key,
+            &pre_processed_circ,
+            &mut prover_transcript.clone(),
+            &mut rand::thread_rng(),
+        )
+        .unwrap();
 
-    let zero = Scalar::zero();
     let one = Scalar::one();
     // On this example, since we are using the same composer, we just need to
-    assert!(proof.verify(
-        &pre_processed_circ,
-        &mut prover_transcript,
-        &verifier_key,
-        &vec![zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, -one, -one],
-    ));
+    // provide the values of the two Public Inputs we've used, in the same order
+    // in which they were added to the circuit.
+    //
+    // If the proof were rejected, the returned error would tell us why.
+    assert!(proof
+        .verify(
+            &verifier_circuit_key,
+            &mut prover_transcript,
+            &verifier_key,
+            &[-one, -one],
+        )
+        .is_ok());
     println!("Proof verified succesfully!");
 }
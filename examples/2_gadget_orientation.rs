@@ -19,15 +19,15 @@ use bls12_381::Scalar;
 use merlin::Transcript;
 use plonk::commitment_scheme::kzg10::{PublicParameters, VerifierKey};
 use plonk::constraint_system::StandardComposer;
-use plonk::fft::EvaluationDomain;
-use plonk::proof_system::{PreProcessedCircuit, Proof};
+use plonk::errors::Error;
+use plonk::proof_system::{Proof, VerifierCircuitKey};
 use std::fs;
 
 /// This function will populate our `Composer` adding to it the witness values that we
 /// provide building the example circuit with them.
 ///
 /// This fucntion is intended to be the gadget builder and it can be called by both prover
-/// and Verifier to populate a composer and be able to generate a `PreProcessedCircuit`
+/// and Verifier to populate a composer and be able to generate a `ProverCircuitKey`
 /// so then they can prove or verify.
 fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_result: Scalar) {
     // Get my secret inputs as Variables so we are able to use them inside the circuit.
@@ -53,8 +53,8 @@ fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_resu
         // q_c. If we would like to add Constants as part of the circuit description
         // (they're not going to change), we can add them here on q_c.
         Scalar::zero(),
-        // Public Inputs
-        Scalar::zero(),
+        // Public Inputs (we don't use any)
+        None,
     );
 
     // We do the same for `C + D`. This time we will use a width 4 gate just to show how we
@@ -73,8 +73,8 @@ fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_resu
         // q_c. If we would like to add Constants as part of the circuit description
         // (they're not going to change), we can add them here on q_c.
         Scalar::zero(),
-        // Public Inputs
-        Scalar::zero(),
+        // Public Inputs (we don't use any)
+        None,
     );
 
     // Now, time to XOR both results!!
@@ -90,7 +90,7 @@ fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_resu
     //
     // Anyway, if you're not sure of what you're doing, we recommend to use 254 bits to be sure that
     // you're not missing anything.
-    let ab_xor_cd = composer.logic_xor_gate(a_plus_b, c_plus_d, 14usize);
+    let (ab_xor_cd, _) = composer.logic_xor_gate(a_plus_b, c_plus_d, 14usize);
 
     // Now that we've XOR'ed our values, it's time to check if the result is
     // really what we expect it to be, a boolean value.
@@ -106,7 +106,7 @@ fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_resu
     // Using this way, we need to know that it is applying the following constraint:
     // `ab_xor_cd - q_c + PI = 0`. So we need to give the negative sign to the public inputs
     // to then force the gate to do `ab_xor_cd - q_c + (-PI) = 0
-    composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), -final_result);
+    composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), Some(-final_result));
 
     // Since we have polynomials inside of our Composer that don't have any coeff != 0 such as q_range,
     // we need to add dummy_constraints which allow us to avoid the `PolynomialDegreeZero` error.
@@ -119,18 +119,12 @@ fn build_proof(
     prover_composer: &mut StandardComposer,
     prover_transcript: &mut Transcript,
 ) -> Proof {
-    // ** Note that we could easily move the following lines to obtain the `PreProcessedCircuit` &
+    // ** Note that we could easily move the following lines to obtain the `ProverCircuitKey` &
     // `PublicParameters(ck, vk)` inside of a `lazy_static!` implementation which will
     // make everything much more easy.**
     //
     // Anyway we will do it here to represent it.
     //
-    // This will give us the order of the circuit that we've built (The number of cates/constraints that our circuit has).
-    // Note that since we should have our `PreProcessedCircuit` already stored, we will not need to compute this (the size)
-    // is already known.
-    let circ_size = prover_composer.circuit_size();
-    // The `EvaluationDomain` is built according to the `circuit_size` of our Composer. To generate it we simply do:
-    let eval_domain = EvaluationDomain::new(circ_size).unwrap();
     // The Commitment Key `ProverKey` which will allow us to compute the commitments and basically "hide" our secret values.
     // It is derived from the Trusted Setup `PublicParameters`.
     //
@@ -141,17 +135,24 @@ fn build_proof(
     let ser_pub_params = fs::read(&"examples/.public_params.bin")
         .expect("File not found. Run example `0_setup_srs` first please");
     let pub_params: PublicParameters = bincode::deserialize(&ser_pub_params).unwrap();
-    // Derive the `ProverKey` from the `PublicParameters`.
-    let (prover_key, _) = pub_params
-        .trim(2 * prover_composer.circuit_size().next_power_of_two())
+    // Derive the `ProverKey` from the `PublicParameters`, trimmed to the degree our circuit needs.
+    let (prover_key, _) = pub_params.trim_for_circuit(prover_composer).unwrap();
+    let (prep_circ, _) = prover_composer
+        .preprocess(&prover_key, prover_transcript)
         .unwrap();
-    let prep_circ = prover_composer.preprocess(&prover_key, prover_transcript, &eval_domain);
-    // ** Note that we could easily move the previous lines to obtain the `PreProcessedCircuit` &
+    // ** Note that we could easily move the previous lines to obtain the `ProverCircuitKey` &
     // `PublicParameters(ck, vk)` inside of a `lazy_static!` implementation which will
     // make everything much more easy.**
 
     // Now we build the proof with the parameters we generated.
-    prover_composer.prove(&prover_key, &prep_circ, prover_transcript)
+    prover_composer
+        .prove(
+            &prover_key,
+            &prep_circ,
+            prover_transcript,
+            &mut rand::thread_rng(),
+        )
+        .unwrap()
 }
 
 // This function could be replaced by a using lazy_static or simply deserializing the values
@@ -159,13 +160,7 @@ fn build_proof(
 fn gen_verifier_params(
     verif_composer: &mut StandardComposer,
     verif_transcript: &mut Transcript,
-) -> (PreProcessedCircuit, VerifierKey) {
-    // This will give us the order of the circuit that we've built (The number of cates/constraints that our circuit has).
-    // Note that since we should have our `PreProcessedCircuit` already stored, we will not need to compute this (the size)
-    // is already known.
-    let circ_size = verif_composer.circuit_size();
-    // The `EvaluationDomain` is built according to the `circuit_size` of our Composer. To generate it we simply do:
-    let eval_domain = EvaluationDomain::new(circ_size).unwrap();
+) -> (VerifierCircuitKey, VerifierKey) {
     // The Commitment Key `ProverKey` which will allow us to compute the commitments and basically "hide" our secret values.
     // It is derived from the Trusted Setup `PublicParameters`.
     //
@@ -177,30 +172,25 @@ fn gen_verifier_params(
         .expect("File not found. Run example `0_setup_srs.rs` first please");
     let pub_params: PublicParameters = bincode::deserialize(&ser_pub_params).unwrap();
     // Derive the `ProverKey` from the `PublicParameters`.
-    let (prover_key, verif_key) = pub_params
-        .trim(verif_composer.circuit_size().next_power_of_two())
+    let (prover_key, verif_key) = pub_params.trim_for_circuit(verif_composer).unwrap();
+    // The verifier only needs to keep the `VerifierCircuitKey`, which also holds the
+    // `EvaluationDomain` of the circuit.
+    let (_, verif_circ_key) = verif_composer
+        .preprocess(&prover_key, verif_transcript)
         .unwrap();
-    let prep_circ = verif_composer.preprocess(&prover_key, verif_transcript, &eval_domain);
-    (prep_circ, verif_key)
+    (verif_circ_key, verif_key)
 }
 
 fn verify_proof(
     proof: &Proof,
-    verif_prep_circ: &PreProcessedCircuit,
+    verif_circ_key: &VerifierCircuitKey,
     verif_key: &VerifierKey,
     verif_transcript: &mut Transcript,
     pub_input: &Scalar,
-) -> bool {
-    let zero = Scalar::zero();
-
-    proof.verify(
-        verif_prep_circ,
-        verif_transcript,
-        verif_key,
-        &[
-            zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, zero, *pub_input,
-        ],
-    )
+) -> Result<(), Error> {
+    // Only the values of the public inputs are needed, the positions of
+    // the gates holding them are stored in the `VerifierCircuitKey`.
+    proof.verify(verif_circ_key, verif_transcript, verif_key, &[*pub_input])
 }
 
 /// The goal of the main function will simulate the place on your code where you
@@ -214,7 +204,7 @@ fn main() -> () {
     let mut prover_composer = StandardComposer::new();
     // Generate a Transcript
     let mut prover_transcript = Transcript::new(b"Gadget-Orientation-Is-Cool");
-    // Generate one `PreProcessedCircuit` of the circuit we'll be working with and store it
+    // Generate one `ProverCircuitKey` of the circuit we'll be working with and store it
     // so we can always import it from serialized data or whatever on an init function.
     gadget_builder(
         // Our composer
@@ -245,12 +235,12 @@ fn main() -> () {
     let mut verifier_composer = StandardComposer::new();
     // Generate a Transcript
     let mut verifier_transcript = Transcript::new(b"Gadget-Orientation-Is-Cool");
-    // The verifier needs to have the same `PreProcessedCircuit` (the same vision of)
+    // The verifier needs to have the same `ProverCircuitKey` (the same vision of)
     // the circuit that the `Prover` has. So normally, we will just build a `PreprocessedCircuit`
     // with whatever values in it (VERIFY HAS NOTHING TO DO WITH THE INPUTS THAT THE VERIFIER ADDS
     // TO THE COMPOSER THAT HE/SHE GENERATES).
     //
-    // As mentioned avobe, we could just get our `PreProcessedCircuit` by deserializing it from a file.
+    // As mentioned avobe, we could just get our `ProverCircuitKey` by deserializing it from a file.
     // Anyway, we will do it explicitly here.
     gadget_builder(
         // Our composer
@@ -268,15 +258,16 @@ fn main() -> () {
 
     // The following part could be as simple as deserialize data or have a lazy_static reference.
     // We will just call a function that will give us the parametes that we could easily serialize/deserialize.
-    let (prep_circ, verif_key) =
+    let (verif_circ_key, verif_key) =
         gen_verifier_params(&mut verifier_composer, &mut verifier_transcript);
 
     assert!(verify_proof(
         &proof_1,
-        &prep_circ,
+        &verif_circ_key,
         &verif_key,
         &mut verifier_transcript,
         &-Scalar::one()
-    ));
+    )
+    .is_ok());
     println!("The proof was succesfully verified!");
 }
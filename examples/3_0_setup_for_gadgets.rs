@@ -3,93 +3,104 @@
 //!
 //! We basically need to serialize a couple of structures that will be always the
 //! same such as:
-//! - EvaluationDomain
-//! - PreProcessedCircuit
-//! - PublicParameters (was done on example 0 so it's not going to be done here).
+//! - ProverCircuitKey
+//! - VerifierCircuitKey
+//! - The commit and opening keys trimmed from the PublicParameters to the size
+//! of the circuit (the PublicParameters were generated on example 0).
 //!
 extern crate bincode;
-extern crate merlin;
 extern crate plonk;
 
 use bls12_381::Scalar;
-use merlin::Transcript;
-use plonk::commitment_scheme::kzg10::PublicParameters;
+use plonk::circuit::Circuit;
+use plonk::commitment_scheme::kzg10::{ProverKey, PublicParameters, VerifierKey};
 use plonk::constraint_system::StandardComposer;
-use plonk::fft::EvaluationDomain;
-use plonk::proof_system::{PreProcessedCircuit, Proof};
+use plonk::proof_system::{Proof, ProverCircuitKey, VerifierCircuitKey};
 use std::fs;
 
-// To do this, we basically need to import our gadget builder function.
-fn gadget_builder(composer: &mut StandardComposer, inputs: &[Scalar], final_result: Scalar) {
-    let a = composer.add_input(inputs[0]);
-    let b = composer.add_input(inputs[1]);
-    let c = composer.add_input(inputs[2]);
-    let d = composer.add_input(inputs[3]);
-
-    let a_plus_b = composer.add(
-        (Scalar::one(), a),
-        (Scalar::one(), b),
-        Scalar::zero(),
-        Scalar::zero(),
-    );
-
-    let c_plus_d = composer.big_add(
-        (Scalar::one(), c),
-        (Scalar::one(), d),
-        (Scalar::zero(), composer.zero_var),
-        Scalar::zero(),
-        Scalar::zero(),
-    );
-
-    let ab_xor_cd = composer.logic_xor_gate(a_plus_b, c_plus_d, 14usize);
-    composer.bool_gate(ab_xor_cd);
-    composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), final_result);
-    composer.add_dummy_constraints();
+// To do this, we basically need to import our circuit. The witness values
+// are held by the circuit itself, so the gadget only describes the constraints.
+#[derive(Debug, Default)]
+struct GadgetCircuit {
+    inputs: [Scalar; 4],
+    final_result: Scalar,
 }
 
-fn build_prep_circ() -> PreProcessedCircuit {
-    // Generate a composer & fill it with whatever witnesses (they're not related)
-    // to the PreProcessedCircuit structure at all
-    let mut composer = StandardComposer::new();
-    let mut transcript = Transcript::new(b"Gadget-Orientation-Is-Cool");
-    gadget_builder(
-        &mut composer,
-        &[Scalar::one(), Scalar::one(), Scalar::one(), Scalar::one()],
-        Scalar::one(),
-    );
-    let circ_size = composer.circuit_size();
-    let eval_domain = EvaluationDomain::new(circ_size).unwrap();
-    let ser_pub_params = fs::read(&"examples/.public_params.bin")
-        .expect("File not found. Run example `0_setup_srs` first please");
-    let pub_params: PublicParameters = bincode::deserialize(&ser_pub_params).unwrap();
-    // Derive the `ProverKey` from the `PublicParameters`.
-    let (prover_key, _) = pub_params
-        .trim(2 * composer.circuit_size().next_power_of_two())
-        .unwrap();
-    let prep_circ = composer.preprocess(&prover_key, &mut transcript, &eval_domain);
-    prep_circ
+impl Circuit for GadgetCircuit {
+    const TRANSCRIPT_LABEL: &'static [u8] = b"Gadget-Orientation-Is-Cool";
+
+    fn gadget(&mut self, composer: &mut StandardComposer) {
+        let a = composer.add_input(self.inputs[0]);
+        let b = composer.add_input(self.inputs[1]);
+        let c = composer.add_input(self.inputs[2]);
+        let d = composer.add_input(self.inputs[3]);
+
+        let a_plus_b = composer.add((Scalar::one(), a), (Scalar::one(), b), Scalar::zero(), None);
+
+        let c_plus_d = composer.big_add(
+            (Scalar::one(), c),
+            (Scalar::one(), d),
+            (Scalar::zero(), composer.zero_var),
+            Scalar::zero(),
+            None,
+        );
+
+        let (ab_xor_cd, _) = composer.logic_xor_gate(a_plus_b, c_plus_d, 14usize);
+        composer.bool_gate(ab_xor_cd);
+        composer.constrain_to_constant(ab_xor_cd, Scalar::zero(), Some(self.final_result));
+        composer.add_dummy_constraints();
+    }
 }
 
-fn build_proof(inputs: &[Scalar], final_result: Scalar, prep_circ: &PreProcessedCircuit) -> Proof {
-    let mut composer = StandardComposer::new();
-    let mut transcript = Transcript::new(b"Gadget-Orientation-Is-Cool");
-    gadget_builder(&mut composer, inputs, final_result);
+fn load_pub_params() -> PublicParameters {
     let ser_pub_params = fs::read(&"examples/.public_params.bin")
         .expect("File not found. Run example `0_setup_srs` first please");
-    let pub_params: PublicParameters = bincode::deserialize(&ser_pub_params).unwrap();
-    // Derive the `ProverKey` from the `PublicParameters`.
-    let (prover_key, _) = pub_params
-        .trim(2 * composer.circuit_size().next_power_of_two())
-        .unwrap();
-    composer.prove(&prover_key, &prep_circ, &mut transcript)
+    bincode::deserialize(&ser_pub_params).unwrap()
+}
+
+fn build_prep_circ(
+    pub_params: &PublicParameters,
+) -> (
+    (ProverKey, ProverCircuitKey),
+    (VerifierKey, VerifierCircuitKey),
+) {
+    // The witness values are not related to the circuit keys at all,
+    // so we can compile the circuit without providing any.
+    GadgetCircuit::default().compile(pub_params).unwrap()
+}
+
+fn build_proof(
+    inputs: [Scalar; 4],
+    final_result: Scalar,
+    commit_key: &ProverKey,
+    prep_circ: &ProverCircuitKey,
+) -> Proof {
+    let mut circuit = GadgetCircuit {
+        inputs,
+        final_result,
+    };
+    circuit
+        .prove(commit_key, prep_circ, &mut rand::thread_rng())
+        .unwrap()
 }
 
 fn main() {
-    // Generate the preprocessed circuit & serialize it.
-    let prep_circ = build_prep_circ();
+    // Generate the preprocessed circuit & serialize both of its keys along
+    // with the trimmed commit and opening keys. The verifier only needs to
+    // load the much smaller `VerifierCircuitKey` and `VerifierKey`.
+    let pub_params = load_pub_params();
+    let ((commit_key, prep_circ), (verifier_key, verif_circ_key)) = build_prep_circ(&pub_params);
+
+    let ser_commit_key = bincode::serialize(&commit_key).unwrap();
+    fs::write("examples/.commit_key_2_3.bin", &ser_commit_key).expect("Unable to write file");
+    let ser_verifier_key = bincode::serialize(&verifier_key).unwrap();
+    fs::write("examples/.verifier_key_2_3.bin", &ser_verifier_key).expect("Unable to write file");
 
     let ser_prep_circ = bincode::serialize(&prep_circ).unwrap();
     fs::write("examples/.prep_circ_2_3.bin", &ser_prep_circ).expect("Unable to write file");
+    let ser_verif_circ_key = bincode::serialize(&verif_circ_key).unwrap();
+    fs::write("examples/.verif_circ_key_2_3.bin", &ser_verif_circ_key)
+        .expect("Unable to write file");
 
     // Now we will build a correct and an incorrect proof to use them in the next
     // example. This is not needed, but it will generate a few proofs to be tested
@@ -100,27 +111,27 @@ fn main() {
     let pub_input = -Scalar::one();
 
     // Build & serialize OK proof
-    let inputs = vec![
+    let inputs = [
         Scalar::from(6u64),
         Scalar::from(4u64),
         Scalar::from(3u64),
         Scalar::from(8u64),
     ];
-    let ok_proof = build_proof(&inputs, pub_input, &prep_circ);
-    let ser_proof_ok = bincode::serialize(&ok_proof).unwrap();
-    fs::write("examples/.proof_ok_2_3.bin", &ser_proof_ok).expect("Unable to write file");
+    let ok_proof = build_proof(inputs, pub_input, &commit_key, &prep_circ);
+    // Proofs have their own canonical encoding, which is the one to use
+    // when sending them through the network.
+    fs::write("examples/.proof_ok_2_3.bin", &ok_proof.to_bytes()).expect("Unable to write file");
 
     // Build & serialize KO proof
-    let bad_inputs = vec![
+    let bad_inputs = [
         Scalar::from(73u64),
         Scalar::from(449u64),
         Scalar::from(999u64),
         Scalar::from(9329u64),
     ];
 
-    let ko_proof = build_proof(&bad_inputs, pub_input, &prep_circ);
-    let ser_proof_ko = bincode::serialize(&ko_proof).unwrap();
-    fs::write("examples/.proof_ko_2_3.bin", &ser_proof_ko).expect("Unable to write file");
+    let ko_proof = build_proof(bad_inputs, pub_input, &commit_key, &prep_circ);
+    fs::write("examples/.proof_ko_2_3.bin", &ko_proof.to_bytes()).expect("Unable to write file");
 
     println!("Files were written successfully!");
 }
@@ -0,0 +1,268 @@
+//! Tools & traits for PLONK circuits.
+//!
+//! A `Circuit` describes the shape of a circuit through its `gadget`
+//! function, while the witness values it is built with are held by the
+//! type that implements it. The same type can therefore be compiled once,
+//! with whatever witness values, into the keys of the prover and the
+//! verifier, and later be used to prove many times by simply setting its
+//! witnesses before calling `prove`.
+
+use crate::commitment_scheme::kzg10::{ProverKey, PublicParameters, VerifierKey};
+use crate::constraint_system::StandardComposer;
+use crate::errors::Error;
+use crate::proof_system::{Proof, ProverCircuitKey, VerifierCircuitKey, Widget};
+use bls12_381::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+/// Trait that should be implemented for any circuit that is meant to be
+/// compiled, proven and verified with the `StandardComposer`.
+///
+/// Implementors only need to provide `gadget` and a `TRANSCRIPT_LABEL`,
+/// plus `widgets` if the gadget adds custom widgets to the composer.
+/// The public parameters are trimmed to the size of the circuit once, when
+/// it is compiled, and the transcripts used by prover and verifier are
+/// derived by the default methods of the trait.
+pub trait Circuit {
+    /// Label used to initialise the transcripts of both the prover and
+    /// the verifier of the circuit.
+    const TRANSCRIPT_LABEL: &'static [u8];
+
+    /// Builds the circuit into the `composer` using the witness values
+    /// currently held by `self`.
+    ///
+    /// The constraints added must not depend on the witness values, since
+    /// the circuit keys are computed once and re-used for every proof.
+    /// As with any other composer, `add_dummy_constraints` should be
+    /// called once the circuit has been built.
+    fn gadget(&mut self, composer: &mut StandardComposer);
+
+    /// Returns the custom widgets that `gadget` adds to the composer, in
+    /// the order they are added.
+    ///
+    /// The verifier needs them to check the custom gates of the circuit.
+    /// Defaults to none, which is right for circuits that never call
+    /// `add_widget`.
+    fn widgets(&self) -> Vec<&dyn Widget> {
+        Vec::new()
+    }
+
+    /// Compiles the circuit, returning the keys of the prover and of the
+    /// verifier: the commit and opening keys trimmed from `pub_params` to
+    /// the size of the circuit, along with the `ProverCircuitKey` and the
+    /// `VerifierCircuitKey` that describe it.
+    ///
+    /// The gadget is built on a witness-free composer, so the witness
+    /// values held by `self` are never looked at.
+    fn compile(
+        &mut self,
+        pub_params: &PublicParameters,
+    ) -> Result<
+        (
+            (ProverKey, ProverCircuitKey),
+            (VerifierKey, VerifierCircuitKey),
+        ),
+        Error,
+    > {
+        let mut composer = StandardComposer::witness_free();
+        self.gadget(&mut composer);
+
+        let (commit_key, verifier_key) = pub_params.trim_for_circuit(&composer)?;
+
+        let mut transcript = Transcript::new(Self::TRANSCRIPT_LABEL);
+        let (prover_circuit_key, verifier_circuit_key) =
+            composer.preprocess(&commit_key, &mut transcript)?;
+
+        Ok((
+            (commit_key, prover_circuit_key),
+            (verifier_key, verifier_circuit_key),
+        ))
+    }
+
+    /// Builds the circuit with the witness values currently held by `self`
+    /// and generates a `Proof` of it, using the `commit_key` returned by
+    /// `compile`.
+    fn prove<R: RngCore + CryptoRng>(
+        &mut self,
+        commit_key: &ProverKey,
+        prover_circuit_key: &ProverCircuitKey,
+        rng: &mut R,
+    ) -> Result<Proof, Error> {
+        let mut composer = StandardComposer::new();
+        self.gadget(&mut composer);
+
+        let mut transcript = Transcript::new(Self::TRANSCRIPT_LABEL);
+        prover_circuit_key
+            .verifier_circuit_key()
+            .seed_transcript(&mut transcript);
+
+        composer.prove(commit_key, prover_circuit_key, &mut transcript, rng)
+    }
+
+    /// Verifies a `Proof` of the circuit against the provided public inputs,
+    /// using the `verifier_key` returned by `compile`, and returns the
+    /// reason it was rejected if it is not valid.
+    fn verify(
+        &self,
+        verifier_key: &VerifierKey,
+        verifier_circuit_key: &VerifierCircuitKey,
+        proof: &Proof,
+        pub_inputs: &[Scalar],
+    ) -> Result<(), Error> {
+        let mut transcript = Transcript::new(Self::TRANSCRIPT_LABEL);
+        verifier_circuit_key.seed_transcript(&mut transcript);
+
+        proof.verify_with_widgets(
+            verifier_circuit_key,
+            &mut transcript,
+            verifier_key,
+            pub_inputs,
+            &self.widgets(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment_scheme::kzg10::Commitment;
+    use crate::fft::Polynomial;
+    use crate::proof_system::WireEvaluations;
+    use bls12_381::G1Affine;
+
+    // Proves knowledge of two factors of twelve.
+    #[derive(Debug, Default)]
+    struct FactorsCircuit {
+        a: Scalar,
+        b: Scalar,
+    }
+
+    impl Circuit for FactorsCircuit {
+        const TRANSCRIPT_LABEL: &'static [u8] = b"factors-circuit";
+
+        fn gadget(&mut self, composer: &mut StandardComposer) {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let product = composer.mul(Scalar::one(), a, b, Scalar::zero(), None);
+            composer.constrain_to_constant(product, Scalar::from(12u64), None);
+            composer.add_dummy_constraints();
+        }
+    }
+
+    #[test]
+    fn test_circuit_compile_prove_verify() {
+        let pub_params = PublicParameters::setup(1 << 6, &mut rand::thread_rng()).unwrap();
+
+        // Keys are generated without knowing any witness.
+        let ((commit_key, prover_circuit_key), (verifier_key, verifier_circuit_key)) =
+            FactorsCircuit::default().compile(&pub_params).unwrap();
+
+        let mut circuit = FactorsCircuit {
+            a: Scalar::from(3u64),
+            b: Scalar::from(4u64),
+        };
+        let proof = circuit
+            .prove(&commit_key, &prover_circuit_key, &mut rand::thread_rng())
+            .unwrap();
+        assert!(circuit
+            .verify(&verifier_key, &verifier_circuit_key, &proof, &[])
+            .is_ok());
+
+        // The same circuit can be re-used to prove a different witness.
+        circuit.a = Scalar::from(2u64);
+        circuit.b = Scalar::from(6u64);
+        let proof = circuit
+            .prove(&commit_key, &prover_circuit_key, &mut rand::thread_rng())
+            .unwrap();
+        assert!(circuit
+            .verify(&verifier_key, &verifier_circuit_key, &proof, &[])
+            .is_ok());
+
+        // A wrong witness produces a proof that does not verify.
+        circuit.a = Scalar::from(5u64);
+        let proof = circuit
+            .prove(&commit_key, &prover_circuit_key, &mut rand::thread_rng())
+            .unwrap();
+        assert!(circuit
+            .verify(&verifier_key, &verifier_circuit_key, &proof, &[])
+            .is_err());
+    }
+
+    // Checks `a * a = c` when its selector is set
+    #[derive(Debug)]
+    struct SquareWidget;
+
+    impl Widget for SquareWidget {
+        fn name(&self) -> &'static str {
+            "square"
+        }
+
+        fn num_selectors(&self) -> usize {
+            1
+        }
+
+        fn compute_quotient_i(&self, selectors: &[Scalar], wires: &WireEvaluations) -> Scalar {
+            selectors[0] * (wires.a.square() - wires.c)
+        }
+
+        fn compute_linearisation(
+            &self,
+            selectors: &[&Polynomial],
+            wires: &WireEvaluations,
+        ) -> Polynomial {
+            selectors[0] * &(wires.a.square() - wires.c)
+        }
+
+        fn compute_linearisation_commitment(
+            &self,
+            selectors: &[Commitment],
+            scalars: &mut Vec<Scalar>,
+            points: &mut Vec<G1Affine>,
+            wires: &WireEvaluations,
+        ) {
+            scalars.push(wires.a.square() - wires.c);
+            points.push(selectors[0].0);
+        }
+    }
+
+    // Proves knowledge of a square root of nine with a custom gate.
+    #[derive(Debug, Default)]
+    struct SquareRootCircuit {
+        a: Scalar,
+    }
+
+    impl Circuit for SquareRootCircuit {
+        const TRANSCRIPT_LABEL: &'static [u8] = b"square-root-circuit";
+
+        fn gadget(&mut self, composer: &mut StandardComposer) {
+            let widget = composer.add_widget(Box::new(SquareWidget));
+            let zero = composer.zero_var;
+            let a = composer.add_input(self.a);
+            let a_sq = composer.add_input(self.a.square());
+            composer.custom_gate(widget, a, zero, a_sq, zero, &[Scalar::one()]);
+            composer.constrain_to_constant(a_sq, Scalar::from(9u64), None);
+            composer.add_dummy_constraints();
+        }
+
+        fn widgets(&self) -> Vec<&dyn Widget> {
+            vec![&SquareWidget as &dyn Widget]
+        }
+    }
+
+    #[test]
+    fn test_circuit_with_widgets() {
+        let pub_params = PublicParameters::setup(1 << 6, &mut rand::thread_rng()).unwrap();
+        let ((commit_key, prover_circuit_key), (verifier_key, verifier_circuit_key)) =
+            SquareRootCircuit::default().compile(&pub_params).unwrap();
+
+        let mut circuit = SquareRootCircuit {
+            a: Scalar::from(3u64),
+        };
+        let proof = circuit
+            .prove(&commit_key, &prover_circuit_key, &mut rand::thread_rng())
+            .unwrap();
+        assert!(circuit
+            .verify(&verifier_key, &verifier_circuit_key, &proof, &[])
+            .is_ok());
+    }
+}
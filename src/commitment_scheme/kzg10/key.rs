@@ -347,24 +347,39 @@ impl VerifierKey {
         points: &[Scalar],
         proofs: &[Proof],
         transcript: &mut dyn TranscriptProtocol,
+    ) -> bool {
+        let challenge = transcript.challenge_scalar(b"batch"); // XXX: Verifier can add their own randomness at this point
+        let powers = util::powers_of(&challenge, proofs.len() - 1);
+
+        self.batch_check_with_weights(points, proofs, &powers)
+    }
+
+    /// Checks whether a batch of polynomials evaluated at different points, returned their specified value.
+    /// Each check is scaled by its corresponding weight before being folded into a single pairing check.
+    ///
+    /// The weights must be unpredictable to whoever produced the `proofs`, otherwise
+    /// invalid proofs could be crafted to cancel each other out.
+    pub fn batch_check_with_weights(
+        &self,
+        points: &[Scalar],
+        proofs: &[Proof],
+        weights: &[Scalar],
     ) -> bool {
         let mut total_c = G1Projective::identity();
         let mut total_w = G1Projective::identity();
 
-        let challenge = transcript.challenge_scalar(b"batch"); // XXX: Verifier can add their own randomness at this point
-        let powers = util::powers_of(&challenge, proofs.len() - 1);
         // Instead of multiplying g and gamma_g in each turn, we simply accumulate
         // their coefficients and perform a final multiplication at the end.
         let mut g_multiplier = Scalar::zero();
 
-        for ((proof, challenge), point) in proofs.iter().zip(powers).zip(points) {
+        for ((proof, weight), point) in proofs.iter().zip(weights).zip(points) {
             let mut c = G1Projective::from(proof.commitment_to_polynomial.0);
             let w = proof.commitment_to_witness.0;
             c += w * point;
-            g_multiplier += challenge * proof.evaluated_point;
+            g_multiplier += weight * proof.evaluated_point;
 
-            total_c += c * challenge;
-            total_w += w * challenge;
+            total_c += c * weight;
+            total_w += w * weight;
         }
         total_c -= self.g * g_multiplier;
 
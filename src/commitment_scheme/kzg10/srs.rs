@@ -3,6 +3,8 @@ use super::{
     errors::Error,
     key::{ProverKey, VerifierKey},
 };
+use crate::constraint_system::StandardComposer;
+use crate::errors::Error as PlonkError;
 use crate::util;
 use bls12_381::{G1Affine, G1Projective, G2Affine, G2Prepared};
 use rand_core::RngCore;
@@ -171,6 +173,24 @@ impl PublicParameters {
         Ok((truncated_prover_key, verifier_key))
     }
 
+    /// Trims the public parameters to the degree needed to preprocess and
+    /// prove the circuit built by `composer`, see
+    /// `StandardComposer::required_srs_degree`.
+    /// Returns an error if the public parameters do not support that degree.
+    pub fn trim_for_circuit(
+        &self,
+        composer: &StandardComposer,
+    ) -> Result<(ProverKey, VerifierKey), PlonkError> {
+        let required = composer.required_srs_degree();
+        if required > self.max_degree() {
+            return Err(PlonkError::SRSTooSmall {
+                required,
+                available: self.max_degree(),
+            });
+        }
+        Ok(self.trim(required)?)
+    }
+
     /// Max degree specifies the largest polynomial that this prover key can commit to.
     pub fn max_degree(&self) -> usize {
         self.commit_key.max_degree()
@@ -13,19 +13,122 @@
 // maximum performance and minimum circuit sizes.
 #![allow(clippy::too_many_arguments)]
 use crate::bit_iterator::*;
-use crate::commitment_scheme::kzg10::ProverKey;
+use crate::commitment_scheme::kzg10::errors::Error as KZG10Error;
+use crate::commitment_scheme::kzg10::{Commitment, ProverKey};
+use crate::constraint_system::LookupTable;
+use crate::constraint_system::UInt8;
 use crate::constraint_system::Variable;
 use crate::constraint_system::WireData;
+use crate::errors::Error;
 use crate::fft::{EvaluationDomain, Evaluations, Polynomial};
+use crate::hash::{blake2s, sha256, SpongeGadget};
+use crate::merkle::MerklePath;
+use crate::pedersen;
 use crate::permutation::Permutation;
 use crate::proof_system::linearisation_poly;
 use crate::proof_system::quotient_poly;
-use crate::proof_system::widget::{ArithmeticWidget, LogicWidget, PermutationWidget, RangeWidget};
-use crate::proof_system::{proof::Proof, PreProcessedCircuit};
+use crate::proof_system::widget::lookup::{self, LookupPolynomials};
+use crate::proof_system::widget::{
+    ArithmeticWidget, EccAddWidget, FixedBaseWidget, LogicWidget, LookupWidget, PermutationWidget,
+    RangeWidget,
+};
+use crate::proof_system::widget::{CustomWidget, Widget, WireEvaluations};
+use crate::proof_system::{LookupProof, Proof, ProverCircuitKey, VerifierCircuitKey};
+use crate::schnorr;
 use crate::transcript::TranscriptProtocol;
+use crate::util;
 use bls12_381::Scalar;
+use jubjub::{AffinePoint, ExtendedPoint, Fr as JubJubScalar, EDWARDS_D, GENERATOR};
+use rand_core::{CryptoRng, RngCore};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+// Number of bits the scalars of the Jubjub scalar multiplication gadgets are
+// decomposed into, enough to hold any Jubjub scalar.
+const JUBJUB_SCALAR_BITS: usize = 252;
+
+// Maximum number of bits of the values compared by the comparison gadgets,
+// which keeps the differences they range check far below the modulus.
+const MAX_COMPARISON_BITS: usize = 250;
+
+// A window of `fixed_base_scalar_mul`, which adds one of `-3 * P`, `-P`,
+// `P` and `3 * P` to the accumulated point. Along with the two points, it
+// holds the selectors that its gate stores them in.
+#[derive(Debug, Clone, Copy)]
+struct FixedBaseWindow {
+    q_m: Scalar,
+    q_l: Scalar,
+    q_r: Scalar,
+    q_o: Scalar,
+    q_c: Scalar,
+    q_4: Scalar,
+    point: AffinePoint,
+    triple: AffinePoint,
+}
+
+// The windows of `fixed_base_scalar_mul` for a generator `G`, most
+// significant first, where the `j`-th window holds `P = 4^j * G / 2`.
+//
+// A scalar with the quads `q_j` is computed as the sum of the signed digits
+// `(2 * q_j - 3) * P`, which adds up to `scalar * G - (4^k - 1) * G / 2` for
+// `k` windows, so the accumulated point starts at `offset = (4^k - 1) * G / 2`.
+#[derive(Debug, Clone)]
+struct FixedBaseTable {
+    offset: AffinePoint,
+    windows: Vec<FixedBaseWindow>,
+}
+
+impl FixedBaseTable {
+    // The generator must lie in the prime order subgroup for `G / 2` to exist
+    // and to be the point the windows add up to. Every other point, including
+    // the identity, is rejected before any of them is computed.
+    fn new(generator: AffinePoint) -> FixedBaseTable {
+        assert!(
+            bool::from(generator.is_prime_order()),
+            "the generator of a fixed base scalar multiplication must be a point of prime order"
+        );
+
+        let inv_3 = Scalar::from(3u64).invert().unwrap();
+        let inv_8 = Scalar::from(8u64).invert().unwrap();
+
+        let mut point = ExtendedPoint::from(generator) * JubJubScalar::from(2u64).invert().unwrap();
+        let mut sum = ExtendedPoint::identity();
+        let mut windows = Vec::with_capacity(JUBJUB_SCALAR_BITS / 2);
+        for _ in 0..JUBJUB_SCALAR_BITS / 2 {
+            let p = AffinePoint::from(point);
+            let triple = AffinePoint::from(point.double() + point);
+            let (x_1, y_1) = (p.get_u(), p.get_v());
+            let (x_3, y_3) = (triple.get_u(), triple.get_v());
+
+            // See `FixedBaseWidget` for the way the points are stored
+            let q_r = (y_3 - y_1) * inv_8;
+            let q_4 = (x_3 * y_3 * inv_3 - x_1 * y_1) * inv_8;
+            let q_m = (x_3 - x_1 * Scalar::from(3u64))
+                * (x_3 * y_3 - x_1 * y_1 * Scalar::from(3u64))
+                    .invert()
+                    .unwrap();
+            windows.push(FixedBaseWindow {
+                q_m,
+                q_l: y_1 - q_r,
+                q_r,
+                q_o: x_1 * y_1 - q_4,
+                q_c: x_1 - q_m * x_1 * y_1,
+                q_4,
+                point: p,
+                triple,
+            });
+
+            sum += point;
+            point = point.double().double();
+        }
+        windows.reverse();
+
+        FixedBaseTable {
+            offset: AffinePoint::from(sum.double() + sum),
+            windows,
+        }
+    }
+}
 
 /// A composer is a circuit builder
 /// and will dictate how a circuit is built
@@ -55,8 +158,29 @@ pub struct StandardComposer {
     q_range: Vec<Scalar>,
     // logic selector
     q_logic: Vec<Scalar>,
-
-    public_inputs: Vec<Scalar>,
+    // point addition selector
+    q_ecc_add: Vec<Scalar>,
+    // fixed base scalar multiplication selector
+    q_fixed_base: Vec<Scalar>,
+    // lookup selector
+    q_lookup: Vec<Scalar>,
+
+    // Tables that the lookup gates refer to by their index
+    lookup_tables: Vec<LookupTable>,
+
+    // Tables of the generators of `fixed_base_scalar_mul`, indexed by the
+    // encoding of the generator, so that each one is only computed once
+    fixed_base_tables: HashMap<[u8; 32], FixedBaseTable>,
+
+    // Custom widgets, which the custom gates refer to by their index
+    widgets: Vec<Box<dyn Widget>>,
+    // Selectors of every custom widget. A column only holds the rows up to
+    // the last gate of its widget, the rest of them are zero.
+    custom_selectors: Vec<Vec<Vec<Scalar>>>,
+
+    // Sparse representation of the public inputs, indexed by the
+    // gate that holds each one of them
+    public_inputs_sparse_store: BTreeMap<usize, Scalar>,
 
     // witness vectors
     w_l: Vec<Variable>,
@@ -74,30 +198,58 @@ pub struct StandardComposer {
     // N.B. They should not be exposed to the end user once added into the composer
     pub(crate) variables: HashMap<Variable, Scalar>,
 
+    // When set, the values given to `add_input` are discarded so that the
+    // circuit can be preprocessed without knowing any witness
+    witness_free: bool,
+
     pub(crate) perm: Permutation,
 }
 
 impl StandardComposer {
     /// Computes the pre-processed polynomials
-    /// So the verifier can verify a proof made using this circuit
+    /// So the verifier can verify a proof made using this circuit.
+    ///
+    /// Returns the `ProverCircuitKey`, which holds everything the prover
+    /// needs, and the much smaller `VerifierCircuitKey`, which only holds the
+    /// commitments that the verifier needs. The circuit description is
+    /// also appended to the `transcript`.
+    ///
+    /// The evaluation domain is derived from the size of the circuit and
+    /// stored in both keys, and the `commit_key` can be obtained with
+    /// `PublicParameters::trim_for_circuit`.
+    ///
+    /// Returns an error if the `commit_key` is too small to commit to the
+    /// polynomials of the circuit.
     pub fn preprocess(
         &mut self,
         commit_key: &ProverKey,
         transcript: &mut dyn TranscriptProtocol,
-        domain: &EvaluationDomain,
-    ) -> PreProcessedCircuit {
+    ) -> Result<(ProverCircuitKey, VerifierCircuitKey), Error> {
         let k = self.q_m.len();
-        assert!(self.q_o.len() == k);
-        assert!(self.q_l.len() == k);
-        assert!(self.q_r.len() == k);
-        assert!(self.q_c.len() == k);
-        assert!(self.q_4.len() == k);
-        assert!(self.q_arith.len() == k);
-        assert!(self.q_range.len() == k);
-        assert!(self.q_logic.len() == k);
-        assert!(self.w_l.len() == k);
-        assert!(self.w_r.len() == k);
-        assert!(self.w_o.len() == k);
+        if [
+            self.q_o.len(),
+            self.q_l.len(),
+            self.q_r.len(),
+            self.q_c.len(),
+            self.q_4.len(),
+            self.q_arith.len(),
+            self.q_range.len(),
+            self.q_logic.len(),
+            self.q_ecc_add.len(),
+            self.q_fixed_base.len(),
+            self.q_lookup.len(),
+            self.w_l.len(),
+            self.w_r.len(),
+            self.w_o.len(),
+            self.w_4.len(),
+        ]
+        .iter()
+        .any(|&len| len != k)
+        {
+            return Err(Error::MismatchedPolyLen);
+        }
+        let rows = self.required_rows();
+        let domain = EvaluationDomain::new(rows).ok_or(Error::DomainTooLarge { size: rows })?;
 
         //1. Pad circuit to a power of two
         self.pad(domain.size as usize - self.n);
@@ -112,177 +264,258 @@ impl StandardComposer {
         let q_arith_poly = Polynomial::from_coefficients_slice(&domain.ifft(&self.q_arith));
         let q_range_poly = Polynomial::from_coefficients_slice(&domain.ifft(&self.q_range));
         let q_logic_poly = Polynomial::from_coefficients_slice(&domain.ifft(&self.q_logic));
+        let q_ecc_add_poly = Polynomial::from_coefficients_slice(&domain.ifft(&self.q_ecc_add));
+        let q_fixed_base_poly =
+            Polynomial::from_coefficients_slice(&domain.ifft(&self.q_fixed_base));
 
-        // 2b. Compute 4n evaluations of selector polynomial
-        let domain_4n = EvaluationDomain::new(4 * domain.size()).unwrap();
-        let q_m_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_m_poly.coeffs), domain_4n);
-        let q_l_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_l_poly.coeffs), domain_4n);
-        let q_r_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_r_poly.coeffs), domain_4n);
-        let q_o_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_o_poly.coeffs), domain_4n);
-        let q_c_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_c_poly.coeffs), domain_4n);
-        let q_4_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_4_poly.coeffs), domain_4n);
-        let q_arith_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_arith_poly.coeffs), domain_4n);
-        let q_range_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_range_poly.coeffs), domain_4n);
-        let q_logic_eval_4n =
-            Evaluations::from_vec_and_domain(domain_4n.coset_fft(&q_logic_poly.coeffs), domain_4n);
+        // 2b. Compute 8n evaluations of selector polynomial
+        //
+        // The blinded wire and permutation polynomials have degree `n + 2`,
+        // so the quotient numerator no longer fits in a 4n coset.
+        let domain_8n = EvaluationDomain::new(8 * domain.size()).ok_or(Error::DomainTooLarge {
+            size: 8 * domain.size(),
+        })?;
+        let q_m_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_m_poly.coeffs), domain_8n);
+        let q_l_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_l_poly.coeffs), domain_8n);
+        let q_r_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_r_poly.coeffs), domain_8n);
+        let q_o_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_o_poly.coeffs), domain_8n);
+        let q_c_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_c_poly.coeffs), domain_8n);
+        let q_4_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_4_poly.coeffs), domain_8n);
+        let q_arith_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_arith_poly.coeffs), domain_8n);
+        let q_range_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_range_poly.coeffs), domain_8n);
+        let q_logic_eval_8n =
+            Evaluations::from_vec_and_domain(domain_8n.coset_fft(&q_logic_poly.coeffs), domain_8n);
+        let q_ecc_add_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&q_ecc_add_poly.coeffs),
+            domain_8n,
+        );
+        let q_fixed_base_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&q_fixed_base_poly.coeffs),
+            domain_8n,
+        );
 
         // 3. Compute the sigma polynomials
         let (left_sigma_poly, right_sigma_poly, out_sigma_poly, fourth_sigma_poly) =
-            self.perm.compute_sigma_polynomials(self.n, domain);
+            self.perm.compute_sigma_polynomials(self.n, &domain);
 
-        // 3a. Compute 4n evaluations of sigma polynomials and the linear polynomial
-        let left_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&left_sigma_poly.coeffs),
-            domain_4n,
+        // 3a. Compute 8n evaluations of sigma polynomials and the linear polynomial
+        let left_sigma_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&left_sigma_poly.coeffs),
+            domain_8n,
         );
-        let right_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&right_sigma_poly.coeffs),
-            domain_4n,
+        let right_sigma_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&right_sigma_poly.coeffs),
+            domain_8n,
         );
-        let out_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&out_sigma_poly.coeffs),
-            domain_4n,
+        let out_sigma_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&out_sigma_poly.coeffs),
+            domain_8n,
         );
-        let fourth_sigma_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&fourth_sigma_poly.coeffs),
-            domain_4n,
+        let fourth_sigma_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&fourth_sigma_poly.coeffs),
+            domain_8n,
         );
-        let linear_eval_4n = Evaluations::from_vec_and_domain(
-            domain_4n.coset_fft(&[Scalar::zero(), Scalar::one()]),
-            domain_4n,
+        let linear_eval_8n = Evaluations::from_vec_and_domain(
+            domain_8n.coset_fft(&[Scalar::zero(), Scalar::one()]),
+            domain_8n,
         );
 
         // 4. Commit to polynomials
         //
-        let q_m_poly_commit = commit_key.commit(&q_m_poly).unwrap_or_default();
-        let q_l_poly_commit = commit_key.commit(&q_l_poly).unwrap_or_default();
-        let q_r_poly_commit = commit_key.commit(&q_r_poly).unwrap_or_default();
-        let q_o_poly_commit = commit_key.commit(&q_o_poly).unwrap_or_default();
-        let q_c_poly_commit = commit_key.commit(&q_c_poly).unwrap_or_default();
-        let q_4_poly_commit = commit_key.commit(&q_4_poly).unwrap_or_default();
-        let q_arith_poly_commit = commit_key.commit(&q_arith_poly).unwrap_or_default();
-        let q_range_poly_commit = commit_key.commit(&q_range_poly).unwrap_or_default();
-        let q_logic_poly_commit = commit_key.commit(&q_logic_poly).unwrap_or_default();
-
-        let left_sigma_poly_commit = commit_key.commit(&left_sigma_poly).unwrap();
-        let right_sigma_poly_commit = commit_key.commit(&right_sigma_poly).unwrap();
-        let out_sigma_poly_commit = commit_key.commit(&out_sigma_poly).unwrap();
-        let fourth_sigma_poly_commit = commit_key.commit(&fourth_sigma_poly).unwrap();
-
-        //5. Add polynomial commitments to transcript
-        //
-        transcript.append_commitment(b"q_m", &q_m_poly_commit);
-        transcript.append_commitment(b"q_l", &q_l_poly_commit);
-        transcript.append_commitment(b"q_r", &q_r_poly_commit);
-        transcript.append_commitment(b"q_o", &q_o_poly_commit);
-        transcript.append_commitment(b"q_c", &q_c_poly_commit);
-        transcript.append_commitment(b"q_4", &q_4_poly_commit);
-        transcript.append_commitment(b"q_arith", &q_arith_poly_commit);
-        transcript.append_commitment(b"q_range", &q_range_poly_commit);
-        transcript.append_commitment(b"q_logic", &q_logic_poly_commit);
-
-        transcript.append_commitment(b"left_sigma", &left_sigma_poly_commit);
-        transcript.append_commitment(b"right_sigma", &right_sigma_poly_commit);
-        transcript.append_commitment(b"out_sigma", &out_sigma_poly_commit);
-        transcript.append_commitment(b"fourth_sigma", &fourth_sigma_poly_commit);
-
-        // Append circuit size to transcript
-        transcript.circuit_domain_sep(self.circuit_size() as u64);
+        let q_m_poly_commit = Self::commit_selector(commit_key, &q_m_poly)?;
+        let q_l_poly_commit = Self::commit_selector(commit_key, &q_l_poly)?;
+        let q_r_poly_commit = Self::commit_selector(commit_key, &q_r_poly)?;
+        let q_o_poly_commit = Self::commit_selector(commit_key, &q_o_poly)?;
+        let q_c_poly_commit = Self::commit_selector(commit_key, &q_c_poly)?;
+        let q_4_poly_commit = Self::commit_selector(commit_key, &q_4_poly)?;
+        let q_arith_poly_commit = Self::commit_selector(commit_key, &q_arith_poly)?;
+        let q_range_poly_commit = Self::commit_selector(commit_key, &q_range_poly)?;
+        let q_logic_poly_commit = Self::commit_selector(commit_key, &q_logic_poly)?;
+        let q_ecc_add_poly_commit = Self::commit_selector(commit_key, &q_ecc_add_poly)?;
+        let q_fixed_base_poly_commit = Self::commit_selector(commit_key, &q_fixed_base_poly)?;
+
+        let left_sigma_poly_commit = Self::commit(commit_key, &left_sigma_poly)?;
+        let right_sigma_poly_commit = Self::commit(commit_key, &right_sigma_poly)?;
+        let out_sigma_poly_commit = Self::commit(commit_key, &out_sigma_poly)?;
+        let fourth_sigma_poly_commit = Self::commit(commit_key, &fourth_sigma_poly)?;
+
+        // The fixed base gates read their tables from the arithmetic selectors
+        let fixed_base_widget = FixedBaseWidget::new((
+            q_fixed_base_poly,
+            q_fixed_base_poly_commit,
+            Some(q_fixed_base_eval_8n),
+        ));
 
         let arithmetic_widget = ArithmeticWidget::new((
-            (q_m_poly, q_m_poly_commit, Some(q_m_eval_4n)),
-            (q_l_poly, q_l_poly_commit, Some(q_l_eval_4n)),
-            (q_r_poly, q_r_poly_commit, Some(q_r_eval_4n)),
-            (q_o_poly, q_o_poly_commit, Some(q_o_eval_4n)),
+            (q_m_poly, q_m_poly_commit, Some(q_m_eval_8n)),
+            (q_l_poly, q_l_poly_commit, Some(q_l_eval_8n)),
+            (q_r_poly, q_r_poly_commit, Some(q_r_eval_8n)),
+            (q_o_poly, q_o_poly_commit, Some(q_o_eval_8n)),
             // XXX: Should try to remove the clones
-            (q_c_poly.clone(), q_c_poly_commit, Some(q_c_eval_4n.clone())),
-            (q_4_poly, q_4_poly_commit, Some(q_4_eval_4n)),
-            (q_arith_poly, q_arith_poly_commit, Some(q_arith_eval_4n)),
+            (q_c_poly.clone(), q_c_poly_commit, Some(q_c_eval_8n.clone())),
+            (q_4_poly, q_4_poly_commit, Some(q_4_eval_8n)),
+            (q_arith_poly, q_arith_poly_commit, Some(q_arith_eval_8n)),
         ));
 
         let range_widget =
-            RangeWidget::new((q_range_poly, q_range_poly_commit, Some(q_range_eval_4n)));
+            RangeWidget::new((q_range_poly, q_range_poly_commit, Some(q_range_eval_8n)));
+
+        // Circuits without lookup tables skip the lookup argument
+        let lookup_widget = if self.lookup_tables.is_empty() {
+            None
+        } else {
+            Some(self.preprocess_lookup(
+                commit_key,
+                (&domain, &domain_8n),
+                (q_c_poly.clone(), q_c_poly_commit, Some(q_c_eval_8n.clone())),
+            )?)
+        };
 
         let logic_widget = LogicWidget::new(
-            (q_c_poly, q_c_poly_commit, Some(q_c_eval_4n)),
-            (q_logic_poly, q_logic_poly_commit, Some(q_logic_eval_4n)),
+            (q_c_poly, q_c_poly_commit, Some(q_c_eval_8n)),
+            (q_logic_poly, q_logic_poly_commit, Some(q_logic_eval_8n)),
         );
 
+        let ecc_add_widget = EccAddWidget::new((
+            q_ecc_add_poly,
+            q_ecc_add_poly_commit,
+            Some(q_ecc_add_eval_8n),
+        ));
+
         let perm_widget = PermutationWidget::new(
             (
                 left_sigma_poly,
                 left_sigma_poly_commit,
-                Some(left_sigma_eval_4n),
+                Some(left_sigma_eval_8n),
             ),
             (
                 right_sigma_poly,
                 right_sigma_poly_commit,
-                Some(right_sigma_eval_4n),
+                Some(right_sigma_eval_8n),
             ),
             (
                 out_sigma_poly,
                 out_sigma_poly_commit,
-                Some(out_sigma_eval_4n),
+                Some(out_sigma_eval_8n),
             ),
             (
                 fourth_sigma_poly,
                 fourth_sigma_poly_commit,
-                Some(fourth_sigma_eval_4n),
+                Some(fourth_sigma_eval_8n),
             ),
-            linear_eval_4n,
+            linear_eval_8n,
         );
 
-        PreProcessedCircuit {
-            n: self.n,
+        let custom_widgets = self
+            .custom_selectors
+            .iter()
+            .zip(self.widgets.iter())
+            .map(|(columns, widget)| {
+                let selectors = columns
+                    .iter()
+                    .map(|column| {
+                        let mut column = column.clone();
+                        column.resize(domain.size(), Scalar::zero());
+                        let poly = Polynomial::from_coefficients_vec(domain.ifft(&column));
+                        let eval_8n = Evaluations::from_vec_and_domain(
+                            domain_8n.coset_fft(&poly.coeffs),
+                            domain_8n,
+                        );
+                        let commit = Self::commit_selector(commit_key, &poly)?;
+                        Ok((poly, commit, Some(eval_8n)))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(CustomWidget::new(widget.name(), selectors))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let prover_circuit_key = ProverCircuitKey {
+            domain,
+            pi_pos: self.pi_positions(),
             arithmetic: arithmetic_widget,
             range: range_widget,
             logic: logic_widget,
+            ecc_add: ecc_add_widget,
+            fixed_base: fixed_base_widget,
+            lookup: lookup_widget,
+            custom: custom_widgets,
             permutation: perm_widget,
-            // Compute 4n evaluations for X^n -1
-            v_h_coset_4n: domain_4n.compute_vanishing_poly_over_coset(domain.size() as u64),
-        }
+            // Compute 8n evaluations for X^n -1
+            v_h_coset_8n: domain_8n.compute_vanishing_poly_over_coset(domain.size() as u64),
+        };
+        let verifier_circuit_key = prover_circuit_key.verifier_circuit_key();
+
+        //5. Add polynomial commitments to transcript
+        verifier_circuit_key.seed_transcript(transcript);
+
+        Ok((prover_circuit_key, verifier_circuit_key))
     }
 
     /// Prove will compute the pre-processed polynomials and
-    /// produce a proof
-    pub fn prove(
+    /// produce a proof.
+    ///
+    /// The witness, permutation and quotient polynomials are blinded with
+    /// randomness drawn from `rng`, so the proof reveals nothing about the
+    /// witness beyond the validity of the statement.
+    ///
+    /// Returns an error if the composer was created with `witness_free`,
+    /// if the circuit it holds is not the one described by
+    /// `preprocessed_circuit` or if the `commit_key` is too small.
+    pub fn prove<R: RngCore + CryptoRng>(
         &mut self,
         commit_key: &ProverKey,
-        preprocessed_circuit: &PreProcessedCircuit,
+        preprocessed_circuit: &ProverCircuitKey,
         transcript: &mut dyn TranscriptProtocol,
-    ) -> Proof {
-        let domain = EvaluationDomain::new(self.n).unwrap();
+        rng: &mut R,
+    ) -> Result<Proof, Error> {
+        if self.witness_free {
+            return Err(Error::WitnessFreeComposer);
+        }
+        let domain = preprocessed_circuit.domain;
+        if self.required_rows().next_power_of_two() != domain.size()
+            || self.pi_positions() != preprocessed_circuit.pi_pos
+            || self.widgets.len() != preprocessed_circuit.custom.len()
+            || self
+                .widgets
+                .iter()
+                .zip(preprocessed_circuit.custom.iter())
+                .any(|(widget, custom)| widget.name() != custom.name)
+            || self.lookup_tables.is_empty() != preprocessed_circuit.lookup.is_none()
+        {
+            return Err(Error::MismatchedCircuit);
+        }
 
         //1. Compute witness Polynomials
         //
         // Convert Variables to Scalars padding them to the
         // correct domain size.
         let pad = vec![Scalar::zero(); domain.size() - self.w_l.len()];
-        let w_l_scalar = &[&self.to_scalars(&self.w_l)[..], &pad].concat();
-        let w_r_scalar = &[&self.to_scalars(&self.w_r)[..], &pad].concat();
-        let w_o_scalar = &[&self.to_scalars(&self.w_o)[..], &pad].concat();
-        let w_4_scalar = &[&self.to_scalars(&self.w_4)[..], &pad].concat();
-
-        // Witnesses are now in evaluation form, convert them to coefficients
-        // So that we may commit to them
-        let w_l_poly = Polynomial::from_coefficients_vec(domain.ifft(w_l_scalar));
-        let w_r_poly = Polynomial::from_coefficients_vec(domain.ifft(w_r_scalar));
-        let w_o_poly = Polynomial::from_coefficients_vec(domain.ifft(w_o_scalar));
-        let w_4_poly = Polynomial::from_coefficients_vec(domain.ifft(w_4_scalar));
+        let w_l_scalar = &[&self.to_scalars(&self.w_l)?[..], &pad].concat();
+        let w_r_scalar = &[&self.to_scalars(&self.w_r)?[..], &pad].concat();
+        let w_o_scalar = &[&self.to_scalars(&self.w_o)?[..], &pad].concat();
+        let w_4_scalar = &[&self.to_scalars(&self.w_4)?[..], &pad].concat();
+
+        // Witnesses are now in evaluation form, convert them to blinded
+        // coefficients so that we may commit to them
+        let (w_l_poly, w_r_poly, w_o_poly, w_4_poly) = Self::compute_wire_polys(
+            &domain,
+            (w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar),
+            rng,
+        );
 
         // Commit to witness polynomials
-        let w_l_poly_commit = commit_key.commit(&w_l_poly).unwrap();
-        let w_r_poly_commit = commit_key.commit(&w_r_poly).unwrap();
-        let w_o_poly_commit = commit_key.commit(&w_o_poly).unwrap();
-        let w_4_poly_commit = commit_key.commit(&w_4_poly).unwrap();
+        let w_l_poly_commit = Self::commit(commit_key, &w_l_poly)?;
+        let w_r_poly_commit = Self::commit(commit_key, &w_r_poly)?;
+        let w_o_poly_commit = Self::commit(commit_key, &w_o_poly)?;
+        let w_4_poly_commit = Self::commit(commit_key, &w_4_poly)?;
 
         // Add witness polynomial commitments to transcript
         transcript.append_commitment(b"w_l", &w_l_poly_commit);
@@ -290,7 +523,26 @@ impl StandardComposer {
         transcript.append_commitment(b"w_o", &w_o_poly_commit);
         transcript.append_commitment(b"w_4", &w_4_poly_commit);
 
-        // 2. Compute permutation polynomial
+        // Add public inputs to transcript, so that the challenges
+        // depend on them
+        transcript.append_public_inputs(&self.public_inputs());
+
+        // 2. Compute lookup polynomials
+        //
+        // Circuits without lookup tables skip the lookup argument
+        let lookup = match &preprocessed_circuit.lookup {
+            Some(lookup_widget) => Some(self.compute_lookup_polynomials(
+                commit_key,
+                &domain,
+                lookup_widget,
+                (w_l_scalar, w_r_scalar, w_o_scalar),
+                transcript,
+                rng,
+            )?),
+            None => None,
+        };
+
+        // 3. Compute permutation polynomials
         //
         //
         // Compute permutation challenges; `beta` and `gamma`
@@ -298,35 +550,40 @@ impl StandardComposer {
         transcript.append_scalar(b"beta", &beta);
         let gamma = transcript.challenge_scalar(b"gamma");
 
-        let z_poly = self.perm.compute_permutation_poly(
+        let z_poly = self.compute_permutation_poly(
             &domain,
-            &w_l_scalar,
-            &w_r_scalar,
-            &w_o_scalar,
-            &w_4_scalar,
+            (w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar),
             &(beta, gamma),
-            (
-                &preprocessed_circuit.permutation.left_sigma.polynomial,
-                &preprocessed_circuit.permutation.right_sigma.polynomial,
-                &preprocessed_circuit.permutation.out_sigma.polynomial,
-                &preprocessed_circuit.permutation.fourth_sigma.polynomial,
-            ),
+            &preprocessed_circuit.permutation,
+            rng,
         );
 
         // Commit to permutation polynomial
         //
-        let z_poly_commit = commit_key.commit(&z_poly).unwrap();
+        let z_poly_commit = Self::commit(commit_key, &z_poly)?;
 
         // Add permutation polynomial commitment to transcript
         transcript.append_commitment(b"z", &z_poly_commit);
 
-        // 3. Compute public inputs polynomial
-        let pi_poly = Polynomial::from_coefficients_vec(domain.ifft(&self.public_inputs));
+        // 4. Compute public inputs polynomial
+        let pi_poly =
+            Polynomial::from_coefficients_vec(domain.ifft(&self.construct_dense_pi_vec()));
 
-        // 4. Compute quotient polynomial
+        // 5. Compute quotient polynomial
         //
         // Compute quotient challenge; `alpha`
         let alpha = transcript.challenge_scalar(b"alpha");
+        // Compute the challenge that separates the point addition constraints
+        let ecc_separation_challenge = transcript.challenge_scalar(b"ecc_separation_challenge");
+        // Compute the challenge that separates the fixed base scalar multiplication constraints
+        let fixed_base_separation_challenge =
+            transcript.challenge_scalar(b"fixed_base_separation_challenge");
+        // Compute the challenges that separate the constraints of every custom widget
+        let custom_separation_challenges: Vec<Scalar> = self
+            .widgets
+            .iter()
+            .map(|_| transcript.challenge_scalar(b"custom_separation_challenge"))
+            .collect();
 
         let t_poly = quotient_poly::compute(
             &domain,
@@ -335,16 +592,21 @@ impl StandardComposer {
             (&w_l_poly, &w_r_poly, &w_o_poly, &w_4_poly),
             &pi_poly,
             &(alpha, beta, gamma),
+            &ecc_separation_challenge,
+            &fixed_base_separation_challenge,
+            lookup.as_ref().map(|(lookup, _)| lookup),
+            (&self.widgets, &custom_separation_challenges),
         );
 
-        // Split quotient polynomial into 4 degree `n` polynomials
-        let (t_1_poly, t_2_poly, t_3_poly, t_4_poly) = self.split_tx_poly(domain.size(), &t_poly);
+        // Split quotient polynomial into 4 blinded polynomials
+        let (t_1_poly, t_2_poly, t_3_poly, t_4_poly) =
+            self.split_tx_poly(domain.size(), &t_poly, rng);
 
         // Commit to splitted quotient polynomial
-        let t_1_commit = commit_key.commit(&t_1_poly).unwrap();
-        let t_2_commit = commit_key.commit(&t_2_poly).unwrap();
-        let t_3_commit = commit_key.commit(&t_3_poly).unwrap();
-        let t_4_commit = commit_key.commit(&t_4_poly).unwrap();
+        let t_1_commit = Self::commit(commit_key, &t_1_poly)?;
+        let t_2_commit = Self::commit(commit_key, &t_2_poly)?;
+        let t_3_commit = Self::commit(commit_key, &t_3_poly)?;
+        let t_4_commit = Self::commit(commit_key, &t_4_poly)?;
 
         // Add quotient polynomial commitments to transcript
         transcript.append_commitment(b"t_1", &t_1_commit);
@@ -352,7 +614,7 @@ impl StandardComposer {
         transcript.append_commitment(b"t_3", &t_3_commit);
         transcript.append_commitment(b"t_4", &t_4_commit);
 
-        // 4. Compute linearisation polynomial
+        // 6. Compute linearisation polynomial
         //
         // Compute evaluation challenge; `z`
         let z_challenge = transcript.challenge_scalar(b"z");
@@ -361,12 +623,16 @@ impl StandardComposer {
             &domain,
             &preprocessed_circuit,
             &(alpha, beta, gamma, z_challenge),
+            &ecc_separation_challenge,
+            &fixed_base_separation_challenge,
             &w_l_poly,
             &w_r_poly,
             &w_o_poly,
             &w_4_poly,
             &t_poly,
             &z_poly,
+            lookup.as_ref().map(|(lookup, _)| lookup),
+            (&self.widgets, &custom_separation_challenges),
         );
 
         // Add evaluations to transcript
@@ -376,17 +642,28 @@ impl StandardComposer {
         transcript.append_scalar(b"d_eval", &evaluations.proof.d_eval);
         transcript.append_scalar(b"a_next_eval", &evaluations.proof.a_next_eval);
         transcript.append_scalar(b"b_next_eval", &evaluations.proof.b_next_eval);
+        transcript.append_scalar(b"c_next_eval", &evaluations.proof.c_next_eval);
         transcript.append_scalar(b"d_next_eval", &evaluations.proof.d_next_eval);
         transcript.append_scalar(b"left_sig_eval", &evaluations.proof.left_sigma_eval);
         transcript.append_scalar(b"right_sig_eval", &evaluations.proof.right_sigma_eval);
         transcript.append_scalar(b"out_sig_eval", &evaluations.proof.out_sigma_eval);
         transcript.append_scalar(b"q_arith_eval", &evaluations.proof.q_arith_eval);
         transcript.append_scalar(b"q_c_eval", &evaluations.proof.q_c_eval);
+        transcript.append_scalar(b"q_fixed_base_eval", &evaluations.proof.q_fixed_base_eval);
         transcript.append_scalar(b"perm_eval", &evaluations.proof.perm_eval);
+        if let Some(lookup) = &evaluations.lookup {
+            transcript.append_scalar(b"f_eval", &lookup.f_eval);
+            transcript.append_scalar(b"h_1_eval", &lookup.h_1_eval);
+            transcript.append_scalar(b"h_1_next_eval", &lookup.h_1_next_eval);
+            transcript.append_scalar(b"h_2_next_eval", &lookup.h_2_next_eval);
+            transcript.append_scalar(b"table_eval", &lookup.table_eval);
+            transcript.append_scalar(b"table_next_eval", &lookup.table_next_eval);
+            transcript.append_scalar(b"lookup_perm_eval", &lookup.lookup_perm_eval);
+        }
         transcript.append_scalar(b"t_eval", &evaluations.quot_eval);
         transcript.append_scalar(b"r_eval", &evaluations.proof.lin_poly_eval);
 
-        // 5. Compute Openings using KZG10
+        // 7. Compute Openings using KZG10
         //
         // We merge the quotient polynomial using the `z_challenge` so the SRS is linear in the circuit size `n`
         let quot = Self::compute_quotient_opening_poly(
@@ -399,45 +676,76 @@ impl StandardComposer {
         );
 
         // Compute aggregate witness to polynomials evaluated at the evaluation challenge `z`
-        let aggregate_witness = commit_key.compute_aggregate_witness(
-            &[
-                quot,
-                lin_poly,
-                w_l_poly.clone(),
-                w_r_poly.clone(),
-                w_o_poly,
-                w_4_poly.clone(),
-                preprocessed_circuit
-                    .permutation
-                    .left_sigma
-                    .polynomial
-                    .clone(),
-                preprocessed_circuit
-                    .permutation
-                    .right_sigma
-                    .polynomial
-                    .clone(),
-                preprocessed_circuit
-                    .permutation
-                    .out_sigma
-                    .polynomial
-                    .clone(),
-            ],
-            &z_challenge,
-            transcript,
+        let mut polys = vec![
+            quot,
+            lin_poly,
+            w_l_poly.clone(),
+            w_r_poly.clone(),
+            w_o_poly.clone(),
+            w_4_poly.clone(),
+            preprocessed_circuit
+                .permutation
+                .left_sigma
+                .polynomial
+                .clone(),
+            preprocessed_circuit
+                .permutation
+                .right_sigma
+                .polynomial
+                .clone(),
+            preprocessed_circuit
+                .permutation
+                .out_sigma
+                .polynomial
+                .clone(),
+            preprocessed_circuit.arithmetic.q_arith.polynomial.clone(),
+            preprocessed_circuit.arithmetic.q_c.polynomial.clone(),
+            preprocessed_circuit
+                .fixed_base
+                .q_fixed_base
+                .polynomial
+                .clone(),
+        ];
+        let mut shifted_polys = Self::shifted_witness_polys(
+            &z_poly,
+            (&w_l_poly, &w_r_poly, &w_o_poly, &w_4_poly),
+            lookup.as_ref().map(|(lookup, _)| lookup),
         );
-        let w_z_comm = commit_key.commit(&aggregate_witness).unwrap();
+        if let Some((lookup, _)) = &lookup {
+            polys.extend_from_slice(&[
+                lookup.f_poly.clone(),
+                lookup.h_1_poly.clone(),
+                lookup.table_poly.clone(),
+            ]);
+            shifted_polys.push(lookup.table_poly.clone());
+        }
+        let aggregate_witness =
+            commit_key.compute_aggregate_witness(&polys, &z_challenge, transcript);
+        let w_z_comm = Self::commit(commit_key, &aggregate_witness)?;
 
         // Compute aggregate witness to polynomials evaluated at the shifted evaluation challenge
         let shifted_aggregate_witness = commit_key.compute_aggregate_witness(
-            &[z_poly, w_l_poly, w_r_poly, w_4_poly],
+            &shifted_polys,
             &(z_challenge * domain.group_gen),
             transcript,
         );
-        let w_zx_comm = commit_key.commit(&shifted_aggregate_witness).unwrap();
+        let w_zx_comm = Self::commit(commit_key, &shifted_aggregate_witness)?;
+
+        let lookup = match (lookup, evaluations.lookup) {
+            (Some((_, [f_comm, h_1_comm, h_2_comm, z_2_comm])), Some(evaluations)) => {
+                Some(LookupProof {
+                    f_comm,
+                    h_1_comm,
+                    h_2_comm,
+                    z_2_comm,
+                    evaluations,
+                })
+            }
+            _ => None,
+        };
 
         // Create Proof
-        Proof {
+        Ok(Proof {
             a_comm: w_l_poly_commit,
             b_comm: w_r_poly_commit,
             c_comm: w_o_poly_commit,
@@ -454,12 +762,37 @@ impl StandardComposer {
             w_zw_comm: w_zx_comm,
 
             evaluations: evaluations.proof,
-        }
+            lookup,
+        })
     }
     /// Returns the number of gates in the circuit
     pub fn circuit_size(&self) -> usize {
         self.n
     }
+
+    /// Returns the maximum degree of the polynomials committed to when
+    /// preprocessing and proving the circuit, which is the degree the
+    /// `PublicParameters` must be trimmed to.
+    ///
+    /// For a domain of size `n`, the blinded witness and permutation
+    /// polynomials have degree `n + 2`, so the quotient polynomial has
+    /// degree up to `4n + 10` and its last piece degree up to `n + 10`.
+    pub fn required_srs_degree(&self) -> usize {
+        self.required_rows().next_power_of_two() + 10
+    }
+
+    // Returns the number of rows the evaluation domain must hold. Besides
+    // the gates, it must fit every row of the lookup tables, and the last
+    // row cannot hold a lookup gate since it closes the lookup accumulator.
+    fn required_rows(&self) -> usize {
+        let table_rows: usize = self.lookup_tables.iter().map(|table| table.len()).sum();
+        let lookup_rows = self
+            .q_lookup
+            .iter()
+            .rposition(|q| *q != Scalar::zero())
+            .map_or(0, |last| last + 2);
+        self.n.max(table_rows).max(lookup_rows)
+    }
 }
 
 impl Default for StandardComposer {
@@ -481,26 +814,300 @@ impl StandardComposer {
         StandardComposer::with_expected_size(0)
     }
 
+    /// Generates a new empty `StandardComposer` meant to be used only for
+    /// key generation.
+    ///
+    /// Every value given to `add_input` is treated as unknown and replaced
+    /// by zero, so gadgets build exactly the same selectors and wiring
+    /// without ever evaluating a witness. The resulting composer can be
+    /// preprocessed but not proven.
+    pub fn witness_free() -> Self {
+        let mut composer = StandardComposer::new();
+        composer.witness_free = true;
+        composer
+    }
+
+    /// Returns true if the composer was created with `witness_free` and
+    /// therefore holds no witness values.
+    pub fn is_witness_free(&self) -> bool {
+        self.witness_free
+    }
+
     /// Returns the public inputs that the `StandardComposer` has stored until
-    /// the time when this function is called as a `Vec<Scalar>`.
-    #[cfg(feature = "trace")]
+    /// the time when this function is called as a `Vec<Scalar>`, in the
+    /// order in which the verifier expects them.
     pub fn public_inputs(&self) -> Vec<Scalar> {
-        self.public_inputs.clone()
+        self.public_inputs_sparse_store.values().cloned().collect()
+    }
+
+    /// Returns the indexes of the gates that hold a public input.
+    pub fn pi_positions(&self) -> Vec<usize> {
+        self.public_inputs_sparse_store.keys().cloned().collect()
+    }
+
+    /// Constructs the dense public inputs vector, which holds a value for
+    /// every gate of the circuit.
+    pub(crate) fn construct_dense_pi_vec(&self) -> Vec<Scalar> {
+        let mut pi = vec![Scalar::zero(); self.n];
+        for (pos, value) in self.public_inputs_sparse_store.iter() {
+            pi[*pos] = *value;
+        }
+        pi
+    }
+
+    /// Records the public input of the gate that is about to be added, if any.
+    fn add_pi(&mut self, pi: Option<Scalar>) {
+        if let Some(pi) = pi {
+            self.public_inputs_sparse_store.insert(self.n, pi);
+        }
     }
 
-    /// Split `t(X)` poly into 3 degree `n` polynomials.
-    pub fn split_tx_poly(
+    /// Split `t(X)` poly into 4 polynomials and blind them.
+    ///
+    /// The first three pieces hold `n` coefficients each and the fourth one
+    /// holds the remainder. Each of the first three pieces gets a random
+    /// `b * X^n` term whose counterpart `-b` is subtracted from the constant
+    /// term of the next piece, so `t_1 + X^n t_2 + X^2n t_3 + X^3n t_4`
+    /// still equals `t(X)`.
+    pub fn split_tx_poly<R: RngCore + CryptoRng>(
         &self,
         n: usize,
         t_x: &Polynomial,
+        rng: &mut R,
+    ) -> (Polynomial, Polynomial, Polynomial, Polynomial) {
+        let mut coeffs = t_x.coeffs.clone();
+        if coeffs.len() < 3 * n + 1 {
+            coeffs.resize(3 * n + 1, Scalar::zero());
+        }
+
+        let mut t_1 = coeffs[0..n].to_vec();
+        let mut t_2 = coeffs[n..2 * n].to_vec();
+        let mut t_3 = coeffs[2 * n..3 * n].to_vec();
+        let mut t_4 = coeffs[3 * n..].to_vec();
+
+        let b_1 = util::random_scalar(rng);
+        let b_2 = util::random_scalar(rng);
+        let b_3 = util::random_scalar(rng);
+
+        t_1.push(b_1);
+        t_2[0] -= b_1;
+        t_2.push(b_2);
+        t_3[0] -= b_2;
+        t_3.push(b_3);
+        t_4[0] -= b_3;
+
+        (
+            Polynomial::from_coefficients_vec(t_1),
+            Polynomial::from_coefficients_vec(t_2),
+            Polynomial::from_coefficients_vec(t_3),
+            Polynomial::from_coefficients_vec(t_4),
+        )
+    }
+
+    /// Interpolates the wire polynomials from their evaluations over the
+    /// domain.
+    ///
+    /// Every wire is opened at both `z` and `z * omega`, so every
+    /// polynomial is blinded with three scalars.
+    fn compute_wire_polys<R: RngCore + CryptoRng>(
+        domain: &EvaluationDomain,
+        (w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar): (
+            &[Scalar],
+            &[Scalar],
+            &[Scalar],
+            &[Scalar],
+        ),
+        rng: &mut R,
     ) -> (Polynomial, Polynomial, Polynomial, Polynomial) {
+        let mut interpolate = |scalars: &[Scalar]| {
+            let poly = Polynomial::from_coefficients_vec(domain.ifft(scalars));
+            Self::blind_poly(&poly, 2, domain.size(), rng)
+        };
         (
-            Polynomial::from_coefficients_vec(t_x[0..n].to_vec()),
-            Polynomial::from_coefficients_vec(t_x[n..2 * n].to_vec()),
-            Polynomial::from_coefficients_vec(t_x[2 * n..3 * n].to_vec()),
-            Polynomial::from_coefficients_vec(t_x[3 * n..].to_vec()),
+            interpolate(w_l_scalar),
+            interpolate(w_r_scalar),
+            interpolate(w_o_scalar),
+            interpolate(w_4_scalar),
         )
     }
+
+    /// Computes the permutation polynomial `z`, which is blinded with three
+    /// scalars as it is opened at both `z` and `z * omega`.
+    fn compute_permutation_poly<R: RngCore + CryptoRng>(
+        &self,
+        domain: &EvaluationDomain,
+        (w_l_scalar, w_r_scalar, w_o_scalar, w_4_scalar): (
+            &[Scalar],
+            &[Scalar],
+            &[Scalar],
+            &[Scalar],
+        ),
+        challenges: &(Scalar, Scalar),
+        permutation: &PermutationWidget,
+        rng: &mut R,
+    ) -> Polynomial {
+        let z_poly = self.perm.compute_permutation_poly(
+            domain,
+            w_l_scalar,
+            w_r_scalar,
+            w_o_scalar,
+            w_4_scalar,
+            challenges,
+            (
+                &permutation.left_sigma.polynomial,
+                &permutation.right_sigma.polynomial,
+                &permutation.out_sigma.polynomial,
+                &permutation.fourth_sigma.polynomial,
+            ),
+        );
+        Self::blind_poly(&z_poly, 2, domain.size(), rng)
+    }
+
+    /// Returns the witness polynomials that are opened at `z * omega`, in
+    /// the order they are aggregated in. Each of them is opened twice, so
+    /// each of them must be blinded with three scalars.
+    ///
+    /// The table polynomial of the lookup argument is opened at `z * omega`
+    /// as well, but it is public and comes last.
+    fn shifted_witness_polys(
+        z_poly: &Polynomial,
+        (w_l_poly, w_r_poly, w_o_poly, w_4_poly): (
+            &Polynomial,
+            &Polynomial,
+            &Polynomial,
+            &Polynomial,
+        ),
+        lookup: Option<&LookupPolynomials>,
+    ) -> Vec<Polynomial> {
+        let mut polys = vec![
+            z_poly.clone(),
+            w_l_poly.clone(),
+            w_r_poly.clone(),
+            w_o_poly.clone(),
+            w_4_poly.clone(),
+        ];
+        if let Some(lookup) = lookup {
+            polys.extend_from_slice(&[
+                lookup.z_2_poly.clone(),
+                lookup.h_1_poly.clone(),
+                lookup.h_2_poly.clone(),
+            ]);
+        }
+        polys
+    }
+
+    /// Computes the polynomials of the lookup argument and commits to them,
+    /// drawing the lookup challenges from the transcript along the way.
+    ///
+    /// Returns the polynomials together with the commitments to `f`, `h_1`,
+    /// `h_2` and `z_2`, in that order.
+    fn compute_lookup_polynomials<R: RngCore + CryptoRng>(
+        &self,
+        commit_key: &ProverKey,
+        domain: &EvaluationDomain,
+        lookup_widget: &LookupWidget,
+        (w_l_scalar, w_r_scalar, w_o_scalar): (&[Scalar], &[Scalar], &[Scalar]),
+        transcript: &mut dyn TranscriptProtocol,
+        rng: &mut R,
+    ) -> Result<(LookupPolynomials, [Commitment; 4]), Error> {
+        // Compute table compression challenge; `zeta`
+        let zeta = transcript.challenge_scalar(b"zeta");
+
+        // Compress the lookup tables and the queries of the lookup gates,
+        // then sort the queries by the order of the table
+        let table_poly = lookup_widget.compute_table_poly(&zeta);
+        let table_scalar = domain.fft(&table_poly.coeffs);
+        let f_scalar = self.compute_lookup_queries(
+            (w_l_scalar, w_r_scalar, w_o_scalar),
+            &zeta,
+            table_scalar[0],
+        );
+        let (h_1_scalar, h_2_scalar) = lookup::compute_sorted_evals(&f_scalar, &table_scalar);
+
+        let f_poly = Polynomial::from_coefficients_vec(domain.ifft(&f_scalar));
+        let h_1_poly = Polynomial::from_coefficients_vec(domain.ifft(&h_1_scalar));
+        let h_2_poly = Polynomial::from_coefficients_vec(domain.ifft(&h_2_scalar));
+
+        // `h_1` and `h_2` are opened at `z * omega` as well
+        let f_poly = Self::blind_poly(&f_poly, 1, domain.size(), rng);
+        let h_1_poly = Self::blind_poly(&h_1_poly, 2, domain.size(), rng);
+        let h_2_poly = Self::blind_poly(&h_2_poly, 2, domain.size(), rng);
+
+        // Commit to lookup polynomials
+        let f_poly_commit = Self::commit(commit_key, &f_poly)?;
+        let h_1_poly_commit = Self::commit(commit_key, &h_1_poly)?;
+        let h_2_poly_commit = Self::commit(commit_key, &h_2_poly)?;
+
+        // Add lookup polynomial commitments to transcript
+        transcript.append_commitment(b"f", &f_poly_commit);
+        transcript.append_commitment(b"h_1", &h_1_poly_commit);
+        transcript.append_commitment(b"h_2", &h_2_poly_commit);
+
+        // Compute lookup accumulator challenges; `delta` and `epsilon`
+        let delta = transcript.challenge_scalar(b"delta");
+        let epsilon = transcript.challenge_scalar(b"epsilon");
+
+        let z_2_scalar = lookup::compute_accumulator_evals(
+            &f_scalar,
+            &table_scalar,
+            &h_1_scalar,
+            &h_2_scalar,
+            (&delta, &epsilon),
+        );
+        let z_2_poly = Polynomial::from_coefficients_vec(domain.ifft(&z_2_scalar));
+        let z_2_poly = Self::blind_poly(&z_2_poly, 2, domain.size(), rng);
+
+        // Commit to the lookup accumulator and add it to the transcript
+        let z_2_poly_commit = Self::commit(commit_key, &z_2_poly)?;
+        transcript.append_commitment(b"z_2", &z_2_poly_commit);
+
+        // Compute the challenge that separates the lookup constraints
+        let separation_challenge = transcript.challenge_scalar(b"lookup_separation_challenge");
+
+        Ok((
+            LookupPolynomials {
+                f_poly,
+                h_1_poly,
+                h_2_poly,
+                z_2_poly,
+                table_poly,
+                challenges: (zeta, delta, epsilon),
+                separation_challenge,
+            },
+            [
+                f_poly_commit,
+                h_1_poly_commit,
+                h_2_poly_commit,
+                z_2_poly_commit,
+            ],
+        ))
+    }
+
+    /// Blinds `poly` by adding `b(X) * Z_H(X)` to it, where `b(X)` is a random
+    /// polynomial of degree `hiding_degree` and `Z_H(X) = X^n - 1`.
+    ///
+    /// The evaluations over the domain are left untouched, so the blinded
+    /// polynomial still satisfies every circuit identity.
+    fn blind_poly<R: RngCore + CryptoRng>(
+        poly: &Polynomial,
+        hiding_degree: usize,
+        n: usize,
+        rng: &mut R,
+    ) -> Polynomial {
+        let mut coeffs = poly.coeffs.clone();
+        if coeffs.len() < n + hiding_degree + 1 {
+            coeffs.resize(n + hiding_degree + 1, Scalar::zero());
+        }
+
+        for i in 0..=hiding_degree {
+            let blinding_scalar = util::random_scalar(rng);
+            coeffs[i] -= blinding_scalar;
+            coeffs[n + i] += blinding_scalar;
+        }
+
+        Polynomial::from_coefficients_vec(coeffs)
+    }
+
     /// Fixes a variable in the witness to be a part of the circuit description.
     /// This method is (currently) only used in the following context:
     /// We have gates which only require 3/4 wires,
@@ -517,13 +1124,128 @@ impl StandardComposer {
             Scalar::zero(),
             Scalar::zero(),
             -value,
-            Scalar::zero(),
+            None,
         );
     }
 
     /// Convert variables to their actual witness values.
-    pub(crate) fn to_scalars(&self, vars: &[Variable]) -> Vec<Scalar> {
-        vars.par_iter().map(|var| self.variables[var]).collect()
+    ///
+    /// Returns an error if any of the variables was not allocated by this
+    /// composer.
+    pub(crate) fn to_scalars(&self, vars: &[Variable]) -> Result<Vec<Scalar>, Error> {
+        vars.par_iter()
+            .map(|var| {
+                self.variables
+                    .get(var)
+                    .copied()
+                    .ok_or(Error::UnknownVariable)
+            })
+            .collect()
+    }
+
+    /// Computes the polynomials, commitments and 8n evaluations of the
+    /// lookup selector and of the columns of the lookup tables.
+    fn preprocess_lookup(
+        &self,
+        commit_key: &ProverKey,
+        (domain, domain_8n): (&EvaluationDomain, &EvaluationDomain),
+        q_c: (Polynomial, Commitment, Option<Evaluations>),
+    ) -> Result<LookupWidget, Error> {
+        // A table column can be constant, so it is committed to as a selector
+        let preprocess = |evals: &[Scalar]| -> Result<_, Error> {
+            let poly = Polynomial::from_coefficients_vec(domain.ifft(evals));
+            let commit = Self::commit_selector(commit_key, &poly)?;
+            let eval_8n =
+                Evaluations::from_vec_and_domain(domain_8n.coset_fft(&poly.coeffs), *domain_8n);
+            Ok((poly, commit, Some(eval_8n)))
+        };
+
+        let (table_1, table_2, table_3, table_4) = self.lookup_table_columns(domain.size());
+        Ok(LookupWidget::new(
+            preprocess(&self.q_lookup)?,
+            q_c,
+            preprocess(&table_1)?,
+            preprocess(&table_2)?,
+            preprocess(&table_3)?,
+            preprocess(&table_4)?,
+        ))
+    }
+
+    /// Builds the columns of the lookup tables over a domain of `n` rows:
+    /// the three values of every row followed by the id of its table.
+    ///
+    /// The last row is repeated until the domain is filled.
+    fn lookup_table_columns(
+        &self,
+        n: usize,
+    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>) {
+        let mut rows: Vec<[Scalar; 4]> = self
+            .lookup_tables
+            .iter()
+            .enumerate()
+            .flat_map(|(id, table)| {
+                table
+                    .rows()
+                    .iter()
+                    .map(move |row| [row[0], row[1], row[2], Scalar::from(id as u64)])
+            })
+            .collect();
+        let last = rows.last().cloned().unwrap_or([Scalar::zero(); 4]);
+        rows.resize(n, last);
+
+        (
+            rows.iter().map(|row| row[0]).collect(),
+            rows.iter().map(|row| row[1]).collect(),
+            rows.iter().map(|row| row[2]).collect(),
+            rows.iter().map(|row| row[3]).collect(),
+        )
+    }
+
+    /// Computes the compressed query of every row of the domain. Rows
+    /// without a lookup gate query `default`, the first entry of the
+    /// compressed table, so that they are always found in it.
+    fn compute_lookup_queries(
+        &self,
+        (w_l, w_r, w_o): (&[Scalar], &[Scalar], &[Scalar]),
+        zeta: &Scalar,
+        default: Scalar,
+    ) -> Vec<Scalar> {
+        (0..w_l.len())
+            .map(|i| match self.q_lookup.get(i) {
+                Some(q) if *q != Scalar::zero() => {
+                    lookup::compress(&w_l[i], &w_r[i], &w_o[i], &self.q_c[i], zeta)
+                }
+                _ => default,
+            })
+            .collect()
+    }
+
+    /// Commits to `poly`, reporting a `commit_key` that is too small to
+    /// commit to it as `Error::SRSTooSmall`.
+    fn commit(commit_key: &ProverKey, poly: &Polynomial) -> Result<Commitment, Error> {
+        commit_key.commit(poly).map_err(|err| match err {
+            KZG10Error::PolynomialDegreeTooLarge => Error::SRSTooSmall {
+                required: poly.degree(),
+                available: commit_key.max_degree(),
+            },
+            err => err.into(),
+        })
+    }
+
+    /// Commits to a selector polynomial.
+    ///
+    /// The selectors of the gates that a circuit does not use are zero, so
+    /// they are committed to as the identity point. Since some selector
+    /// evaluations are opened in the proof, a selector that is a non-zero
+    /// constant is committed to as `c * G`.
+    fn commit_selector(commit_key: &ProverKey, poly: &Polynomial) -> Result<Commitment, Error> {
+        match Self::commit(commit_key, poly) {
+            Err(Error::KZG10(KZG10Error::PolynomialDegreeIsZero)) => match poly.coeffs.first() {
+                Some(c) => Ok(Commitment::from_projective(commit_key.powers_of_g[0] * c)),
+                None => Ok(Commitment::empty()),
+            },
+            res => res,
+        }
     }
 
     /// Computes the quotient opening polynomial.
@@ -565,7 +1287,18 @@ impl StandardComposer {
             q_arith: Vec::with_capacity(expected_size),
             q_range: Vec::with_capacity(expected_size),
             q_logic: Vec::with_capacity(expected_size),
-            public_inputs: Vec::with_capacity(expected_size),
+            q_ecc_add: Vec::with_capacity(expected_size),
+            q_fixed_base: Vec::with_capacity(expected_size),
+            q_lookup: Vec::with_capacity(expected_size),
+
+            lookup_tables: Vec::new(),
+
+            fixed_base_tables: HashMap::new(),
+
+            widgets: Vec::new(),
+            custom_selectors: Vec::new(),
+
+            public_inputs_sparse_store: BTreeMap::new(),
 
             w_l: Vec::with_capacity(expected_size),
             w_r: Vec::with_capacity(expected_size),
@@ -576,6 +1309,8 @@ impl StandardComposer {
 
             variables: HashMap::with_capacity(expected_size),
 
+            witness_free: false,
+
             perm: Permutation::new(),
         };
 
@@ -606,6 +1341,9 @@ impl StandardComposer {
         self.q_arith.extend(zeroes_scalar.iter());
         self.q_range.extend(zeroes_scalar.iter());
         self.q_logic.extend(zeroes_scalar.iter());
+        self.q_ecc_add.extend(zeroes_scalar.iter());
+        self.q_fixed_base.extend(zeroes_scalar.iter());
+        self.q_lookup.extend(zeroes_scalar.iter());
 
         self.w_l.extend(zeroes_var.iter());
         self.w_r.extend(zeroes_var.iter());
@@ -619,9 +1357,13 @@ impl StandardComposer {
     /// to generate and allocate a new variable `var`.
     /// The composer then links the Variable to the Scalar
     /// and returns the Variable for use in the system.
+    ///
+    /// On a `witness_free` composer the Scalar is ignored.
     pub fn add_input(&mut self, s: Scalar) -> Variable {
         // Get a new Variable from the permutation
         let var = self.perm.new_variable();
+        // Witness-free composers never hold the value of a variable
+        let s = if self.witness_free { Scalar::zero() } else { s };
         // The composer now links the Scalar to the Variable returned from the Permutation
         self.variables.insert(var, s);
 
@@ -640,7 +1382,7 @@ impl StandardComposer {
         q_r: Scalar,
         q_o: Scalar,
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.big_add_gate(
             a,
@@ -671,7 +1413,7 @@ impl StandardComposer {
         q_l_a: (Scalar, Variable),
         q_r_b: (Scalar, Variable),
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.big_add(q_l_a, q_r_b, (Scalar::zero(), self.zero_var), q_c, pi)
     }
@@ -692,7 +1434,7 @@ impl StandardComposer {
         q_r_b: (Scalar, Variable),
         q_4_d: (Scalar, Variable),
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         let q_l = q_l_a.0;
         let a = q_l_a.1;
@@ -709,7 +1451,11 @@ impl StandardComposer {
         let a_eval = self.variables[&a];
         let b_eval = self.variables[&b];
         let d_eval = self.variables[&d];
-        let c_eval = (q_l * a_eval) + (q_r * b_eval) + (q_4 * d_eval) + q_c + pi;
+        let c_eval = (q_l * a_eval)
+            + (q_r * b_eval)
+            + (q_4 * d_eval)
+            + q_c
+            + pi.unwrap_or_else(Scalar::zero);
         let c = self.add_input(c_eval);
 
         self.big_add_gate(a, b, c, d, q_l, q_r, q_o, q_4, q_c, pi)
@@ -733,7 +1479,7 @@ impl StandardComposer {
         q_o: Scalar,
         q_4: Scalar,
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.w_l.push(a);
         self.w_r.push(b);
@@ -752,8 +1498,11 @@ impl StandardComposer {
         self.q_arith.push(Scalar::one());
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
 
-        self.public_inputs.push(pi);
+        self.add_pi(pi);
 
         self.perm.add_variables_to_map(a, b, c, d, self.n);
 
@@ -775,7 +1524,7 @@ impl StandardComposer {
         q_m: Scalar,
         q_o: Scalar,
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.big_mul_gate(a, b, c, self.zero_var, q_m, q_o, q_c, Scalar::zero(), pi)
     }
@@ -800,7 +1549,7 @@ impl StandardComposer {
         q_o: Scalar,
         q_c: Scalar,
         q_4: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.w_l.push(a);
         self.w_r.push(b);
@@ -820,8 +1569,11 @@ impl StandardComposer {
 
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
 
-        self.public_inputs.push(pi);
+        self.add_pi(pi);
 
         self.perm.add_variables_to_map(a, b, c, d, self.n);
 
@@ -838,7 +1590,7 @@ impl StandardComposer {
         a: Variable,
         b: Variable,
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         self.big_mul(q_m, a, b, (Scalar::zero(), self.zero_var), q_c, pi)
     }
@@ -860,7 +1612,7 @@ impl StandardComposer {
         b: Variable,
         q_4_d: (Scalar, Variable),
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> Variable {
         let q_o = -Scalar::one();
 
@@ -871,7 +1623,8 @@ impl StandardComposer {
         let a_eval = self.variables[&a];
         let b_eval = self.variables[&b];
         let d_eval = self.variables[&d];
-        let c_eval = (q_m * a_eval * b_eval) + (q_4 * d_eval) + q_c + pi;
+        let c_eval =
+            (q_m * a_eval * b_eval) + (q_4 * d_eval) + q_c + pi.unwrap_or_else(Scalar::zero);
         let c = self.add_input(c_eval);
 
         self.big_mul_gate(a, b, c, d, q_m, q_o, q_c, q_4, pi)
@@ -895,7 +1648,7 @@ impl StandardComposer {
         q_r: Scalar,
         q_o: Scalar,
         q_c: Scalar,
-        pi: Scalar,
+        pi: Option<Scalar>,
     ) -> (Variable, Variable, Variable) {
         self.w_l.push(a);
         self.w_r.push(b);
@@ -913,8 +1666,11 @@ impl StandardComposer {
 
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
 
-        self.public_inputs.push(pi);
+        self.add_pi(pi);
 
         self.perm
             .add_variables_to_map(a, b, c, self.zero_var, self.n);
@@ -925,7 +1681,7 @@ impl StandardComposer {
 
     /// Adds a gate which is designed to constrain a `Variable` to have
     /// a specific constant value which is sent as a `Scalar`.
-    pub fn constrain_to_constant(&mut self, a: Variable, constant: Scalar, pi: Scalar) {
+    pub fn constrain_to_constant(&mut self, a: Variable, constant: Scalar, pi: Option<Scalar>) {
         self.poly_gate(
             a,
             a,
@@ -939,6 +1695,28 @@ impl StandardComposer {
         );
     }
 
+    /// Exposes the value of `a` as a public input of the circuit, returning
+    /// `a` so that it can keep being used.
+    ///
+    /// The verifier will then need to provide that same value, in the order
+    /// in which the public inputs were added, in order to verify the proof.
+    /// Forces `-a + PI = 0`.
+    pub fn add_public_input(&mut self, a: Variable) -> Variable {
+        let value = self.variables[&a];
+        self.poly_gate(
+            a,
+            a,
+            a,
+            Scalar::zero(),
+            -Scalar::one(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Some(value),
+        );
+        a
+    }
+
     /// Adds a boolean constraint (also known as binary constraint) where
     /// the gate eq. will enforce that the `Variable` received is either `0`
     /// or `1` by adding a constraint in the circuit.
@@ -961,8 +1739,9 @@ impl StandardComposer {
 
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
-
-        self.public_inputs.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
 
         self.perm
             .add_variables_to_map(a, a, a, self.zero_var, self.n);
@@ -974,6 +1753,10 @@ impl StandardComposer {
 
     /// Adds a range-constraint gate that checks and constrains a
     /// `Variable` to be inside of the range [0,num_bits].
+    ///
+    /// The witness is decomposed in quads, so an odd `num_bits` is checked
+    /// as `num_bits + 1` bits, with the topmost quad constrained to be a
+    /// single bit by a boolean gate.
     pub fn range_gate(&mut self, witness: Variable, num_bits: usize) {
         // Adds `variable` into the appropriate witness position
         // based on the accumulator number a_i
@@ -1005,8 +1788,10 @@ impl StandardComposer {
 
         // Note: A quad is a quaternary digit
         //
-        // Number of bits should be even, this means that user must pad the number of bits external.
-        assert!(num_bits % 2 == 0);
+        // An odd number of bits is padded to the next even number, and the
+        // extra bit is constrained to be zero once the quads are laid out.
+        let is_odd = num_bits & 1 == 1;
+        let num_bits = num_bits + (num_bits & 1);
 
         // Convert witness to bit representation and reverse
         let value = self.variables[&witness];
@@ -1106,7 +1891,9 @@ impl StandardComposer {
         self.q_4.extend(zeros.iter());
         self.q_range.extend(ones.iter());
         self.q_logic.extend(zeros.iter());
-        self.public_inputs.extend(zeros.iter());
+        self.q_ecc_add.extend(zeros.iter());
+        self.q_fixed_base.extend(zeros.iter());
+        self.q_lookup.extend(zeros.iter());
         self.n += used_gates;
 
         // As mentioned above, we must switch off the range constraint for the last gate
@@ -1124,30 +1911,42 @@ impl StandardComposer {
         let last_accumulator = accumulators.len() - 1;
         self.assert_equal(accumulators[last_accumulator], witness);
         accumulators[last_accumulator] = witness;
+
+        // The first accumulator holds the topmost quad, which for an odd
+        // number of bits must be a single bit
+        if is_odd {
+            self.bool_gate(accumulators[0]);
+        }
     }
 
     /// Performs a logical AND or XOR op between the inputs provided for the specified
     /// number of bits.
     ///
     /// Each logic gate adds `(num_bits / 2) + 1` gates to the circuit to perform the
-    /// whole operation.
+    /// whole operation. An odd `num_bits` is rounded up to the next even number,
+    /// and two boolean gates constrain the topmost quads of the inputs to be single bits.
+    ///
+    /// The last row of the accumulators holds `a` and `b` themselves, so the gate
+    /// also constrains both inputs, and therefore the result, to fit in `num_bits`.
+    ///
+    /// The result is returned along with the accumulators of its quads, from
+    /// the topmost one, which `logic_output_bits` splits in bits.
     ///
     /// ## Selector
     /// - is_xor_gate = 1 -> Performs XOR between the first `num_bits` for `a` and `b`.
     /// - is_xor_gate = 0 -> Performs AND between the first `num_bits` for `a` and `b`.
-    ///
-    /// ## Panics
-    /// This function will panic if the num_bits specified is not even `num_bits % 2 != 0`.
     pub(crate) fn logic_gate(
         &mut self,
         a: Variable,
         b: Variable,
         num_bits: usize,
         is_xor_gate: bool,
-    ) -> Variable {
-        // Since we work on base4, we need to guarantee that we have an even
-        // number of bits representing the greatest input.
-        assert_eq!(num_bits & 1, 0);
+    ) -> (Variable, Vec<Variable>) {
+        // Since we work on base4, we need an even number of bits representing
+        // the greatest input. An odd number is padded with one extra bit, which
+        // is constrained to be zero after the accumulators are laid out.
+        let is_odd = num_bits & 1 == 1;
+        let num_bits = num_bits + (num_bits & 1);
         // We will have exactly `num_bits / 2` quads (quaternary digits) representing
         // both numbers.
         let num_quads = num_bits >> 1;
@@ -1157,14 +1956,15 @@ impl StandardComposer {
         let mut out_accumulator = Scalar::zero();
         let mut left_quad: u8;
         let mut right_quad: u8;
+        // Variables holding the topmost quads of both inputs.
+        let mut top_quads = (self.zero_var, self.zero_var);
         // Get vars as bits and reverse them to get the Little Endian repr.
         let a_bit_iter = BitIterator8::new(self.variables[&a].to_bytes());
         let a_bits: Vec<_> = a_bit_iter.skip(256 - num_bits).collect();
         let b_bit_iter = BitIterator8::new(self.variables[&b].to_bytes());
         let b_bits: Vec<_> = b_bit_iter.skip(256 - num_bits).collect();
-        // XXX Doc this
-        assert!(a_bits.len() >= num_bits);
-        assert!(b_bits.len() >= num_bits);
+        // Accumulators of the result, from the one holding its topmost quad.
+        let mut out_accumulators = Vec::with_capacity(num_quads);
 
         // If we take a look to the program memory structure of the ref. impl.
         // * +-----+-----+-----+-----+
@@ -1247,9 +2047,6 @@ impl StandardComposer {
             //  c      - 4 . c  = | a      - 4 . a  | (& OR ^) | b      - 4 . b  |
             //   i + 1        i   \  i + 1        i /          \  i + 1        i /
             //
-            let prev_left_accum = left_accumulator;
-            let prev_right_accum = right_accumulator;
-            let prev_out_accum = out_accumulator;
             // We also need to add the computed quad fr_s to the circuit representing a logic gate.
             // To do so, we just mul by 4 the previous accomulated result and we add to it
             // the new computed quad.
@@ -1268,16 +2065,20 @@ impl StandardComposer {
             right_accumulator += right_quad_fr;
             out_accumulator *= Scalar::from(4u64);
             out_accumulator += out_quad_fr;
-            // Apply logic transition constraints.
-            assert!(left_accumulator - (prev_left_accum * Scalar::from(4u64)) < Scalar::from(4u64));
-            assert!(
-                right_accumulator - (prev_right_accum * Scalar::from(4u64)) < Scalar::from(4u64)
-            );
-            assert!(out_accumulator - (prev_out_accum * Scalar::from(4u64)) < Scalar::from(4u64));
 
             // Get variables pointing to the previous accumulated values.
-            let var_a = self.add_input(left_accumulator);
-            let var_b = self.add_input(right_accumulator);
+            // The accumulators of the last row are the inputs themselves, which
+            // links them to their quads and constrains them to fit in `num_bits`.
+            let (var_a, var_b) = match i == num_quads - 1 {
+                true => (a, b),
+                false => (
+                    self.add_input(left_accumulator),
+                    self.add_input(right_accumulator),
+                ),
+            };
+            if i == 0 {
+                top_quads = (var_a, var_b);
+            }
             // On the last row of the program memory, we need to pad the
             // output wire with a zero since we started to include it's
             // accumulators one gate before the other wire ones.
@@ -1286,6 +2087,7 @@ impl StandardComposer {
                 false => self.add_input(prod_quad_fr),
             };
             let var_4 = self.add_input(out_accumulator);
+            out_accumulators.push(var_4);
             // Add the variables to the variable map linking them to it's
             // corresponding gate index.
             //
@@ -1332,6 +2134,9 @@ impl StandardComposer {
             self.q_o.push(Scalar::zero());
             self.q_4.push(Scalar::zero());
             self.q_range.push(Scalar::zero());
+            self.q_ecc_add.push(Scalar::zero());
+            self.q_fixed_base.push(Scalar::zero());
+            self.q_lookup.push(Scalar::zero());
             match is_xor_gate {
                 true => {
                     self.q_c.push(-Scalar::one());
@@ -1353,582 +2158,3234 @@ impl StandardComposer {
         self.q_range.push(Scalar::zero());
         self.q_c.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
+
+        let out = self.w_4[self.w_4.len() - 1];
+
+        // For an odd number of bits, the topmost quads of the inputs hold a
+        // single bit each. The topmost quad of the output then also does, as
+        // the logic constraint computes it from the input quads.
+        if is_odd {
+            self.bool_gate(top_quads.0);
+            self.bool_gate(top_quads.1);
+        }
 
-        // We also need to extend the `public_inputs` Vec with
-        // zeros since the coeffs will not be added by the user as
-        // they are not needed.
-        //
-        // It makes no sense to allow the user introduce any kind of value
-        // in the middle of the logical gate iteration.
-        let zeros = vec![Scalar::zero(); num_quads + 1];
-        self.public_inputs.extend(zeros.iter());
-
-        // Now we need to assert that the sum of accumulated values
-        // matches the original values provided to the fn.
-        // Note that we're only considering the quads that are included
-        // in the range 0..num_bits. So, when actually executed, we're checking that
-        // x & ((1 << num_bits +1) -1) == [0..num_quads] accumulated sums of x.
-        //
-        // We could also check that the last gates wire coefficients match the
-        // original values introduced in the function.
-        // This can be done with an `assert_equal` constraint gate or simply
-        // by taking the values behind the n'th variables of `w_l` & `w_r` and
-        // checking that they're equal to the original ones behind the variables
-        // sent through the function parameters.
-        assert_eq!(self.variables[&a], self.variables[&self.w_l[self.n - 1]]);
-        assert_eq!(self.variables[&b], self.variables[&self.w_r[self.n - 1]]);
-
-        // Once the inputs are checked against the accumulated additions,
-        // we can safely return the resulting variable of the gate computation
-        // which is stored on the last program memory row and in the column that
-        // `w_4` is holding.
-        self.w_4[self.w_4.len() - 1]
+        // Since the inputs are the last accumulators, we can safely return the
+        // resulting variable of the gate computation which is stored on the last
+        // logic row and in the column that `w_4` is holding.
+        (out, out_accumulators)
     }
 
-    /// Adds a logical XOR gate that performs the XOR between two values for the
-    /// specified first `num_bits` returning a `Variable` holding the result.
+    /// Splits the result of a logic gate in its `num_bits` bits, least
+    /// significant first, given the accumulators of the result returned by
+    /// `logic_gate`.
     ///
-    /// # Panics
+    /// Every quad is split in two boolean constrained bits, checking that
+    /// `2 * high + low = acc_i - 4 * acc_(i-1)`, which adds `num_bits` boolean
+    /// gates and one addition gate per quad. The topmost quad of an odd
+    /// number of bits only has the low bit.
+    fn logic_output_bits(&mut self, accumulators: &[Variable], num_bits: usize) -> Vec<Variable> {
+        let is_odd = num_bits & 1 == 1;
+        let mut bits = Vec::with_capacity(num_bits);
+        let mut prev_accumulator = self.zero_var;
+        for (i, accumulator) in accumulators.iter().enumerate() {
+            let quad = (self.variables[accumulator]
+                - self.variables[&prev_accumulator] * Scalar::from(4u64))
+            .to_bytes()[0];
+
+            let high = match is_odd && i == 0 {
+                true => self.zero_var,
+                false => {
+                    let high = self.add_input(Scalar::from(u64::from(quad >> 1)));
+                    bits.push(high);
+                    self.bool_gate(high)
+                }
+            };
+            let low = self.add_input(Scalar::from(u64::from(quad & 1)));
+            bits.push(low);
+            self.bool_gate(low);
+
+            self.big_add_gate(
+                high,
+                low,
+                prev_accumulator,
+                *accumulator,
+                Scalar::from(2u64),
+                Scalar::one(),
+                Scalar::from(4u64),
+                -Scalar::one(),
+                Scalar::zero(),
+                None,
+            );
+            prev_accumulator = *accumulator;
+        }
+        bits.reverse();
+        bits
+    }
+
+    /// Adds a logical XOR gate that performs the XOR between two values for the
+    /// specified first `num_bits` returning a `Variable` holding the result, along
+    /// with its boolean constrained bits, least significant first.
     ///
-    /// If the `num_bits` specified in the fn params is odd.
-    pub fn logic_xor_gate(&mut self, a: Variable, b: Variable, num_bits: usize) -> Variable {
-        self.logic_gate(a, b, num_bits, true)
+    /// Both inputs are constrained to fit in `num_bits`, which can be odd, and the
+    /// result is recomposed from its quads, so it fits in `num_bits` as well.
+    pub fn logic_xor_gate(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        num_bits: usize,
+    ) -> (Variable, Vec<Variable>) {
+        let (out, accumulators) = self.logic_gate(a, b, num_bits, true);
+        (out, self.logic_output_bits(&accumulators, num_bits))
     }
 
     /// Adds a logical AND gate that performs the bitwise AND between two values
-    /// for the specified first `num_bits` returning a `Variable` holding the result.
-    ///
-    /// # Panics
+    /// for the specified first `num_bits` returning a `Variable` holding the result,
+    /// along with its boolean constrained bits, least significant first.
     ///
-    /// If the `num_bits` specified in the fn params is odd.
-    pub fn logic_and_gate(&mut self, a: Variable, b: Variable, num_bits: usize) -> Variable {
-        self.logic_gate(a, b, num_bits, false)
+    /// Both inputs are constrained to fit in `num_bits`, which can be odd, and the
+    /// result is recomposed from its quads, so it fits in `num_bits` as well.
+    pub fn logic_and_gate(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        num_bits: usize,
+    ) -> (Variable, Vec<Variable>) {
+        let (out, accumulators) = self.logic_gate(a, b, num_bits, false);
+        (out, self.logic_output_bits(&accumulators, num_bits))
     }
 
-    /// Asserts that two variables are the same
-    // XXX: Instead of wasting a gate, we can use the permutation polynomial to do this
-    pub fn assert_equal(&mut self, a: Variable, b: Variable) {
-        self.poly_gate(
-            a,
-            b,
-            self.zero_var,
-            Scalar::zero(),
-            Scalar::one(),
-            -Scalar::one(),
-            Scalar::zero(),
-            Scalar::zero(),
-            Scalar::zero(),
-        );
+    /// Adds a lookup table to the circuit, returning the id that the
+    /// `lookup_gate`s checking against it must be given.
+    ///
+    /// The rows of every table are committed to when preprocessing the
+    /// circuit, so the evaluation domain holds at least as many rows as
+    /// all of the tables together.
+    pub fn add_lookup_table(&mut self, table: LookupTable) -> usize {
+        self.lookup_tables.push(table);
+        self.lookup_tables.len() - 1
     }
 
+    /// Adds a lookup gate which checks that `(a, b, c)` is a row of the
+    /// table with the given id, eg. an 8-bit XOR takes a single gate when
+    /// checked against `LookupTable::xor(8)`.
+    ///
+    /// # Panics
+    ///
+    /// If no table with the given id was added to the composer.
+    pub fn lookup_gate(&mut self, a: Variable, b: Variable, c: Variable, table_id: usize) {
+        assert!(table_id < self.lookup_tables.len());
+
+        self.w_l.push(a);
+        self.w_r.push(b);
+        self.w_o.push(c);
+        self.w_4.push(self.zero_var);
+
+        self.q_m.push(Scalar::zero());
+        self.q_l.push(Scalar::zero());
+        self.q_r.push(Scalar::zero());
+        self.q_o.push(Scalar::zero());
+        // The id of the table is compressed along with the wires
+        self.q_c.push(Scalar::from(table_id as u64));
+        self.q_4.push(Scalar::zero());
+        self.q_arith.push(Scalar::zero());
 
-    /// This function is used to add a blinding factor to the witness polynomials
-    pub fn add_dummy_constraints(&mut self) {
-        // Add a dummy constraint so that we do not have zero polynomials
-        self.q_m.push(Scalar::from(1));
-        self.q_l.push(Scalar::from(2));
-        self.q_r.push(Scalar::from(3));
-        self.q_o.push(Scalar::from(4));
-        self.q_c.push(Scalar::from(4));
-        self.q_4.push(Scalar::one());
-        self.q_arith.push(Scalar::one());
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
-        self.public_inputs.push(Scalar::zero());
-        let var_six = self.add_input(Scalar::from(6));
-        let var_one = self.add_input(Scalar::from(1));
-        let var_seven = self.add_input(Scalar::from(7));
-        let var_min_twenty = self.add_input(-Scalar::from(20));
-        self.w_l.push(var_six);
-        self.w_r.push(var_seven);
-        self.w_o.push(var_min_twenty);
-        self.w_4.push(var_one);
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::one());
+
         self.perm
-            .add_variables_to_map(var_six, var_seven, var_min_twenty, var_one, self.n);
+            .add_variables_to_map(a, b, c, self.zero_var, self.n);
+
         self.n += 1;
-        //Add another dummy constraint so that we do not get the identity permutation
-        self.q_m.push(Scalar::from(1));
-        self.q_l.push(Scalar::from(1));
-        self.q_r.push(Scalar::from(1));
-        self.q_o.push(Scalar::from(1));
-        self.q_c.push(Scalar::from(127));
+    }
+
+    /// Adds a custom widget to the circuit, returning the id that the
+    /// `custom_gate`s using it must be given.
+    ///
+    /// The widgets must be added in the same order when preprocessing and
+    /// proving the circuit, and be given in that order to
+    /// `Proof::verify_with_widgets`.
+    pub fn add_widget(&mut self, widget: Box<dyn Widget>) -> usize {
+        self.custom_selectors
+            .push(vec![Vec::new(); widget.num_selectors()]);
+        self.widgets.push(widget);
+        self.widgets.len() - 1
+    }
+
+    /// Adds a gate whose identity is given by the custom widget with the
+    /// given id, setting its selectors to `selectors` and every other
+    /// selector to zero.
+    ///
+    /// The identity can refer to the wires of the next gate, which must then
+    /// be added right after this one.
+    ///
+    /// # Panics
+    ///
+    /// If no widget with the given id was added to the composer or if the
+    /// number of `selectors` does not match the widget.
+    pub fn custom_gate(
+        &mut self,
+        widget_id: usize,
+        a: Variable,
+        b: Variable,
+        c: Variable,
+        d: Variable,
+        selectors: &[Scalar],
+    ) {
+        assert!(widget_id < self.widgets.len());
+        assert_eq!(selectors.len(), self.widgets[widget_id].num_selectors());
+
+        self.w_l.push(a);
+        self.w_r.push(b);
+        self.w_o.push(c);
+        self.w_4.push(d);
+
+        self.q_m.push(Scalar::zero());
+        self.q_l.push(Scalar::zero());
+        self.q_r.push(Scalar::zero());
+        self.q_o.push(Scalar::zero());
+        self.q_c.push(Scalar::zero());
         self.q_4.push(Scalar::zero());
-        self.q_arith.push(Scalar::one());
+        self.q_arith.push(Scalar::zero());
+
         self.q_range.push(Scalar::zero());
         self.q_logic.push(Scalar::zero());
-        self.public_inputs.push(Scalar::zero());
-        self.w_l.push(var_min_twenty);
-        self.w_r.push(var_six);
-        self.w_o.push(var_seven);
-        self.w_4.push(self.zero_var);
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
+
+        for (column, selector) in self.custom_selectors[widget_id]
+            .iter_mut()
+            .zip(selectors.iter())
+        {
+            column.resize(self.n, Scalar::zero());
+            column.push(*selector);
+        }
+
+        self.perm.add_variables_to_map(a, b, c, d, self.n);
+
+        self.n += 1;
+    }
+
+    /// Adds a point addition gate that computes the sum of two points of
+    /// the Jubjub curve, `(x_1, y_1) + (x_2, y_2)`, returning the
+    /// `Variable`s that hold the coordinates of the resulting point.
+    ///
+    /// The addition takes two gates: the first one holds both points and
+    /// the second one holds the result along with `x_1 * y_2`. The gate
+    /// does not check that the inputs are points of the curve.
+    pub fn point_addition_gate(
+        &mut self,
+        a: (Variable, Variable),
+        b: (Variable, Variable),
+    ) -> (Variable, Variable) {
+        let zero = self.zero_var;
+        self.conditional_point_addition_gate(a, b, zero)
+    }
+
+    // Adds `b` to `a` unless the boolean `skip` is set, in which case the
+    // result is `a`. The bit takes the free output wire of the second gate
+    // of the addition, so skipping costs no extra gates.
+    fn conditional_point_addition_gate(
+        &mut self,
+        a: (Variable, Variable),
+        b: (Variable, Variable),
+        skip: Variable,
+    ) -> (Variable, Variable) {
+        let (x_1, y_1) = (self.variables[&a.0], self.variables[&a.1]);
+        let (x_2, y_2) = (self.variables[&b.0], self.variables[&b.1]);
+        let add = Scalar::one() - self.variables[&skip];
+
+        // Compute the resulting point with the twisted Edwards addition law
+        // for `a = -1`. The denominators never vanish for points of the curve
+        let x1_y2 = add * x_1 * y_2;
+        let y1_x2 = y_1 * x_2;
+        let k = EDWARDS_D * x1_y2 * y1_x2;
+        let x_3 = (x1_y2 + add * y1_x2 + (Scalar::one() - add) * x_1)
+            * (Scalar::one() + k).invert().unwrap_or(Scalar::zero());
+        let y_3 = (add * (y_1 * y_2 + x_1 * x_2) + (Scalar::one() - add) * y_1)
+            * (Scalar::one() - k).invert().unwrap_or(Scalar::zero());
+
+        let x1_y2 = self.add_input(x1_y2);
+        let x_3 = self.add_input(x_3);
+        let y_3 = self.add_input(y_3);
+
+        // The first gate holds both points
+        // | x_1 | y_1 | x_2 | y_2 |
+        self.w_l.push(a.0);
+        self.w_r.push(a.1);
+        self.w_o.push(b.0);
+        self.w_4.push(b.1);
+        self.perm.add_variables_to_map(a.0, a.1, b.0, b.1, self.n);
+        self.n += 1;
+
+        // The second gate holds the result, it only gets constrained
+        // through the `q_ecc_add` selector of the gate before it
+        // | x_3 | y_3 | skip | (1 - skip) * x_1 * y_2 |
+        self.w_l.push(x_3);
+        self.w_r.push(y_3);
+        self.w_o.push(skip);
+        self.w_4.push(x1_y2);
         self.perm
-            .add_variables_to_map(var_min_twenty, var_six, var_seven, self.zero_var, self.n);
+            .add_variables_to_map(x_3, y_3, skip, x1_y2, self.n);
+        self.n += 1;
+
+        for q_ecc_add in [Scalar::one(), Scalar::zero()].iter() {
+            self.q_m.push(Scalar::zero());
+            self.q_l.push(Scalar::zero());
+            self.q_r.push(Scalar::zero());
+            self.q_o.push(Scalar::zero());
+            self.q_c.push(Scalar::zero());
+            self.q_4.push(Scalar::zero());
+            self.q_arith.push(Scalar::zero());
+            self.q_range.push(Scalar::zero());
+            self.q_logic.push(Scalar::zero());
+            self.q_ecc_add.push(*q_ecc_add);
+            self.q_fixed_base.push(Scalar::zero());
+            self.q_lookup.push(Scalar::zero());
+        }
+
+        (x_3, y_3)
+    }
+
+    /// Adds a fixed base scalar multiplication gadget that computes
+    /// `scalar * generator` on the Jubjub curve, returning the `Variable`s
+    /// that hold the coordinates of the resulting point.
+    ///
+    /// The scalar is split into 2-bit windows and the four points that a
+    /// window can add are precomputed and stored in the selectors of its
+    /// gate, so that every two bits of the scalar cost a single gate. As in
+    /// the range gate, the scalar is accumulated in the fourth wire, one quad
+    /// per gate, and then checked against `scalar`. The table of a generator
+    /// is only computed the first time it is used by the composer.
+    ///
+    /// The value of `scalar` must fit in 252 bits, which is the case for
+    /// every Jubjub scalar, otherwise the circuit will not be satisfied.
+    ///
+    /// # Panics
+    ///
+    /// If `generator` is the identity or does not lie in the prime order
+    /// subgroup of the curve.
+    pub fn fixed_base_scalar_mul(
+        &mut self,
+        scalar: Variable,
+        generator: AffinePoint,
+    ) -> (Variable, Variable) {
+        let table = self.fixed_base_table(generator);
+
+        // Decompose the scalar into quads, most significant first
+        let scalar_bytes = self.variables[&scalar].to_bytes();
+        let quads: Vec<u8> = (0..JUBJUB_SCALAR_BITS / 2)
+            .rev()
+            .map(|i| (scalar_bytes[i >> 2] >> ((i & 3) << 1)) & 3)
+            .collect();
+
+        // The accumulated point starts at the offset of the table
+        let offset = (table.offset.get_u(), table.offset.get_v());
+        let (mut acc_x, mut acc_y) = (self.add_input(offset.0), self.add_input(offset.1));
+        self.constrain_to_constant(acc_x, offset.0, None);
+        self.constrain_to_constant(acc_y, offset.1, None);
+
+        let mut acc_point = ExtendedPoint::from(table.offset);
+        let mut acc_scalar = Scalar::zero();
+        let mut acc = self.zero_var;
+
+        for (quad, window) in quads.iter().zip(table.windows.iter()) {
+            // The quad picks the signed digit `2 * quad - 3` of the window
+            let alpha = match quad {
+                0 => -window.triple,
+                1 => -window.point,
+                2 => window.point,
+                _ => window.triple,
+            };
+            let xy_alpha = self.add_input(alpha.get_u() * alpha.get_v());
+
+            // | acc_x | acc_y | x_a * y_a | scalar acc |
+            self.w_l.push(acc_x);
+            self.w_r.push(acc_y);
+            self.w_o.push(xy_alpha);
+            self.w_4.push(acc);
+            self.perm
+                .add_variables_to_map(acc_x, acc_y, xy_alpha, acc, self.n);
+
+            self.q_m.push(window.q_m);
+            self.q_l.push(window.q_l);
+            self.q_r.push(window.q_r);
+            self.q_o.push(window.q_o);
+            self.q_c.push(window.q_c);
+            self.q_4.push(window.q_4);
+            self.q_arith.push(Scalar::zero());
+            self.q_range.push(Scalar::zero());
+            self.q_logic.push(Scalar::zero());
+            self.q_ecc_add.push(Scalar::zero());
+            self.q_fixed_base.push(Scalar::one());
+            self.q_lookup.push(Scalar::zero());
+            self.n += 1;
+
+            acc_point += ExtendedPoint::from(alpha);
+            acc_scalar = acc_scalar * Scalar::from(4u64) + Scalar::from(u64::from(*quad));
+
+            let acc_affine = AffinePoint::from(acc_point);
+            acc_x = self.add_input(acc_affine.get_u());
+            acc_y = self.add_input(acc_affine.get_v());
+            acc = self.add_input(acc_scalar);
+        }
+
+        // The last gate holds the result, it only gets constrained
+        // through the `q_fixed_base` selector of the gate before it
+        self.w_l.push(acc_x);
+        self.w_r.push(acc_y);
+        self.w_o.push(self.zero_var);
+        self.w_4.push(acc);
+        self.perm
+            .add_variables_to_map(acc_x, acc_y, self.zero_var, acc, self.n);
+
+        self.q_m.push(Scalar::zero());
+        self.q_l.push(Scalar::zero());
+        self.q_r.push(Scalar::zero());
+        self.q_o.push(Scalar::zero());
+        self.q_c.push(Scalar::zero());
+        self.q_4.push(Scalar::zero());
+        self.q_arith.push(Scalar::zero());
+        self.q_range.push(Scalar::zero());
+        self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
         self.n += 1;
+
+        // The accumulated quads must add up to the scalar we were given
+        self.assert_equal(acc, scalar);
+
+        (acc_x, acc_y)
+    }
+
+    // Returns the table of `fixed_base_scalar_mul` for `generator`, which
+    // is computed the first time the generator is used.
+    fn fixed_base_table(&mut self, generator: AffinePoint) -> FixedBaseTable {
+        self.fixed_base_tables
+            .entry(generator.to_bytes())
+            .or_insert_with(|| FixedBaseTable::new(generator))
+            .clone()
+    }
+
+    /// Adds a variable base scalar multiplication gadget that computes
+    /// `scalar * point` on the Jubjub curve, returning the `Variable`s that
+    /// hold the coordinates of the resulting point.
+    ///
+    /// `point` is constrained to be a point of the curve. The scalar is
+    /// decomposed into bits and the result is computed with double-and-add
+    /// using `point_addition_gate`, where the addition of `point` is skipped
+    /// by the point addition gate itself when the bit is not set. The gate
+    /// also constrains the bit to be boolean, so every bit costs five gates:
+    /// two for the doubling, two for the addition and one to accumulate the
+    /// bit into the scalar. Since the addition law of the curve is complete,
+    /// the identity and the points of small order need no special treatment.
+    ///
+    /// The value of `scalar` must fit in 252 bits, which is the case for
+    /// every Jubjub scalar, otherwise the circuit will not be satisfied.
+    pub fn variable_base_scalar_mul(
+        &mut self,
+        scalar: Variable,
+        point: (Variable, Variable),
+    ) -> (Variable, Variable) {
+        // Decompose the scalar, most significant bit first
+        let scalar_bytes = self.variables[&scalar].to_bytes();
+        let bits: Vec<u8> = (0..JUBJUB_SCALAR_BITS)
+            .rev()
+            .map(|i| (scalar_bytes[i >> 3] >> (i & 7)) & 1)
+            .collect();
+
+        // The accumulated point starts at the identity, `(0, 1)`
+        let one = self.add_input(Scalar::one());
+        self.constrain_to_constant(one, Scalar::one(), None);
+
+        self.assert_on_curve(point);
+
+        let mut acc_point = (self.zero_var, one);
+        let mut acc = self.zero_var;
+
+        for bit in bits.iter() {
+            // The point addition gate skips the addition when the bit is
+            // not set, so it takes its complement `1 - bit`
+            let skip = self.add_input(Scalar::from(u64::from(1 - *bit)));
+            acc = self.add(
+                (Scalar::from(2u64), acc),
+                (-Scalar::one(), skip),
+                Scalar::one(),
+                None,
+            );
+
+            acc_point = self.point_addition_gate(acc_point, acc_point);
+            acc_point = self.conditional_point_addition_gate(acc_point, point, skip);
+        }
+
+        // The accumulated bits must add up to the scalar we were given
+        self.assert_equal(acc, scalar);
+
+        acc_point
+    }
+
+    /// Adds a Poseidon hash gadget that hashes a variable-length slice of
+    /// `Variable`s, returning the `Variable` that holds the hash.
+    ///
+    /// The result matches the one of the native `Poseidon::hash`. Use a
+    /// `SpongeGadget` instead when the inputs are not all known at once.
+    pub fn poseidon_hash(&mut self, inputs: &[Variable]) -> Variable {
+        let mut sponge = SpongeGadget::new(self);
+        for input in inputs {
+            sponge.absorb(self, *input);
+        }
+        sponge.squeeze(self)
+    }
+
+    /// Adds a SHA-256 gadget that hashes a variable-length slice of bytes,
+    /// returning the `32` `Variable`s holding the bytes of the digest.
+    ///
+    /// Every input is range checked to fit in a byte. The length of the
+    /// message is fixed by the circuit, and every block of `64` bytes of the
+    /// padded message adds `sha256::COMPRESSION_GATES` gates to it.
+    pub fn sha256(&mut self, bytes: &[Variable]) -> Vec<Variable> {
+        let bytes: Vec<UInt8> = bytes
+            .iter()
+            .map(|byte| UInt8::from_variable(self, *byte))
+            .collect();
+        sha256::hash(self, &bytes)
+            .iter()
+            .map(|byte| byte.variable())
+            .collect()
+    }
+
+    /// Adds a BLAKE2s gadget that hashes a variable-length slice of bytes
+    /// under the given `8`-byte personalization, returning the `32`
+    /// `Variable`s holding the bytes of the digest.
+    ///
+    /// Every input is range checked to fit in a byte. The result matches the
+    /// one of the native `blake2s::hash`.
+    pub fn blake2s(&mut self, bytes: &[Variable], personalization: &[u8; 8]) -> Vec<Variable> {
+        let bytes: Vec<UInt8> = bytes
+            .iter()
+            .map(|byte| UInt8::from_variable(self, *byte))
+            .collect();
+        blake2s::hash_gadget(self, &bytes, personalization)
+            .iter()
+            .map(|byte| byte.variable())
+            .collect()
+    }
+
+    /// Adds a Merkle tree membership gadget that proves that `leaf` belongs
+    /// to the tree with the given `root`, following the authentication
+    /// `path` of the leaf.
+    ///
+    /// At every level, the direction of the path is constrained with a
+    /// `bool_gate` and used to swap the node and its sibling before they
+    /// are hashed together with `poseidon_hash`. The computed root is
+    /// checked against `root`, which is added as a public input of the
+    /// circuit, so the verifier has to provide it to verify the proof.
+    pub fn merkle_membership(&mut self, leaf: Variable, path: &MerklePath, root: Scalar) {
+        let mut node = leaf;
+        for (sibling, is_right) in path.siblings.iter().zip(path.directions.iter()) {
+            let sibling = self.add_input(*sibling);
+            let direction = self.add_input(Scalar::from(u64::from(*is_right)));
+            self.bool_gate(direction);
+
+            // Swap the node and its sibling if the node is the right child
+            let (left, right) = self.conditional_swap(direction, node, sibling);
+
+            node = self.poseidon_hash(&[left, right]);
+        }
+
+        // Forces `-node + root = 0`, with `root` as the public input
+        self.poly_gate(
+            node,
+            node,
+            node,
+            Scalar::zero(),
+            -Scalar::one(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Some(root),
+        );
+    }
+
+    /// Adds a Pedersen commitment gadget that computes `value * G + blinder * H`
+    /// on the Jubjub curve, with the generators of the `pedersen` module,
+    /// returning the `Variable`s that hold the coordinates of the commitment.
+    ///
+    /// Both products are computed with `fixed_base_scalar_mul` and added with
+    /// `point_addition_gate`, so the result matches the native
+    /// `pedersen::commit`. Since `value` is a plain `Variable`, it can also be
+    /// given to other gadgets, eg. to `range_gate` to prove that the committed
+    /// amount fits in `64` bits.
+    pub fn pedersen_commitment(
+        &mut self,
+        value: Variable,
+        blinder: Variable,
+    ) -> (Variable, Variable) {
+        let value_point = self.fixed_base_scalar_mul(value, pedersen::value_generator());
+        let blinding_point = self.fixed_base_scalar_mul(blinder, pedersen::blinding_generator());
+        self.point_addition_gate(value_point, blinding_point)
+    }
+
+    /// Adds a gadget that verifies the Schnorr signature `(r, s)` of
+    /// `message` under `public_key`, as produced by `schnorr::SecretKey::sign`.
+    ///
+    /// The challenge is computed with `poseidon_hash` and split in its
+    /// lowest `250` bits, which form the challenge `c`, and the bits above
+    /// them, both being range checked. The gadget then checks that
+    /// `s * G = r + c * public_key`, computing `s * G` with
+    /// `fixed_base_scalar_mul`, `c * public_key` with
+    /// `variable_base_scalar_mul` and adding `r` with `point_addition_gate`.
+    /// `r` is constrained to be a point of the curve, `public_key` to be a
+    /// point of prime order, which rules out the identity and the points of
+    /// small order, and `s` to be smaller than the order of the Jubjub
+    /// scalar field.
+    ///
+    /// Whether the public key and the message are public inputs is left to
+    /// the caller, eg. with `add_public_input`.
+    pub fn schnorr_verify(
+        &mut self,
+        public_key: (Variable, Variable),
+        message: Variable,
+        r: (Variable, Variable),
+        s: Variable,
+    ) {
+        self.assert_prime_order(public_key);
+        self.assert_on_curve(r);
+
+        // `fixed_base_scalar_mul` only bounds `s` by `2^252`, so the gadget
+        // also range checks `order - 1 - s`, which wraps around the modulus
+        // when `s` is not below the order of the Jubjub scalar field
+        let max_s = Scalar::from_bytes(&(-JubJubScalar::one()).to_bytes()).unwrap();
+        let s_gap = self.add(
+            (-Scalar::one(), s),
+            (Scalar::zero(), self.zero_var),
+            max_s,
+            None,
+        );
+        self.range_gate(s_gap, JUBJUB_SCALAR_BITS);
+
+        let hash = self.poseidon_hash(&[r.0, r.1, public_key.0, public_key.1, message]);
+        let (high, c) = schnorr::split_hash(&self.variables[&hash]);
+        let high = self.add_input(high);
+        let c = self.add_input(c);
+        // Forces `2^250 * high + c - hash = 0`. Since the hash then fits in
+        // 254 bits, which is below the modulus, the split is unique
+        let two_pow = Scalar::from(2u64).pow(&[schnorr::CHALLENGE_BITS as u64, 0, 0, 0]);
+        self.big_add_gate(
+            high,
+            c,
+            hash,
+            self.zero_var,
+            two_pow,
+            Scalar::one(),
+            -Scalar::one(),
+            Scalar::zero(),
+            Scalar::zero(),
+            None,
+        );
+        self.range_gate(high, schnorr::HASH_HIGH_BITS);
+        self.range_gate(c, schnorr::CHALLENGE_BITS);
+
+        let s_g = self.fixed_base_scalar_mul(s, GENERATOR);
+        let c_public_key = self.variable_base_scalar_mul(c, public_key);
+        let (x, y) = self.point_addition_gate(r, c_public_key);
+        self.assert_equal(s_g.0, x);
+        self.assert_equal(s_g.1, y);
+    }
+
+    // Constrains `(x, y)` to be a point of prime order of the Jubjub curve.
+    //
+    // The prover gives the point `q = (x, y) / 8`, which is checked to lie
+    // on the curve and to give back `(x, y)` after three doublings. Since
+    // the cofactor is `8`, that puts `(x, y)` in the prime order subgroup,
+    // where the identity is the only point with `x = 0`.
+    fn assert_prime_order(&mut self, point: (Variable, Variable)) {
+        let (x, y) = (self.variables[&point.0], self.variables[&point.1]);
+        let mut bytes = y.to_bytes();
+        bytes[31] |= (x.to_bytes()[0] & 1) << 7;
+        let point_eval = AffinePoint::from_bytes(bytes);
+        let q = if bool::from(point_eval.is_some()) {
+            let inv_8 = JubJubScalar::from(8u64).invert().unwrap();
+            AffinePoint::from(ExtendedPoint::from(point_eval.unwrap()) * inv_8)
+        } else {
+            AffinePoint::identity()
+        };
+        let q = (self.add_input(q.get_u()), self.add_input(q.get_v()));
+        self.assert_on_curve(q);
+
+        let q_2 = self.point_addition_gate(q, q);
+        let q_4 = self.point_addition_gate(q_2, q_2);
+        let q_8 = self.point_addition_gate(q_4, q_4);
+        self.assert_equal(q_8.0, point.0);
+        self.assert_equal(q_8.1, point.1);
+
+        let x_is_zero = self.is_zero(point.0);
+        self.constrain_to_constant(x_is_zero, Scalar::zero(), None);
+    }
+
+    // Constrains `(x, y)` to be a point of the Jubjub curve, which forces
+    // `-x^2 + y^2 - 1 - d * x^2 * y^2 = 0`
+    fn assert_on_curve(&mut self, point: (Variable, Variable)) {
+        let (x, y) = point;
+        let x_sq = self.mul(Scalar::one(), x, x, Scalar::zero(), None);
+        let y_sq = self.mul(Scalar::one(), y, y, Scalar::zero(), None);
+        self.big_mul_gate(
+            x_sq,
+            y_sq,
+            x_sq,
+            y_sq,
+            -EDWARDS_D,
+            -Scalar::one(),
+            -Scalar::one(),
+            Scalar::one(),
+            None,
+        );
+    }
+
+    /// Asserts that two variables are the same
+    // XXX: Instead of wasting a gate, we can use the permutation polynomial to do this
+    pub fn assert_equal(&mut self, a: Variable, b: Variable) {
+        self.poly_gate(
+            a,
+            b,
+            self.zero_var,
+            Scalar::zero(),
+            Scalar::one(),
+            -Scalar::one(),
+            Scalar::zero(),
+            Scalar::zero(),
+            None,
+        );
+    }
+
+    /// Adds a gadget that returns `a` if `bit` is one and `b` if it is
+    /// zero, computed as `b + bit * (a - b)`.
+    ///
+    /// `bit` must already be constrained to be boolean, eg. with `bool_gate`.
+    pub fn conditional_select(&mut self, bit: Variable, a: Variable, b: Variable) -> Variable {
+        let diff = self.add(
+            (Scalar::one(), a),
+            (-Scalar::one(), b),
+            Scalar::zero(),
+            None,
+        );
+        self.big_mul(
+            Scalar::one(),
+            bit,
+            diff,
+            (Scalar::one(), b),
+            Scalar::zero(),
+            None,
+        )
+    }
+
+    /// Adds a gadget that returns `(b, a)` if `bit` is one and `(a, b)` if
+    /// it is zero.
+    ///
+    /// `bit` must already be constrained to be boolean, eg. with `bool_gate`.
+    pub fn conditional_swap(
+        &mut self,
+        bit: Variable,
+        a: Variable,
+        b: Variable,
+    ) -> (Variable, Variable) {
+        // `a + bit * (b - a)` and `b - bit * (b - a)`
+        let diff = self.add(
+            (Scalar::one(), b),
+            (-Scalar::one(), a),
+            Scalar::zero(),
+            None,
+        );
+        let first = self.big_mul(
+            Scalar::one(),
+            bit,
+            diff,
+            (Scalar::one(), a),
+            Scalar::zero(),
+            None,
+        );
+        let second = self.big_mul(
+            -Scalar::one(),
+            bit,
+            diff,
+            (Scalar::one(), b),
+            Scalar::zero(),
+            None,
+        );
+        (first, second)
+    }
+
+    /// Adds a gadget that returns `1 - a`, the negation of the bit `a`.
+    ///
+    /// `a` must already be constrained to be boolean, which makes the
+    /// result boolean as well.
+    pub fn boolean_not(&mut self, a: Variable) -> Variable {
+        self.add(
+            (-Scalar::one(), a),
+            (Scalar::zero(), self.zero_var),
+            Scalar::one(),
+            None,
+        )
+    }
+
+    /// Adds a gadget that returns `a * b`, the conjunction of the bits `a`
+    /// and `b`.
+    ///
+    /// `a` and `b` must already be constrained to be boolean, which makes
+    /// the result boolean as well.
+    pub fn boolean_and(&mut self, a: Variable, b: Variable) -> Variable {
+        self.binary_boolean_gate(a, b, Scalar::one(), Scalar::zero())
+    }
+
+    /// Adds a gadget that returns `a + b - a * b`, the disjunction of the
+    /// bits `a` and `b`.
+    ///
+    /// `a` and `b` must already be constrained to be boolean, which makes
+    /// the result boolean as well.
+    pub fn boolean_or(&mut self, a: Variable, b: Variable) -> Variable {
+        self.binary_boolean_gate(a, b, -Scalar::one(), Scalar::one())
+    }
+
+    /// Adds a gadget that returns `a + b - 2 * a * b`, the exclusive
+    /// disjunction of the bits `a` and `b`.
+    ///
+    /// `a` and `b` must already be constrained to be boolean, which makes
+    /// the result boolean as well.
+    pub fn boolean_xor(&mut self, a: Variable, b: Variable) -> Variable {
+        self.binary_boolean_gate(a, b, -Scalar::from(2u64), Scalar::one())
+    }
+
+    // Adds a gate whose output is `q_m * a * b + q_lr * (a + b)`
+    fn binary_boolean_gate(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        q_m: Scalar,
+        q_lr: Scalar,
+    ) -> Variable {
+        let a_eval = self.variables[&a];
+        let b_eval = self.variables[&b];
+        let c = self.add_input(q_m * a_eval * b_eval + q_lr * (a_eval + b_eval));
+        self.poly_gate(
+            a,
+            b,
+            c,
+            q_m,
+            q_lr,
+            q_lr,
+            -Scalar::one(),
+            Scalar::zero(),
+            None,
+        );
+        c
+    }
+
+    /// Adds a gadget that returns a bit which is one if `a` is zero and
+    /// zero otherwise.
+    ///
+    /// The prover gives the inverse of `a`, or zero if there is none, as a
+    /// hint `inv`. The gates force `out = 1 - a * inv` and `a * out = 0`,
+    /// so `out` is zero whenever `a` is not, and one when `a` is zero,
+    /// whatever the hint is.
+    pub fn is_zero(&mut self, a: Variable) -> Variable {
+        let a_eval = self.variables[&a];
+        let inv = if a_eval == Scalar::zero() {
+            Scalar::zero()
+        } else {
+            a_eval.invert().unwrap()
+        };
+        let inv = self.add_input(inv);
+
+        let out = self.mul(-Scalar::one(), a, inv, Scalar::one(), None);
+        self.poly_gate(
+            a,
+            out,
+            self.zero_var,
+            Scalar::one(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Scalar::zero(),
+            Scalar::zero(),
+            None,
+        );
+        out
+    }
+
+    /// Adds a gadget that returns a bit which is one if `a` and `b` hold
+    /// the same value and zero otherwise, using `is_zero` on `a - b`.
+    pub fn is_equal(&mut self, a: Variable, b: Variable) -> Variable {
+        let diff = self.add(
+            (Scalar::one(), a),
+            (-Scalar::one(), b),
+            Scalar::zero(),
+            None,
+        );
+        self.is_zero(diff)
+    }
+
+    /// Adds a gadget that returns a bit which is one if `a < b` and zero
+    /// otherwise.
+    ///
+    /// Both `a` and `b` must already be constrained to fit in `num_bits`
+    /// bits, eg. with `range_gate`, otherwise the result is meaningless.
+    ///
+    /// The gadget decomposes `a - b + 2^num_bits` into its top bit, which
+    /// is set if and only if `a >= b`, and the `num_bits` bits below it,
+    /// which are range checked.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits` is zero or larger than `250`.
+    pub fn less_than(&mut self, a: Variable, b: Variable, num_bits: usize) -> Variable {
+        assert!(num_bits > 0 && num_bits <= MAX_COMPARISON_BITS);
+        let two_pow = Scalar::from(2u64).pow(&[num_bits as u64, 0, 0, 0]);
+
+        let diff = self.add((Scalar::one(), a), (-Scalar::one(), b), two_pow, None);
+
+        let diff_bytes = self.variables[&diff].to_bytes();
+        let top = (diff_bytes[num_bits >> 3] >> (num_bits & 7)) & 1;
+        let top = self.add_input(Scalar::from(u64::from(top)));
+        self.bool_gate(top);
+
+        let low = self.add((Scalar::one(), diff), (-two_pow, top), Scalar::zero(), None);
+        self.range_gate(low, num_bits);
+
+        self.boolean_not(top)
+    }
+
+    /// Adds a gadget that constrains `a` to be smaller than `b`, by range
+    /// checking `b - a - 1`, which is cheaper than `less_than`.
+    ///
+    /// Both `a` and `b` must already be constrained to fit in `num_bits`
+    /// bits, eg. with `range_gate`.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits` is zero or larger than `250`.
+    pub fn assert_less_than(&mut self, a: Variable, b: Variable, num_bits: usize) {
+        assert!(num_bits > 0 && num_bits <= MAX_COMPARISON_BITS);
+        let diff = self.add(
+            (Scalar::one(), b),
+            (-Scalar::one(), a),
+            -Scalar::one(),
+            None,
+        );
+        self.range_gate(diff, num_bits);
+    }
+
+    /// Adds a gadget that constrains `x` to lie in the range
+    /// `[lower, upper]`, both bounds included.
+    ///
+    /// With `num_bits` the number of bits of `upper - lower`, the gadget
+    /// range checks both `x - lower` and `upper - x` to `num_bits` bits.
+    /// If `x` was out of the range, one of them would wrap around the
+    /// modulus and not fit.
+    ///
+    /// # Panics
+    ///
+    /// If `upper - lower` does not fit in `250` bits, which is the case when
+    /// `lower` is larger than `upper`.
+    pub fn assert_in_range(&mut self, x: Variable, lower: Scalar, upper: Scalar) {
+        let num_bits = util::num_bits(&(upper - lower));
+        assert!(num_bits <= MAX_COMPARISON_BITS);
+        let num_bits = num_bits.max(1);
+
+        let above_lower = self.add(
+            (Scalar::one(), x),
+            (Scalar::zero(), self.zero_var),
+            -lower,
+            None,
+        );
+        self.range_gate(above_lower, num_bits);
+
+        let below_upper = self.add(
+            (-Scalar::one(), x),
+            (Scalar::zero(), self.zero_var),
+            upper,
+            None,
+        );
+        self.range_gate(below_upper, num_bits);
+    }
+
+    /// This function is used to add a blinding factor to the witness polynomials
+    pub fn add_dummy_constraints(&mut self) {
+        // Add a dummy constraint so that we do not have zero polynomials
+        self.q_m.push(Scalar::from(1));
+        self.q_l.push(Scalar::from(2));
+        self.q_r.push(Scalar::from(3));
+        self.q_o.push(Scalar::from(4));
+        self.q_c.push(Scalar::from(4));
+        self.q_4.push(Scalar::one());
+        self.q_arith.push(Scalar::one());
+        self.q_range.push(Scalar::zero());
+        self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
+        let var_six = self.add_input(Scalar::from(6));
+        let var_one = self.add_input(Scalar::from(1));
+        let var_seven = self.add_input(Scalar::from(7));
+        let var_min_twenty = self.add_input(-Scalar::from(20));
+        self.w_l.push(var_six);
+        self.w_r.push(var_seven);
+        self.w_o.push(var_min_twenty);
+        self.w_4.push(var_one);
+        self.perm
+            .add_variables_to_map(var_six, var_seven, var_min_twenty, var_one, self.n);
+        self.n += 1;
+        //Add another dummy constraint so that we do not get the identity permutation
+        self.q_m.push(Scalar::from(1));
+        self.q_l.push(Scalar::from(1));
+        self.q_r.push(Scalar::from(1));
+        self.q_o.push(Scalar::from(1));
+        self.q_c.push(Scalar::from(127));
+        self.q_4.push(Scalar::zero());
+        self.q_arith.push(Scalar::one());
+        self.q_range.push(Scalar::zero());
+        self.q_logic.push(Scalar::zero());
+        self.q_ecc_add.push(Scalar::zero());
+        self.q_fixed_base.push(Scalar::zero());
+        self.q_lookup.push(Scalar::zero());
+        self.w_l.push(var_min_twenty);
+        self.w_r.push(var_six);
+        self.w_o.push(var_seven);
+        self.w_4.push(self.zero_var);
+        self.perm
+            .add_variables_to_map(var_min_twenty, var_six, var_seven, self.zero_var, self.n);
+        self.n += 1;
+    }
+
+    /// Utility function that allows to check on the "front-end"
+    /// side of the PLONK implementation if the identity polynomial
+    /// is satisfied for each one of the `StandardComposer`'s gates.
+    #[cfg(feature = "trace")]
+    pub fn check_circuit_satisfied(&self) {
+        let w_l = self.to_scalars(&self.w_l).unwrap();
+        let w_r = self.to_scalars(&self.w_r).unwrap();
+        let w_o = self.to_scalars(&self.w_o).unwrap();
+        let w_4 = self.to_scalars(&self.w_4).unwrap();
+        let public_inputs = self.construct_dense_pi_vec();
+        // Computes f(f-1)(f-2)(f-3)
+        let delta = |f: Scalar| -> Scalar {
+            let f_1 = f - Scalar::one();
+            let f_2 = f - Scalar::from(2);
+            let f_3 = f - Scalar::from(3);
+            f * f_1 * f_2 * f_3
+        };
+        let four = Scalar::from(4);
+
+        for i in 0..self.n {
+            let qm = self.q_m[i];
+            let ql = self.q_l[i];
+            let qr = self.q_r[i];
+            let qo = self.q_o[i];
+            let qc = self.q_c[i];
+            let q4 = self.q_4[i];
+            let qarith = self.q_arith[i];
+            let qrange = self.q_range[i];
+            let qlogic = self.q_logic[i];
+            let qecc_add = self.q_ecc_add[i];
+            let qfixed_base = self.q_fixed_base[i];
+            let qlookup = self.q_lookup[i];
+            let pi = public_inputs[i];
+
+            let a = w_l[i];
+            let a_next = w_l[(i + 1) % self.n];
+            let b = w_r[i];
+            let b_next = w_r[(i + 1) % self.n];
+            let c = w_o[i];
+            let c_next = w_o[(i + 1) % self.n];
+            let d = w_4[i];
+            let d_next = w_4[(i + 1) % self.n];
+            #[cfg(feature = "trace-print")]
+            println!(
+                "--------------------------------------------\n
+            #Gate Index = {}
+            #Selector Polynomials:\n
+            - qm -> {:?}\n
+            - ql -> {:?}\n
+            - qr -> {:?}\n
+            - q4 -> {:?}\n
+            - qo -> {:?}\n
+            - qc -> {:?}\n
+            - q_arith -> {:?}\n
+            - q_range -> {:?}\n
+            - q_logic -> {:?}\n
+            - q_ecc_add -> {:?}\n
+            - q_fixed_base -> {:?}\n
+            - q_lookup -> {:?}\n
+            # Witness polynomials:\n
+            - w_l -> {:?}\n
+            - w_r -> {:?}\n
+            - w_o -> {:?}\n
+            - w_4 -> {:?}\n",
+                i,
+                qm,
+                ql,
+                qr,
+                q4,
+                qo,
+                qc,
+                qarith,
+                qrange,
+                qlogic,
+                qecc_add,
+                qfixed_base,
+                qlookup,
+                a,
+                b,
+                c,
+                d
+            );
+            let k = qarith * ((qm * a * b) + (ql * a) + (qr * b) + (qo * c) + (q4 * d) + pi + qc)
+                + qlogic
+                    * (((delta(a_next - four * a) - delta(b_next - four * b)) * c)
+                        + delta(a_next - four * a)
+                        + delta(b_next - four * b)
+                        + delta(d_next - four * d)
+                        + match (qlogic == Scalar::one(), qlogic == -Scalar::one()) {
+                            (true, false) => (a & b) - d,
+                            (false, true) => (a ^ b) - d,
+                            (false, false) => Scalar::zero(),
+                            _ => unreachable!(),
+                        })
+                + qrange
+                    * (delta(c - four * d)
+                        + delta(b - four * c)
+                        + delta(a - four * b)
+                        + delta(d_next - four * a))
+                + qecc_add * {
+                    let add = Scalar::one() - c_next;
+                    ((add * a * d - d_next)
+                        + (a_next * (Scalar::one() + EDWARDS_D * d_next * b * c)
+                            - (c_next * a + d_next + add * b * c))
+                        + (b_next * (Scalar::one() - EDWARDS_D * d_next * b * c)
+                            - (c_next * b + add * (b * d + a * c)))
+                        + (c_next.square() - c_next))
+                }
+                + qfixed_base * {
+                    let quad = d_next - four * d;
+                    let delta = quad + quad - Scalar::from(3);
+                    let delta_sq = delta.square();
+                    let (x_alpha, y_alpha) = (qm * c + qc * delta, ql + qr * delta_sq);
+                    ((delta_sq - Scalar::one()) * (delta_sq - Scalar::from(9)))
+                        + (c - delta * (qo + q4 * delta_sq))
+                        + (a_next * (Scalar::one() + EDWARDS_D * c * a * b)
+                            - (a * y_alpha + b * x_alpha))
+                        + (b_next * (Scalar::one() - EDWARDS_D * c * a * b)
+                            - (b * y_alpha + a * x_alpha))
+                };
+
+            assert_eq!(k, Scalar::zero(), "Check failed at gate {}", i,);
+
+            // The wires of a lookup gate must be a row of the table whose
+            // id is held in `q_c`
+            if qlookup != Scalar::zero() {
+                assert!(
+                    self.lookup_tables
+                        .iter()
+                        .enumerate()
+                        .any(|(id, table)| Scalar::from(id as u64) == qc
+                            && table.contains(&a, &b, &c)),
+                    "Lookup failed at gate {}",
+                    i,
+                );
+            }
+
+            let wires = WireEvaluations {
+                a,
+                a_next,
+                b,
+                b_next,
+                c,
+                d,
+                d_next,
+            };
+            for (id, (widget, columns)) in self
+                .widgets
+                .iter()
+                .zip(self.custom_selectors.iter())
+                .enumerate()
+            {
+                let selectors: Vec<Scalar> = columns
+                    .iter()
+                    .map(|column| column.get(i).cloned().unwrap_or_else(Scalar::zero))
+                    .collect();
+                assert_eq!(
+                    widget.compute_quotient_i(&selectors, &wires),
+                    Scalar::zero(),
+                    "Custom widget {} failed at gate {}",
+                    id,
+                    i,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment_scheme::kzg10::PublicParameters;
+    use crate::constraint_system::{NonNativeField, NonNativeFieldElement};
+    use crate::constraint_system::{UInt16, UInt32, UInt64, UInt8};
+    use crate::hash::Poseidon;
+    use crate::merkle::MerkleTree;
+    use crate::schnorr::{PublicKey, SecretKey, Signature};
+    use bls12_381::{G1Affine, Scalar as Fr};
+    use jubjub::{AffinePoint, ExtendedPoint, Fr as JubJubScalar, GENERATOR};
+    use merlin::Transcript;
+
+    // Returns a composer with `n` constraints
+    fn add_dummy_composer(n: usize) -> StandardComposer {
+        let mut composer = StandardComposer::new();
+
+        let one = Scalar::one();
+
+        let var_one = composer.add_input(one);
+
+        for _ in 0..n {
+            composer.big_add(
+                var_one.into(),
+                var_one.into(),
+                composer.zero_var.into(),
+                Scalar::zero(),
+                None,
+            );
+        }
+        composer.add_dummy_constraints();
+
+        composer
+    }
+
+    #[test]
+    fn test_pad() {
+        let num_constraints = 100;
+        let mut composer: StandardComposer = add_dummy_composer(num_constraints);
+
+        // Pad the circuit to next power of two
+        let next_pow_2 = composer.n.next_power_of_two() as u64;
+        composer.pad(next_pow_2 as usize - composer.n);
+
+        let size = composer.n;
+        assert!(size.is_power_of_two());
+        assert!(composer.q_m.len() == size);
+        assert!(composer.q_l.len() == size);
+        assert!(composer.q_o.len() == size);
+        assert!(composer.q_r.len() == size);
+        assert!(composer.q_c.len() == size);
+        assert!(composer.q_arith.len() == size);
+        assert!(composer.q_range.len() == size);
+        assert!(composer.q_logic.len() == size);
+        assert!(composer.q_ecc_add.len() == size);
+        assert!(composer.q_fixed_base.len() == size);
+        assert!(composer.q_lookup.len() == size);
+        assert!(composer.w_l.len() == size);
+        assert!(composer.w_r.len() == size);
+        assert!(composer.w_o.len() == size);
+    }
+
+    #[test]
+    fn test_witness_free_preprocessing() {
+        let gadget = |composer: &mut StandardComposer, a: Scalar, b: Scalar| {
+            let a = composer.add_input(a);
+            let b = composer.add_input(b);
+            let (a_xor_b, _) = composer.logic_xor_gate(a, b, 32);
+            composer.range_gate(a_xor_b, 32);
+            composer.add_dummy_constraints();
+        };
+        let public_parameters = PublicParameters::setup(2 * 256, &mut rand::thread_rng()).unwrap();
+
+        // Keys computed without any witness
+        let mut key_composer = StandardComposer::witness_free();
+        gadget(&mut key_composer, Scalar::zero(), Scalar::zero());
+        assert!(key_composer.is_witness_free());
+        let (ck, vk) = public_parameters.trim_for_circuit(&key_composer).unwrap();
+        let (_, verifier_circuit_key) = key_composer
+            .preprocess(&ck, &mut Transcript::new(b""))
+            .unwrap();
+
+        // Prover computes its keys with a real witness
+        let mut composer = StandardComposer::new();
+        gadget(
+            &mut composer,
+            Scalar::from(0xdead_beefu64),
+            Scalar::from(0xcafeu64),
+        );
+        assert_eq!(composer.circuit_size(), key_composer.circuit_size());
+        let (prover_circuit_key, _) = composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        assert!(prover_circuit_key.verifier_circuit_key() == verifier_circuit_key);
+
+        let mut transcript = Transcript::new(b"");
+        verifier_circuit_key.seed_transcript(&mut transcript);
+        let proof = composer
+            .prove(
+                &ck,
+                &prover_circuit_key,
+                &mut transcript,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+
+        let mut transcript = Transcript::new(b"");
+        verifier_circuit_key.seed_transcript(&mut transcript);
+        assert!(proof
+            .verify(
+                &verifier_circuit_key,
+                &mut transcript,
+                &vk,
+                &composer.public_inputs()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_witness_free_prove() {
+        let mut composer = StandardComposer::witness_free();
+        composer.add_dummy_constraints();
+        let public_parameters = PublicParameters::setup(2 * 8, &mut rand::thread_rng()).unwrap();
+        let (ck, _) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let (prover_circuit_key, _) = composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        match composer.prove(
+            &ck,
+            &prover_circuit_key,
+            &mut Transcript::new(b""),
+            &mut rand::thread_rng(),
+        ) {
+            Err(Error::WitnessFreeComposer) => {}
+            _ => panic!("a witness-free composer must not be able to prove"),
+        }
+    }
+
+    #[test]
+    fn test_prove_errors() {
+        let public_parameters = PublicParameters::setup(2 * 32, &mut rand::thread_rng()).unwrap();
+
+        let mut composer = StandardComposer::new();
+        composer.add_dummy_constraints();
+        let (ck, _) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let (prover_circuit_key, _) = composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+
+        // The commit key cannot hold the blinded witness polynomials
+        let (small_ck, _) = public_parameters.trim(4).unwrap();
+        match composer.prove(
+            &small_ck,
+            &prover_circuit_key,
+            &mut Transcript::new(b""),
+            &mut rand::thread_rng(),
+        ) {
+            Err(Error::SRSTooSmall { available, .. }) => assert_eq!(available, 4),
+            _ => panic!("proving with a small commit key must fail"),
+        }
+
+        // A circuit with a public input does not match the key
+        let mut other = StandardComposer::new();
+        let one = other.add_input(Scalar::one());
+        other.add_public_input(one);
+        other.add_dummy_constraints();
+        match other.prove(
+            &ck,
+            &prover_circuit_key,
+            &mut Transcript::new(b""),
+            &mut rand::thread_rng(),
+        ) {
+            Err(Error::MismatchedCircuit) => {}
+            _ => panic!("proving a different circuit must fail"),
+        }
+
+        // The public parameters are too small for the circuit
+        let small_parameters = PublicParameters::setup(8, &mut rand::thread_rng()).unwrap();
+        match small_parameters.trim_for_circuit(&other) {
+            Err(Error::SRSTooSmall {
+                required,
+                available,
+            }) => assert_eq!((required, available), (14, 8)),
+            _ => panic!("trimming small public parameters must fail"),
+        }
+    }
+
+    #[test]
+    fn test_proofs_are_blinded() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        let public_parameters = PublicParameters::setup(2 * 16, &mut rand::thread_rng()).unwrap();
+        let mut composer = StandardComposer::new();
+        let a = composer.add_input(Fr::from(3u64));
+        let b = composer.add_input(Fr::from(4u64));
+        let product = composer.mul(Fr::one(), a, b, Fr::zero(), None);
+        composer.constrain_to_constant(product, Fr::from(12u64), None);
+        composer.add_dummy_constraints();
+
+        let (ck, vk) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let (prover_circuit_key, verifier_circuit_key) =
+            composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        let mut transcript = Transcript::new(b"");
+        verifier_circuit_key.seed_transcript(&mut transcript);
+
+        // The same witness proven with two different RNGs
+        let proof_0 = composer
+            .prove(
+                &ck,
+                &prover_circuit_key,
+                &mut transcript.clone(),
+                &mut ChaChaRng::seed_from_u64(1),
+            )
+            .unwrap();
+        let proof_1 = composer
+            .prove(
+                &ck,
+                &prover_circuit_key,
+                &mut transcript.clone(),
+                &mut ChaChaRng::seed_from_u64(2),
+            )
+            .unwrap();
+
+        assert_ne!(proof_0.a_comm, proof_1.a_comm);
+        assert_ne!(proof_0.z_comm, proof_1.z_comm);
+        assert_ne!(proof_0.t_1_comm, proof_1.t_1_comm);
+        assert_ne!(proof_0.t_2_comm, proof_1.t_2_comm);
+        assert_ne!(proof_0.t_3_comm, proof_1.t_3_comm);
+        assert_ne!(proof_0.t_4_comm, proof_1.t_4_comm);
+
+        for proof in [proof_0, proof_1].iter() {
+            assert!(proof
+                .verify(
+                    &verifier_circuit_key,
+                    &mut transcript.clone(),
+                    &vk,
+                    &composer.public_inputs()
+                )
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_shifted_polys_are_blinded() {
+        let public_parameters = PublicParameters::setup(2 * 64, &mut rand::thread_rng()).unwrap();
+        let mut composer = StandardComposer::new();
+        let xor = composer.add_lookup_table(LookupTable::xor(2));
+        let a = composer.add_input(Fr::from(0b01u64));
+        let b = composer.add_input(Fr::from(0b11u64));
+        let a_xor_b = composer.add_input(Fr::from(0b10u64));
+        composer.lookup_gate(a, b, a_xor_b, xor);
+        composer.add_dummy_constraints();
+
+        let (ck, _) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let (prover_circuit_key, _) = composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        let domain = prover_circuit_key.domain;
+        let mut rng = rand::thread_rng();
+
+        // Compute the witness polynomials the same way as the prover
+        let pad = vec![Fr::zero(); domain.size() - composer.w_l.len()];
+        let w_l_scalar = &[&composer.to_scalars(&composer.w_l).unwrap()[..], &pad].concat();
+        let w_r_scalar = &[&composer.to_scalars(&composer.w_r).unwrap()[..], &pad].concat();
+        let w_o_scalar = &[&composer.to_scalars(&composer.w_o).unwrap()[..], &pad].concat();
+        let w_4_scalar = &[&composer.to_scalars(&composer.w_4).unwrap()[..], &pad].concat();
+        let wire_scalars = (
+            &w_l_scalar[..],
+            &w_r_scalar[..],
+            &w_o_scalar[..],
+            &w_4_scalar[..],
+        );
+
+        let (w_l_poly, w_r_poly, w_o_poly, w_4_poly) =
+            StandardComposer::compute_wire_polys(&domain, wire_scalars, &mut rng);
+        let (lookup, _) = composer
+            .compute_lookup_polynomials(
+                &ck,
+                &domain,
+                prover_circuit_key.lookup.as_ref().unwrap(),
+                (w_l_scalar, w_r_scalar, w_o_scalar),
+                &mut Transcript::new(b""),
+                &mut rng,
+            )
+            .unwrap();
+        let z_poly = composer.compute_permutation_poly(
+            &domain,
+            wire_scalars,
+            &(Fr::from(2u64), Fr::from(3u64)),
+            &prover_circuit_key.permutation,
+            &mut rng,
+        );
+
+        // Every witness polynomial opened at `z * omega` has a blinding
+        // polynomial of degree 2, which leaves a single evaluation at `z`
+        // and another at `z * omega` independent of the witness
+        let shifted_polys = StandardComposer::shifted_witness_polys(
+            &z_poly,
+            (&w_l_poly, &w_r_poly, &w_o_poly, &w_4_poly),
+            Some(&lookup),
+        );
+        assert_eq!(shifted_polys.len(), 8);
+        for poly in shifted_polys.iter() {
+            assert_eq!(poly.degree(), domain.size() + 2);
+        }
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_prove_verify() {
+        let ok = test_gadget(
+            |composer| {
+                // do nothing except add the dummy constraints
+            },
+            200,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_logic_xor_constraint() {
+        // Should pass since the XOR result is correct and the bit-num is even.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(500u64));
+                let witness_b = composer.add_input(Scalar::from(357u64));
+                let (xor_res, _) = composer.logic_gate(witness_a, witness_b, 10, true);
+                // Check that the XOR result is indeed what we are expecting.
+                composer.constrain_to_constant(xor_res, Scalar::from(500u64 ^ 357u64), None);
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should pass since the AND result is correct even the bit-num is even.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(469u64));
+                let witness_b = composer.add_input(Scalar::from(321u64));
+                let (xor_res, _) = composer.logic_gate(witness_a, witness_b, 10, false);
+                // Check that the AND result is indeed what we are expecting.
+                composer.constrain_to_constant(xor_res, Scalar::from(469u64 & 321u64), None);
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should not pass since the XOR result is not correct even the bit-num is even.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(139u64));
+                let witness_b = composer.add_input(Scalar::from(33u64));
+                let (xor_res, _) = composer.logic_gate(witness_a, witness_b, 10, true);
+                // Check that the XOR result is indeed what we are expecting.
+                composer.constrain_to_constant(xor_res, Scalar::from(139u64 & 33u64), None);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_logical_gate_odd_bit_num() {
+        // Should pass since the top quads of the inputs hold a single bit.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(500u64));
+                let witness_b = composer.add_input(Scalar::from(499u64));
+                let (xor_res, _) = composer.logic_gate(witness_a, witness_b, 9, true);
+                // Check that the XOR result is indeed what we are expecting.
+                composer.constrain_to_constant(xor_res, Scalar::from(7u64), None);
+            },
+            200,
+        );
+        assert!(ok);
+
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(1u64));
+                let witness_b = composer.add_input(Scalar::from(1u64));
+                let (and_res, _) = composer.logic_and_gate(witness_a, witness_b, 1);
+                composer.constrain_to_constant(and_res, Scalar::one(), None);
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should not pass since 512 does not fit in 9 bits.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(512u64));
+                let witness_b = composer.add_input(Scalar::from(3u64));
+                let (xor_res, _) = composer.logic_gate(witness_a, witness_b, 9, true);
+                composer.constrain_to_constant(xor_res, Scalar::from(3u64), None);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_logical_gate_bits() {
+        // The bits of the result are returned least significant first
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(500u64));
+                let witness_b = composer.add_input(Scalar::from(357u64));
+                let (_, bits) = composer.logic_xor_gate(witness_a, witness_b, 10);
+                assert_eq!(bits.len(), 10);
+                for (i, bit) in bits.iter().enumerate() {
+                    let value = ((500u64 ^ 357u64) >> i) & 1;
+                    composer.constrain_to_constant(*bit, Scalar::from(value), None);
+                }
+            },
+            200,
+        );
+        assert!(ok);
+
+        // The topmost quad of an odd number of bits only holds one bit
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(469u64));
+                let witness_b = composer.add_input(Scalar::from(321u64));
+                let (_, bits) = composer.logic_and_gate(witness_a, witness_b, 9);
+                assert_eq!(bits.len(), 9);
+                for (i, bit) in bits.iter().enumerate() {
+                    let value = ((469u64 & 321u64) >> i) & 1;
+                    composer.constrain_to_constant(*bit, Scalar::from(value), None);
+                }
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should not pass since the lowest bit of 500 ^ 357 is one
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(500u64));
+                let witness_b = composer.add_input(Scalar::from(357u64));
+                let (_, bits) = composer.logic_xor_gate(witness_a, witness_b, 10);
+                composer.constrain_to_constant(bits[0], Scalar::zero(), None);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_logical_gate_input_out_of_range() {
+        // Should not pass since the left input does not fit in 10 bits,
+        // even though the result matches its lowest 10 bits.
+        let ok = test_gadget(
+            |composer| {
+                let witness_a = composer.add_input(Scalar::from(1024u64 + 500u64));
+                let witness_b = composer.add_input(Scalar::from(357u64));
+                let (xor_res, _) = composer.logic_xor_gate(witness_a, witness_b, 10);
+                composer.constrain_to_constant(xor_res, Scalar::from(500u64 ^ 357u64), None);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_range_constraint() {
+        // Should fail as the number is not 32 bits
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from((u32::max_value() as u64) + 1));
+                composer.range_gate(witness, 32);
+            },
+            200,
+        );
+        assert!(!ok);
+
+        // Should fail as number is greater than 32 bits
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from(u64::max_value()));
+                composer.range_gate(witness, 32);
+            },
+            200,
+        );
+        assert!(!ok);
+
+        // Should pass as the number is within 34 bits
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from(2u64.pow(34) - 1));
+                composer.range_gate(witness, 34);
+            },
+            200,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_odd_bit_range() {
+        // Should pass as the number is within 33 bits
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from(2u64.pow(33) - 1));
+                composer.range_gate(witness, 33);
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should fail as the number is not 33 bits
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from(2u64.pow(33)));
+                composer.range_gate(witness, 33);
+            },
+            200,
+        );
+        assert!(!ok);
+
+        // Should pass as the number is a single bit
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::one());
+                composer.range_gate(witness, 1);
+            },
+            200,
+        );
+        assert!(ok);
+
+        // Should fail as the number is not a single bit
+        let ok = test_gadget(
+            |composer| {
+                let witness = composer.add_input(Scalar::from(2u64));
+                composer.range_gate(witness, 1);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_pi() {
+        let ok = test_gadget(
+            |composer| {
+                let var_one = composer.add_input(Fr::one());
+
+                let should_be_three = composer.big_add(
+                    var_one.into(),
+                    var_one.into(),
+                    composer.zero_var.into(),
+                    Scalar::zero(),
+                    Some(Scalar::one()),
+                );
+                composer.constrain_to_constant(should_be_three, Scalar::from(3), None);
+                let should_be_four = composer.big_add(
+                    var_one.into(),
+                    var_one.into(),
+                    composer.zero_var.into(),
+                    Scalar::zero(),
+                    Some(Scalar::from(2)),
+                );
+                composer.constrain_to_constant(should_be_four, Scalar::from(4), None);
+            },
+            200,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_add_public_input() {
+        let public_parameters = PublicParameters::setup(2 * 16, &mut rand::thread_rng()).unwrap();
+        let mut composer = StandardComposer::new();
+        let five = composer.add_input(Fr::from(5u64));
+        let five = composer.add_public_input(five);
+        let ten = composer.add((Fr::one(), five), (Fr::one(), five), Fr::zero(), None);
+        composer.add_public_input(ten);
+        composer.add_dummy_constraints();
+        assert_eq!(
+            composer.public_inputs(),
+            vec![Fr::from(5u64), Fr::from(10u64)]
+        );
+
+        let (ck, vk) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let (prover_circuit_key, verifier_circuit_key) =
+            composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        assert_eq!(verifier_circuit_key.pi_pos, vec![1, 3]);
+
+        let mut transcript = Transcript::new(b"");
+        verifier_circuit_key.seed_transcript(&mut transcript);
+        let proof = composer
+            .prove(
+                &ck,
+                &prover_circuit_key,
+                &mut transcript,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+
+        let verify = |pub_inputs: &[Fr]| {
+            let mut transcript = Transcript::new(b"");
+            verifier_circuit_key.seed_transcript(&mut transcript);
+            proof.verify(&verifier_circuit_key, &mut transcript, &vk, pub_inputs)
+        };
+        assert!(verify(&[Fr::from(5u64), Fr::from(10u64)]).is_ok());
+        match verify(&[Fr::from(5u64), Fr::from(11u64)]) {
+            Err(Error::ProofVerificationFailed) => {}
+            _ => panic!("a wrong public input must be rejected"),
+        }
+        match verify(&[Fr::from(5u64)]) {
+            Err(Error::PublicInputsMismatch { expected, found }) => {
+                assert_eq!((expected, found), (2, 1))
+            }
+            _ => panic!("a missing public input must be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        let public_parameters = PublicParameters::setup(2 * 16, &mut rand::thread_rng()).unwrap();
+        // Proves knowledge of `a` and `b` such that `a * b = 12` and `a + b` is public
+        let build = |a: u64, b: u64| {
+            let mut composer = StandardComposer::new();
+            let a = composer.add_input(Fr::from(a));
+            let b = composer.add_input(Fr::from(b));
+            let product = composer.mul(Fr::one(), a, b, Fr::zero(), None);
+            composer.constrain_to_constant(product, Fr::from(12u64), None);
+            let sum = composer.add((Fr::one(), a), (Fr::one(), b), Fr::zero(), None);
+            composer.add_public_input(sum);
+            composer.add_dummy_constraints();
+            composer
+        };
+
+        let mut key_composer = build(0, 0);
+        let (ck, vk) = public_parameters.trim_for_circuit(&key_composer).unwrap();
+        let (_, verifier_circuit_key) = key_composer
+            .preprocess(&ck, &mut Transcript::new(b""))
+            .unwrap();
+
+        let mut transcript = Transcript::new(b"");
+        verifier_circuit_key.seed_transcript(&mut transcript);
+
+        let prove = |a: u64, b: u64| {
+            let mut composer = build(a, b);
+            let (prover_circuit_key, _) =
+                composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+            let proof = composer
+                .prove(
+                    &ck,
+                    &prover_circuit_key,
+                    &mut transcript.clone(),
+                    &mut rand::thread_rng(),
+                )
+                .unwrap();
+            (proof, composer.public_inputs())
+        };
+        let (proof_0, pi_0) = prove(3, 4);
+        let (proof_1, pi_1) = prove(2, 6);
+        let (proof_2, pi_2) = prove(5, 7);
+
+        let (mut t_0, mut t_1) = (transcript.clone(), transcript.clone());
+        let mut valid: [(&Proof, &[Fr], &mut dyn TranscriptProtocol); 2] = [
+            (&proof_0, &pi_0[..], &mut t_0),
+            (&proof_1, &pi_1[..], &mut t_1),
+        ];
+        assert!(Proof::batch_verify(
+            &mut valid,
+            &verifier_circuit_key,
+            &vk,
+            &[],
+            &mut rand::thread_rng()
+        )
+        .is_ok());
+
+        // The third proof does not satisfy the circuit and the second one is
+        // given the public inputs of the first one
+        let (mut t_0, mut t_1, mut t_2, mut t_3) = (
+            transcript.clone(),
+            transcript.clone(),
+            transcript.clone(),
+            transcript.clone(),
+        );
+        let mut invalid: [(&Proof, &[Fr], &mut dyn TranscriptProtocol); 4] = [
+            (&proof_0, &pi_0[..], &mut t_0),
+            (&proof_1, &pi_0[..], &mut t_1),
+            (&proof_2, &pi_2[..], &mut t_2),
+            (&proof_0, &[][..], &mut t_3),
+        ];
+        assert_eq!(
+            Proof::batch_verify(
+                &mut invalid,
+                &verifier_circuit_key,
+                &vk,
+                &[],
+                &mut rand::thread_rng()
+            ),
+            Err(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_correct_add_mul_gate() {
+        let ok = test_gadget(
+            |composer| {
+                // Verify that (4+5+5) * (6+7+7) = 280
+                let four = composer.add_input(Fr::from(4));
+                let five = composer.add_input(Fr::from(5));
+                let six = composer.add_input(Fr::from(6));
+                let seven = composer.add_input(Fr::from(7));
+
+                let fourteen =
+                    composer.big_add(four.into(), five.into(), five.into(), Scalar::zero(), None);
+
+                let twenty =
+                    composer.big_add(six.into(), seven.into(), seven.into(), Scalar::zero(), None);
+
+                // There are quite a few ways to check the equation is correct, depending on your circumstance
+                // If we already have the output wire, we can constrain the output of the mul_gate to be equal to it
+                // If we do not, we can compute it using the `mul`
+                // If the output is public, we can also constrain the output wire of the mul gate to it. This is what this test does
+                let output = composer.mul(Scalar::one(), fourteen, twenty, Scalar::zero(), None);
+                composer.constrain_to_constant(output, Scalar::from(280), None);
+            },
+            200,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_correct_add_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let zero = composer.add_input(Fr::zero());
+                let one = composer.add_input(Fr::one());
+
+                let c = composer.add(
+                    (Scalar::one(), one),
+                    (Scalar::zero(), zero),
+                    Scalar::from(2u64),
+                    None,
+                );
+                composer.constrain_to_constant(c, Scalar::from(3), None);
+            },
+            32,
+        );
+        assert!(ok)
+    }
+    #[test]
+    fn test_correct_big_add_mul_gate() {
+        let ok = test_gadget(
+            |composer| {
+                // Verify that (4+5+5) * (6+7+7) + (8*9) = 352
+                let four = composer.add_input(Fr::from(4));
+                let five = composer.add_input(Fr::from(5));
+                let six = composer.add_input(Fr::from(6));
+                let seven = composer.add_input(Fr::from(7));
+                let nine = composer.add_input(Fr::from(9));
+
+                let fourteen =
+                    composer.big_add(four.into(), five.into(), five.into(), Scalar::zero(), None);
+
+                let twenty =
+                    composer.big_add(six.into(), seven.into(), seven.into(), Scalar::zero(), None);
+
+                let output = composer.big_mul(
+                    Scalar::one(),
+                    fourteen,
+                    twenty,
+                    (Scalar::from(8), nine),
+                    Scalar::zero(),
+                    None,
+                );
+                composer.constrain_to_constant(output, Scalar::from(352), None);
+            },
+            200,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_incorrect_add_mul_gate() {
+        let ok = test_gadget(
+            |composer| {
+                // Verify that (5+5) * (6+7) != 117
+                let five = composer.add_input(Fr::from(5));
+                let six = composer.add_input(Fr::from(6));
+                let seven = composer.add_input(Fr::from(7));
+
+                let five_plus_five = composer.big_add(
+                    five.into(),
+                    five.into(),
+                    composer.zero_var.into(),
+                    Scalar::zero(),
+                    None,
+                );
+
+                let six_plus_seven = composer.big_add(
+                    six.into(),
+                    seven.into(),
+                    composer.zero_var.into(),
+                    Scalar::zero(),
+                    None,
+                );
+
+                let output = composer.mul(
+                    Scalar::one(),
+                    five_plus_five,
+                    six_plus_seven,
+                    Scalar::zero(),
+                    None,
+                );
+                composer.constrain_to_constant(output, Scalar::from(117), None);
+            },
+            200,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_correct_bool_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let zero = composer.add_input(Fr::zero());
+                let one = composer.add_input(Fr::one());
+
+                composer.bool_gate(zero);
+                composer.bool_gate(one);
+            },
+            32,
+        );
+        assert!(ok)
+    }
+
+    #[test]
+    fn test_incorrect_bool_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let zero = composer.add_input(Fr::from(5));
+                let one = composer.add_input(Fr::one());
+
+                composer.bool_gate(zero);
+                composer.bool_gate(one);
+            },
+            32,
+        );
+        assert!(!ok)
+    }
+
+    #[test]
+    fn test_point_addition_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let p = ExtendedPoint::from(GENERATOR) * JubJubScalar::from(5u64);
+                let q = ExtendedPoint::from(GENERATOR) * JubJubScalar::from(7u64);
+                let expected = AffinePoint::from(p + q);
+                let (p, q) = (AffinePoint::from(p), AffinePoint::from(q));
+
+                let p = (composer.add_input(p.get_u()), composer.add_input(p.get_v()));
+                let q = (composer.add_input(q.get_u()), composer.add_input(q.get_v()));
+                let (x, y) = composer.point_addition_gate(p, q);
+
+                assert_eq!(composer.variables[&x], expected.get_u());
+                assert_eq!(composer.variables[&y], expected.get_v());
+                composer.constrain_to_constant(x, expected.get_u(), None);
+                composer.constrain_to_constant(y, expected.get_v(), None);
+            },
+            64,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_incorrect_point_addition_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let p = ExtendedPoint::from(GENERATOR) * JubJubScalar::from(5u64);
+                let q = ExtendedPoint::from(GENERATOR) * JubJubScalar::from(7u64);
+                let wrong = AffinePoint::from(p + p);
+                let (p, q) = (AffinePoint::from(p), AffinePoint::from(q));
+
+                let p = (composer.add_input(p.get_u()), composer.add_input(p.get_v()));
+                let q = (composer.add_input(q.get_u()), composer.add_input(q.get_v()));
+                let (x, y) = composer.point_addition_gate(p, q);
+
+                composer.constrain_to_constant(x, wrong.get_u(), None);
+                composer.constrain_to_constant(y, wrong.get_v(), None);
+            },
+            64,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_fixed_base_scalar_mul() {
+        let ok = test_gadget(
+            |composer| {
+                // The largest Jubjub scalar uses every bit of the decomposition,
+                // while zero picks the lowest digit of every window
+                for scalar in [-JubJubScalar::one(), JubJubScalar::zero()].iter() {
+                    let expected = AffinePoint::from(ExtendedPoint::from(GENERATOR) * scalar);
+
+                    let scalar = composer.add_input(Fr::from_bytes(&scalar.to_bytes()).unwrap());
+                    let size = composer.circuit_size();
+                    let (x, y) = composer.fixed_base_scalar_mul(scalar, GENERATOR);
+                    // One gate per 2-bit window, plus the offset, the result
+                    // and the check of the scalar
+                    assert_eq!(composer.circuit_size() - size, 130);
+
+                    assert_eq!(composer.variables[&x], expected.get_u());
+                    assert_eq!(composer.variables[&y], expected.get_v());
+                    composer.constrain_to_constant(x, expected.get_u(), None);
+                    composer.constrain_to_constant(y, expected.get_v(), None);
+                }
+            },
+            512,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fixed_base_scalar_mul_identity() {
+        let mut composer = StandardComposer::new();
+        let scalar = composer.add_input(Fr::one());
+        composer.fixed_base_scalar_mul(scalar, AffinePoint::identity());
+    }
+
+    #[test]
+    fn test_incorrect_fixed_base_scalar_mul() {
+        let ok = test_gadget(
+            |composer| {
+                let scalar = JubJubScalar::from(0xdead_beefu64);
+                let wrong = AffinePoint::from(
+                    ExtendedPoint::from(GENERATOR) * (scalar + JubJubScalar::one()),
+                );
+
+                let scalar = composer.add_input(Fr::from_bytes(&scalar.to_bytes()).unwrap());
+                let (x, y) = composer.fixed_base_scalar_mul(scalar, GENERATOR);
+
+                composer.constrain_to_constant(x, wrong.get_u(), None);
+                composer.constrain_to_constant(y, wrong.get_v(), None);
+            },
+            512,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_variable_base_scalar_mul() {
+        let ok = test_gadget(
+            |composer| {
+                let point =
+                    AffinePoint::from(ExtendedPoint::from(GENERATOR) * JubJubScalar::from(9u64));
+                let scalar = -JubJubScalar::from(0xdead_beefu64);
+                let expected = AffinePoint::from(ExtendedPoint::from(point) * scalar);
+
+                let point = (
+                    composer.add_input(point.get_u()),
+                    composer.add_input(point.get_v()),
+                );
+                let scalar = composer.add_input(Fr::from_bytes(&scalar.to_bytes()).unwrap());
+                let size = composer.circuit_size();
+                let (x, y) = composer.variable_base_scalar_mul(scalar, point);
+                // Five gates per bit, plus the identity, the curve check of
+                // the point and the check of the scalar
+                assert_eq!(composer.circuit_size() - size, 5 * 252 + 5);
+
+                assert_eq!(composer.variables[&x], expected.get_u());
+                assert_eq!(composer.variables[&y], expected.get_v());
+                composer.constrain_to_constant(x, expected.get_u(), None);
+                composer.constrain_to_constant(y, expected.get_v(), None);
+            },
+            4096,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_variable_base_scalar_mul_edge_cases() {
+        let ok = test_gadget(
+            |composer| {
+                // `(0, -1)` is the point of order two of the curve
+                let small_order = AffinePoint::from_bytes((-Fr::one()).to_bytes()).unwrap();
+                let cases = [
+                    (AffinePoint::identity(), JubJubScalar::from(7u64)),
+                    (small_order, JubJubScalar::from(3u64)),
+                ];
+
+                for (point, scalar) in cases.iter() {
+                    let expected = AffinePoint::from(ExtendedPoint::from(*point) * scalar);
+
+                    let point = (
+                        composer.add_input(point.get_u()),
+                        composer.add_input(point.get_v()),
+                    );
+                    let scalar = composer.add_input(Fr::from_bytes(&scalar.to_bytes()).unwrap());
+                    let (x, y) = composer.variable_base_scalar_mul(scalar, point);
+
+                    assert_eq!(composer.variables[&x], expected.get_u());
+                    assert_eq!(composer.variables[&y], expected.get_v());
+                    composer.constrain_to_constant(x, expected.get_u(), None);
+                    composer.constrain_to_constant(y, expected.get_v(), None);
+                }
+            },
+            4096,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_incorrect_variable_base_scalar_mul() {
+        let ok = test_gadget(
+            |composer| {
+                let point =
+                    AffinePoint::from(ExtendedPoint::from(GENERATOR) * JubJubScalar::from(9u64));
+                let scalar = JubJubScalar::from(0xdead_beefu64);
+                let wrong =
+                    AffinePoint::from(ExtendedPoint::from(point) * (scalar + JubJubScalar::one()));
+
+                let point = (
+                    composer.add_input(point.get_u()),
+                    composer.add_input(point.get_v()),
+                );
+                let scalar = composer.add_input(Fr::from_bytes(&scalar.to_bytes()).unwrap());
+                let (x, y) = composer.variable_base_scalar_mul(scalar, point);
+
+                composer.constrain_to_constant(x, wrong.get_u(), None);
+                composer.constrain_to_constant(y, wrong.get_v(), None);
+            },
+            4096,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_variable_base_scalar_mul_off_curve() {
+        let ok = test_gadget(
+            |composer| {
+                // `(1, 1)` is not a point of the curve, so the gadget must
+                // reject it even though the scalar multiplication is
+                // computed consistently from it
+                let point = (composer.add_input(Fr::one()), composer.add_input(Fr::one()));
+                let scalar = composer.add_input(Fr::from(3u64));
+                composer.variable_base_scalar_mul(scalar, point);
+            },
+            4096,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_poseidon_hash() {
+        let ok = test_gadget(
+            |composer| {
+                let inputs = [Fr::from(3u64), Fr::from(5u64), Fr::from(8u64)];
+                let expected = Poseidon::new().hash(&inputs);
+
+                let inputs: Vec<Variable> = inputs.iter().map(|i| composer.add_input(*i)).collect();
+                let hash = composer.poseidon_hash(&inputs);
+
+                composer.constrain_to_constant(hash, expected, None);
+            },
+            1024,
+        );
+        assert!(ok);
     }
 
-    /// Utility function that allows to check on the "front-end"
-    /// side of the PLONK implementation if the identity polynomial
-    /// is satisfied for each one of the `StandardComposer`'s gates.
-    #[cfg(feature = "trace")]
-    pub fn check_circuit_satisfied(&self) {
-        let w_l = self.to_scalars(&self.w_l);
-        let w_r = self.to_scalars(&self.w_r);
-        let w_o = self.to_scalars(&self.w_o);
-        let w_4 = self.to_scalars(&self.w_4);
-        // Computes f(f-1)(f-2)(f-3)
-        let delta = |f: Scalar| -> Scalar {
-            let f_1 = f - Scalar::one();
-            let f_2 = f - Scalar::from(2);
-            let f_3 = f - Scalar::from(3);
-            f * f_1 * f_2 * f_3
-        };
-        let four = Scalar::from(4);
+    #[test]
+    fn test_incorrect_poseidon_hash() {
+        let ok = test_gadget(
+            |composer| {
+                let wrong = Poseidon::new().hash(&[Fr::from(3u64), Fr::from(5u64)]);
 
-        for i in 0..self.n {
-            let qm = self.q_m[i];
-            let ql = self.q_l[i];
-            let qr = self.q_r[i];
-            let qo = self.q_o[i];
-            let qc = self.q_c[i];
-            let q4 = self.q_4[i];
-            let qarith = self.q_arith[i];
-            let qrange = self.q_range[i];
-            let qlogic = self.q_logic[i];
-            let pi = self.public_inputs[i];
+                let inputs = [
+                    composer.add_input(Fr::from(5u64)),
+                    composer.add_input(Fr::from(3u64)),
+                ];
+                let hash = composer.poseidon_hash(&inputs);
 
-            let a = w_l[i];
-            let a_next = w_l[(i + 1) % self.n];
-            let b = w_r[i];
-            let b_next = w_r[(i + 1) % self.n];
-            let c = w_o[i];
-            let d = w_4[i];
-            let d_next = w_4[(i + 1) % self.n];
-            #[cfg(feature = "trace-print")]
-            println!(
-                "--------------------------------------------\n
-            #Gate Index = {}
-            #Selector Polynomials:\n
-            - qm -> {:?}\n
-            - ql -> {:?}\n
-            - qr -> {:?}\n
-            - q4 -> {:?}\n
-            - qo -> {:?}\n
-            - qc -> {:?}\n
-            - q_arith -> {:?}\n
-            - q_range -> {:?}\n
-            - q_logic -> {:?}\n
-            # Witness polynomials:\n
-            - w_l -> {:?}\n
-            - w_r -> {:?}\n
-            - w_o -> {:?}\n
-            - w_4 -> {:?}\n",
-                i, qm, ql, qr, q4, qo, qc, qarith, qrange, qlogic, a, b, c, d
-            );
-            let k = qarith * ((qm * a * b) + (ql * a) + (qr * b) + (qo * c) + (q4 * d) + pi + qc)
-                + qlogic
-                    * (((delta(a_next - four * a) - delta(b_next - four * b)) * c)
-                        + delta(a_next - four * a)
-                        + delta(b_next - four * b)
-                        + delta(d_next - four * d)
-                        + match (qlogic == Scalar::one(), qlogic == -Scalar::one()) {
-                            (true, false) => (a & b) - d,
-                            (false, true) => (a ^ b) - d,
-                            (false, false) => Scalar::zero(),
-                            _ => unreachable!(),
-                        })
-                + qrange
-                    * (delta(c - four * d)
-                        + delta(b - four * c)
-                        + delta(a - four * b)
-                        + delta(d_next - four * a));
+                composer.constrain_to_constant(hash, wrong, None);
+            },
+            1024,
+        );
+        assert!(!ok);
+    }
 
-            assert_eq!(k, Scalar::zero(), "Check failed at gate {}", i,);
-        }
+    #[test]
+    fn test_merkle_membership() {
+        let ok = test_gadget(
+            |composer| {
+                let mut tree = MerkleTree::new(2);
+                for i in 1..=3u64 {
+                    tree.insert(Fr::from(i));
+                }
+                // The sibling of the last leaf is an empty one
+                let path = tree.path(2).unwrap();
+
+                let leaf = composer.add_input(Fr::from(3u64));
+                composer.merkle_membership(leaf, &path, tree.root());
+            },
+            2048,
+        );
+        assert!(ok);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::commitment_scheme::kzg10::PublicParameters;
-    use bls12_381::Scalar as Fr;
-    use merlin::Transcript;
+    #[test]
+    fn test_incorrect_merkle_membership() {
+        let ok = test_gadget(
+            |composer| {
+                let mut tree = MerkleTree::new(2);
+                for i in 1..=3u64 {
+                    tree.insert(Fr::from(i));
+                }
+                let path = tree.path(1).unwrap();
 
-    // Returns a composer with `n` constraints
-    fn add_dummy_composer(n: usize) -> StandardComposer {
-        let mut composer = StandardComposer::new();
+                // The path of the second leaf does not hold for the first one
+                let leaf = composer.add_input(Fr::from(1u64));
+                composer.merkle_membership(leaf, &path, tree.root());
+            },
+            2048,
+        );
+        assert!(!ok);
+    }
 
-        let one = Scalar::one();
+    #[test]
+    fn test_pedersen_commitment() {
+        let ok = test_gadget(
+            |composer| {
+                let value = JubJubScalar::from(0xdead_beefu64);
+                let blinder = -JubJubScalar::from(0x1234_5678u64);
+                let expected = pedersen::commit(&value, &blinder);
+
+                let value = composer.add_input(Fr::from_bytes(&value.to_bytes()).unwrap());
+                let blinder = composer.add_input(Fr::from_bytes(&blinder.to_bytes()).unwrap());
+                let (x, y) = composer.pedersen_commitment(value, blinder);
+                // The committed value is also proven to fit in 64 bits
+                composer.range_gate(value, 64);
+
+                assert_eq!(composer.variables[&x], expected.get_u());
+                assert_eq!(composer.variables[&y], expected.get_v());
+                composer.constrain_to_constant(x, expected.get_u(), None);
+                composer.constrain_to_constant(y, expected.get_v(), None);
+            },
+            1024,
+        );
+        assert!(ok);
+    }
 
-        let var_one = composer.add_input(one);
+    #[test]
+    fn test_incorrect_pedersen_commitment() {
+        let ok = test_gadget(
+            |composer| {
+                let value = JubJubScalar::from(100u64);
+                let blinder = JubJubScalar::from(7u64);
+                let expected = pedersen::commit(&value, &blinder);
 
-        for _ in 0..n {
-            composer.big_add(
-                var_one.into(),
-                var_one.into(),
-                composer.zero_var.into(),
-                Scalar::zero(),
-                Scalar::zero(),
-            );
-        }
-        composer.add_dummy_constraints();
+                // The commitment does not open to any other value
+                let value = composer.add_input(Fr::from(101u64));
+                let blinder = composer.add_input(Fr::from(7u64));
+                let (x, y) = composer.pedersen_commitment(value, blinder);
 
-        composer
+                composer.constrain_to_constant(x, expected.get_u(), None);
+                composer.constrain_to_constant(y, expected.get_v(), None);
+            },
+            1024,
+        );
+        assert!(!ok);
+    }
+
+    // Adds the inputs of `schnorr_verify` for the signature of `message`
+    fn add_schnorr_inputs(
+        composer: &mut StandardComposer,
+        public_key: &PublicKey,
+        signature: &Signature,
+        message: Fr,
+    ) -> (
+        (Variable, Variable),
+        Variable,
+        (Variable, Variable),
+        Variable,
+    ) {
+        let public_key = (
+            composer.add_input(public_key.point().get_u()),
+            composer.add_input(public_key.point().get_v()),
+        );
+        let message = composer.add_input(message);
+        let r = (
+            composer.add_input(signature.r().get_u()),
+            composer.add_input(signature.r().get_v()),
+        );
+        let s = composer.add_input(Fr::from_bytes(&signature.s().to_bytes()).unwrap());
+        (public_key, message, r, s)
     }
 
     #[test]
-    fn test_pad() {
-        let num_constraints = 100;
-        let mut composer: StandardComposer = add_dummy_composer(num_constraints);
+    fn test_schnorr_verify() {
+        let ok = test_gadget(
+            |composer| {
+                let mut rng = rand::thread_rng();
+                let secret_key = SecretKey::random(&mut rng);
+                let public_key = PublicKey::from(&secret_key);
+                let message = Fr::from(0xdead_beefu64);
+                let signature = secret_key.sign(&message, &mut rng);
+
+                let (public_key, message, r, s) =
+                    add_schnorr_inputs(composer, &public_key, &signature, message);
+                composer.schnorr_verify(public_key, message, r, s);
+            },
+            8192,
+        );
+        assert!(ok);
+    }
 
-        // Pad the circuit to next power of two
-        let next_pow_2 = composer.n.next_power_of_two() as u64;
-        composer.pad(next_pow_2 as usize - composer.n);
+    #[test]
+    fn test_incorrect_schnorr_verify() {
+        let ok = test_gadget(
+            |composer| {
+                let mut rng = rand::thread_rng();
+                let secret_key = SecretKey::random(&mut rng);
+                let public_key = PublicKey::from(&secret_key);
+                let signature = secret_key.sign(&Fr::from(0xdead_beefu64), &mut rng);
+
+                // The signature does not hold for any other message
+                let (public_key, message, r, s) =
+                    add_schnorr_inputs(composer, &public_key, &signature, Fr::from(7u64));
+                composer.schnorr_verify(public_key, message, r, s);
+            },
+            8192,
+        );
+        assert!(!ok);
+    }
 
-        let size = composer.n;
-        assert!(size.is_power_of_two());
-        assert!(composer.q_m.len() == size);
-        assert!(composer.q_l.len() == size);
-        assert!(composer.q_o.len() == size);
-        assert!(composer.q_r.len() == size);
-        assert!(composer.q_c.len() == size);
-        assert!(composer.q_arith.len() == size);
-        assert!(composer.q_range.len() == size);
-        assert!(composer.q_logic.len() == size);
-        assert!(composer.w_l.len() == size);
-        assert!(composer.w_r.len() == size);
-        assert!(composer.w_o.len() == size);
+    #[test]
+    fn test_schnorr_verify_identity_public_key() {
+        let ok = test_gadget(
+            |composer| {
+                // Under the identity `s * G = r` holds for any message, so
+                // only a message whose hash can be split is looked for
+                let s = JubJubScalar::from(7u64);
+                let r = AffinePoint::from(ExtendedPoint::from(GENERATOR) * s);
+                let identity = AffinePoint::identity();
+                let message = (0u64..)
+                    .map(Fr::from)
+                    .find(|message| {
+                        let hash = Poseidon::new().hash(&[
+                            r.get_u(),
+                            r.get_v(),
+                            identity.get_u(),
+                            identity.get_v(),
+                            *message,
+                        ]);
+                        util::num_bits(&hash) <= schnorr::CHALLENGE_BITS + schnorr::HASH_HIGH_BITS
+                    })
+                    .unwrap();
+
+                let public_key = (
+                    composer.add_input(identity.get_u()),
+                    composer.add_input(identity.get_v()),
+                );
+                let message = composer.add_input(message);
+                let r = (composer.add_input(r.get_u()), composer.add_input(r.get_v()));
+                let s = composer.add_input(Fr::from_bytes(&s.to_bytes()).unwrap());
+                composer.schnorr_verify(public_key, message, r, s);
+            },
+            8192,
+        );
+        assert!(!ok);
+    }
+
+    // SHA-256 digest of "abc", from the examples of FIPS 180-2
+    const SHA256_ABC: [u8; 32] = [
+        0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22,
+        0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00,
+        0x15, 0xad,
+    ];
+
+    // Allocates a witness for every byte of `bytes`
+    fn add_bytes(composer: &mut StandardComposer, bytes: &[u8]) -> Vec<Variable> {
+        bytes
+            .iter()
+            .map(|byte| composer.add_input(Fr::from(u64::from(*byte))))
+            .collect()
     }
 
-    #[cfg(feature = "trace")]
     #[test]
-    fn test_prove_verify() {
+    fn test_sha256() {
         let ok = test_gadget(
             |composer| {
-                // do nothing except add the dummy constraints
+                let message = add_bytes(composer, b"abc");
+                let digest = composer.sha256(&message);
+                for (byte, expected) in digest.iter().zip(SHA256_ABC.iter()) {
+                    composer.constrain_to_constant(*byte, Fr::from(u64::from(*expected)), None);
+                }
             },
-            200,
+            1 << 15,
         );
         assert!(ok);
     }
 
     #[test]
-    fn test_logic_xor_constraint() {
-        // Should pass since the XOR result is correct and the bit-num is even.
+    fn test_incorrect_sha256() {
         let ok = test_gadget(
             |composer| {
-                let witness_a = composer.add_input(Scalar::from(500u64));
-                let witness_b = composer.add_input(Scalar::from(357u64));
-                let xor_res = composer.logic_gate(witness_a, witness_b, 10, true);
-                // Check that the XOR result is indeed what we are expecting.
-                composer.constrain_to_constant(
-                    xor_res,
-                    Scalar::from(500u64 ^ 357u64),
-                    Scalar::zero(),
-                );
+                // The last byte of the digest is wrong
+                let mut wrong_digest = SHA256_ABC;
+                wrong_digest[31] ^= 1;
+
+                let message = add_bytes(composer, b"abc");
+                let digest = composer.sha256(&message);
+                for (byte, expected) in digest.iter().zip(wrong_digest.iter()) {
+                    composer.constrain_to_constant(*byte, Fr::from(u64::from(*expected)), None);
+                }
+            },
+            1 << 15,
+        );
+        assert!(!ok);
+    }
+
+    // BLAKE2s digest of "abc" under the personalization "Zcash_PH"
+    const BLAKE2S_ABC: [u8; 32] = [
+        0x08, 0xe4, 0x56, 0x64, 0xfe, 0x33, 0x4f, 0xb3, 0x02, 0xd6, 0x5e, 0x60, 0x72, 0xd9, 0xf3,
+        0x43, 0x96, 0x7a, 0x96, 0x70, 0xbf, 0x8e, 0xd3, 0x1c, 0x1a, 0x44, 0xf8, 0x92, 0x82, 0xa3,
+        0xbd, 0xbe,
+    ];
+
+    #[test]
+    fn test_blake2s() {
+        let ok = test_gadget(
+            |composer| {
+                let message = add_bytes(composer, b"abc");
+                let digest = composer.blake2s(&message, b"Zcash_PH");
+                for (byte, expected) in digest.iter().zip(BLAKE2S_ABC.iter()) {
+                    composer.constrain_to_constant(*byte, Fr::from(u64::from(*expected)), None);
+                }
+            },
+            1 << 15,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_incorrect_blake2s() {
+        let ok = test_gadget(
+            |composer| {
+                // The digest is the one of a different personalization
+                let message = add_bytes(composer, b"abc");
+                let digest = composer.blake2s(&message, b"Zcash_ph");
+                for (byte, expected) in digest.iter().zip(BLAKE2S_ABC.iter()) {
+                    composer.constrain_to_constant(*byte, Fr::from(u64::from(*expected)), None);
+                }
+            },
+            1 << 15,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_lookup_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let xor = composer.add_lookup_table(LookupTable::xor(4));
+                let and = composer.add_lookup_table(LookupTable::and(4));
+                let range = composer.add_lookup_table(LookupTable::range(8));
+
+                let a = composer.add_input(Fr::from(0b1010u64));
+                let b = composer.add_input(Fr::from(0b0110u64));
+                let a_xor_b = composer.add_input(Fr::from(0b1100u64));
+                let a_and_b = composer.add_input(Fr::from(0b0010u64));
+                let byte = composer.add_input(Fr::from(255u64));
+                let zero = composer.zero_var;
+
+                composer.lookup_gate(a, b, a_xor_b, xor);
+                composer.lookup_gate(a, b, a_and_b, and);
+                composer.lookup_gate(byte, zero, zero, range);
+            },
+            1024,
+        );
+        assert!(ok);
+    }
+
+    #[test]
+    fn test_incorrect_lookup_gate() {
+        let ok = test_gadget(
+            |composer| {
+                let xor = composer.add_lookup_table(LookupTable::xor(4));
+                let and = composer.add_lookup_table(LookupTable::and(4));
+
+                let a = composer.add_input(Fr::from(0b1010u64));
+                let b = composer.add_input(Fr::from(0b0110u64));
+                let a_xor_b = composer.add_input(Fr::from(0b1100u64));
+
+                // The row is in the XOR table but not in the AND one
+                composer.lookup_gate(a, b, a_xor_b, xor);
+                composer.lookup_gate(a, b, a_xor_b, and);
+            },
+            1024,
+        );
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_lookup_argument_only_with_tables() {
+        let public_parameters = PublicParameters::setup(2 * 256, &mut rand::thread_rng()).unwrap();
+
+        for with_tables in [false, true].iter() {
+            let mut composer = StandardComposer::new();
+            let a = composer.add_input(Fr::from(0b1010u64));
+            let b = composer.add_input(Fr::from(0b0110u64));
+            let a_xor_b = composer.add_input(Fr::from(0b1100u64));
+            if *with_tables {
+                let xor = composer.add_lookup_table(LookupTable::xor(4));
+                composer.lookup_gate(a, b, a_xor_b, xor);
+            } else {
+                composer.add((Fr::one(), a), (Fr::one(), b), Fr::zero(), None);
+            }
+            composer.add_dummy_constraints();
+
+            let (ck, vk) = public_parameters.trim_for_circuit(&composer).unwrap();
+            let (prover_circuit_key, verifier_circuit_key) =
+                composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+            assert_eq!(verifier_circuit_key.lookup.is_some(), *with_tables);
+
+            let mut transcript = Transcript::new(b"");
+            verifier_circuit_key.seed_transcript(&mut transcript);
+            let mut proof = composer
+                .prove(
+                    &ck,
+                    &prover_circuit_key,
+                    &mut transcript.clone(),
+                    &mut rand::thread_rng(),
+                )
+                .unwrap();
+
+            // Only the proofs of circuits with lookup tables pay for the lookup argument
+            let bytes = proof.to_bytes();
+            if *with_tables {
+                assert!(proof.lookup.is_some());
+                assert_eq!(bytes.len(), Proof::SIZE + Proof::LOOKUP_SIZE);
+            } else {
+                assert!(proof.lookup.is_none());
+                assert_eq!(bytes.len(), Proof::SIZE);
+            }
+            assert!(Proof::from_bytes(&bytes)
+                .unwrap()
+                .verify(
+                    &verifier_circuit_key,
+                    &mut transcript.clone(),
+                    &vk,
+                    &composer.public_inputs()
+                )
+                .is_ok());
+
+            // The lookup argument cannot be stripped from the proof
+            if *with_tables {
+                proof.lookup = None;
+                match proof.verify(
+                    &verifier_circuit_key,
+                    &mut transcript.clone(),
+                    &vk,
+                    &composer.public_inputs(),
+                ) {
+                    Err(Error::ProofVerificationFailed) => {}
+                    _ => panic!("a proof without its lookup argument must be rejected"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_conditional_select_and_swap() {
+        let ok = test_gadget(
+            |composer| {
+                let a = composer.add_input(Fr::from(10u64));
+                let b = composer.add_input(Fr::from(20u64));
+                for bit in 0..2u64 {
+                    let bit_var = composer.add_input(Fr::from(bit));
+                    composer.bool_gate(bit_var);
+
+                    let (first, second) = match bit {
+                        1 => (Fr::from(20u64), Fr::from(10u64)),
+                        _ => (Fr::from(10u64), Fr::from(20u64)),
+                    };
+                    let selected = composer.conditional_select(bit_var, a, b);
+                    composer.constrain_to_constant(selected, second, None);
+
+                    let (x, y) = composer.conditional_swap(bit_var, a, b);
+                    composer.constrain_to_constant(x, first, None);
+                    composer.constrain_to_constant(y, second, None);
+                }
             },
             200,
         );
         assert!(ok);
+    }
 
-        // Should pass since the AND result is correct even the bit-num is even.
+    #[test]
+    fn test_boolean_gadgets() {
         let ok = test_gadget(
             |composer| {
-                let witness_a = composer.add_input(Scalar::from(469u64));
-                let witness_b = composer.add_input(Scalar::from(321u64));
-                let xor_res = composer.logic_gate(witness_a, witness_b, 10, false);
-                // Check that the AND result is indeed what we are expecting.
-                composer.constrain_to_constant(
-                    xor_res,
-                    Scalar::from(469u64 & 321u64),
-                    Scalar::zero(),
-                );
+                for (a, b) in [(0u64, 0u64), (0, 1), (1, 0), (1, 1)].iter() {
+                    let a_var = composer.add_input(Fr::from(*a));
+                    let b_var = composer.add_input(Fr::from(*b));
+                    composer.bool_gate(a_var);
+                    composer.bool_gate(b_var);
+
+                    let not = composer.boolean_not(a_var);
+                    let and = composer.boolean_and(a_var, b_var);
+                    let or = composer.boolean_or(a_var, b_var);
+                    let xor = composer.boolean_xor(a_var, b_var);
+
+                    composer.constrain_to_constant(not, Fr::from(1 - a), None);
+                    composer.constrain_to_constant(and, Fr::from(a & b), None);
+                    composer.constrain_to_constant(or, Fr::from(a | b), None);
+                    composer.constrain_to_constant(xor, Fr::from(a ^ b), None);
+                }
             },
             200,
         );
         assert!(ok);
+    }
 
-        // Should not pass since the XOR result is not correct even the bit-num is even.
+    #[test]
+    fn test_is_zero_and_is_equal() {
         let ok = test_gadget(
             |composer| {
-                let witness_a = composer.add_input(Scalar::from(139u64));
-                let witness_b = composer.add_input(Scalar::from(33u64));
-                let xor_res = composer.logic_gate(witness_a, witness_b, 10, true);
-                // Check that the XOR result is indeed what we are expecting.
-                composer.constrain_to_constant(
-                    xor_res,
-                    Scalar::from(139u64 & 33u64),
-                    Scalar::zero(),
-                );
+                let zero = composer.zero_var;
+                let a = composer.add_input(Fr::from(7u64));
+                let b = composer.add_input(Fr::from(7u64));
+                let c = composer.add_input(Fr::from(8u64));
+
+                let zero_is_zero = composer.is_zero(zero);
+                let a_is_zero = composer.is_zero(a);
+                let a_is_b = composer.is_equal(a, b);
+                let a_is_c = composer.is_equal(a, c);
+
+                composer.constrain_to_constant(zero_is_zero, Fr::one(), None);
+                composer.constrain_to_constant(a_is_zero, Fr::zero(), None);
+                composer.constrain_to_constant(a_is_b, Fr::one(), None);
+                composer.constrain_to_constant(a_is_c, Fr::zero(), None);
             },
             200,
         );
-        assert!(!ok);
+        assert!(ok);
     }
 
     #[test]
-    #[should_panic]
-    fn test_logical_gate_odd_bit_num() {
-        // Should fail since the bit-num is odd.
+    fn test_incorrect_is_equal() {
         let ok = test_gadget(
             |composer| {
-                let witness_a = composer.add_input(Scalar::from(500u64));
-                let witness_b = composer.add_input(Scalar::from(499u64));
-                let xor_res = composer.logic_gate(witness_a, witness_b, 9, true);
-                // Check that the XOR result is indeed what we are expecting.
-                composer.constrain_to_constant(xor_res, Scalar::from(7u64), Scalar::zero());
+                let a = composer.add_input(Fr::from(7u64));
+                let c = composer.add_input(Fr::from(8u64));
+
+                let a_is_c = composer.is_equal(a, c);
+                composer.constrain_to_constant(a_is_c, Fr::one(), None);
             },
             200,
         );
-        assert!(ok);
+        assert!(!ok);
     }
 
     #[test]
-    fn test_range_constraint() {
-        // Should fail as the number is not 32 bits
+    fn test_less_than() {
         let ok = test_gadget(
             |composer| {
-                let witness = composer.add_input(Scalar::from((u32::max_value() as u64) + 1));
-                composer.range_gate(witness, 32);
+                // An odd and an even number of bits
+                for num_bits in [7, 8].iter() {
+                    let three = composer.add_input(Fr::from(3u64));
+                    let five = composer.add_input(Fr::from(5u64));
+                    let max = composer.add_input(Fr::from((1u64 << num_bits) - 1));
+                    let zero = composer.zero_var;
+
+                    let cases = [
+                        (three, five, Fr::one()),
+                        (five, three, Fr::zero()),
+                        (five, five, Fr::zero()),
+                        (three, max, Fr::one()),
+                        (max, zero, Fr::zero()),
+                    ];
+                    for (a, b, expected) in cases.iter() {
+                        let lt = composer.less_than(*a, *b, *num_bits);
+                        composer.constrain_to_constant(lt, *expected, None);
+                    }
+                    composer.assert_less_than(three, max, *num_bits);
+                }
             },
-            200,
+            1024,
         );
-        assert!(!ok);
+        assert!(ok);
+    }
 
-        // Should fail as number is greater than 32 bits
+    #[test]
+    fn test_incorrect_assert_less_than() {
         let ok = test_gadget(
             |composer| {
-                let witness = composer.add_input(Scalar::from(u64::max_value()));
-                composer.range_gate(witness, 32);
+                let five = composer.add_input(Fr::from(5u64));
+                composer.assert_less_than(five, five, 7);
             },
-            200,
+            256,
         );
         assert!(!ok);
+    }
 
-        // Should pass as the number is within 34 bits
+    #[test]
+    fn test_assert_in_range() {
         let ok = test_gadget(
             |composer| {
-                let witness = composer.add_input(Scalar::from(2u64.pow(34) - 1));
-                composer.range_gate(witness, 34);
+                let (lower, upper) = (Fr::from(1000u64), Fr::from(1_000_000_000_000_000_000u64));
+                for x in [1000u64, 123_456_789, 1_000_000_000_000_000_000].iter() {
+                    let x = composer.add_input(Fr::from(*x));
+                    composer.assert_in_range(x, lower, upper);
+                }
+                // A range holding a single value
+                let x = composer.add_input(Fr::from(42u64));
+                composer.assert_in_range(x, Fr::from(42u64), Fr::from(42u64));
             },
-            200,
+            1024,
         );
         assert!(ok);
     }
 
     #[test]
-    #[should_panic]
-    fn test_odd_bit_range() {
-        // Should fail as the number we we need a even number of bits
-        let _ok = test_gadget(
+    fn test_incorrect_assert_in_range() {
+        let below = test_gadget(
             |composer| {
-                let witness = composer.add_input(Scalar::from(u32::max_value() as u64));
-                composer.range_gate(witness, 33);
+                let x = composer.add_input(Fr::from(999u64));
+                composer.assert_in_range(
+                    x,
+                    Fr::from(1000u64),
+                    Fr::from(1_000_000_000_000_000_000u64),
+                );
             },
-            200,
+            1024,
+        );
+        assert!(!below);
+
+        let above = test_gadget(
+            |composer| {
+                let x = composer.add_input(Fr::from(1_000_000_000_000_000_001u64));
+                composer.assert_in_range(
+                    x,
+                    Fr::from(1000u64),
+                    Fr::from(1_000_000_000_000_000_000u64),
+                );
+            },
+            1024,
         );
+        assert!(!above);
     }
 
     #[test]
-    fn test_pi() {
+    fn test_uint_arithmetic() {
         let ok = test_gadget(
             |composer| {
-                let var_one = composer.add_input(Fr::one());
-
-                let should_be_three = composer.big_add(
-                    var_one.into(),
-                    var_one.into(),
-                    composer.zero_var.into(),
-                    Scalar::zero(),
-                    Scalar::one(),
-                );
-                composer.constrain_to_constant(should_be_three, Scalar::from(3), Scalar::zero());
-                let should_be_four = composer.big_add(
-                    var_one.into(),
-                    var_one.into(),
-                    composer.zero_var.into(),
-                    Scalar::zero(),
-                    Scalar::from(2),
+                let a = UInt32::new(composer, 0xffff_fff0);
+                let b = UInt32::new(composer, 0x20);
+                let (sum, carry) = a.wrapping_add(composer, &b);
+                composer.constrain_to_constant(sum.variable(), Scalar::from(0x10u64), None);
+                composer.constrain_to_constant(carry, Scalar::one(), None);
+
+                let (sum, carry) = b.wrapping_add(composer, &b);
+                composer.constrain_to_constant(sum.variable(), Scalar::from(0x40u64), None);
+                composer.constrain_to_constant(carry, Scalar::zero(), None);
+
+                let values = [a, a, b, UInt32::constant(composer, 7)];
+                let sum = UInt32::wrapping_sum(composer, &values);
+                let expected = 0xffff_fff0u32
+                    .wrapping_add(0xffff_fff0)
+                    .wrapping_add(0x20)
+                    .wrapping_add(7);
+                composer.constrain_to_constant(sum.variable(), Scalar::from(expected as u64), None);
+                assert_eq!(sum.value(composer), expected);
+
+                let x = UInt64::new(composer, u64::max_value());
+                let (sum, carry) = x.wrapping_add(composer, &x);
+                composer.constrain_to_constant(
+                    sum.variable(),
+                    Scalar::from(u64::max_value() - 1),
+                    None,
                 );
-                composer.constrain_to_constant(should_be_four, Scalar::from(4), Scalar::zero());
+                composer.constrain_to_constant(carry, Scalar::one(), None);
             },
-            200,
+            1024,
         );
         assert!(ok);
+
+        // Should fail since the sum wraps around
+        let ok = test_gadget(
+            |composer| {
+                let a = UInt8::new(composer, 200);
+                let (sum, _) = a.wrapping_add(composer, &a);
+                composer.constrain_to_constant(sum.variable(), Scalar::from(400u64), None);
+            },
+            1024,
+        );
+        assert!(!ok);
     }
 
     #[test]
-    fn test_correct_add_mul_gate() {
+    fn test_uint_bitwise() {
         let ok = test_gadget(
             |composer| {
-                // Verify that (4+5+5) * (6+7+7) = 280
-                let four = composer.add_input(Fr::from(4));
-                let five = composer.add_input(Fr::from(5));
-                let six = composer.add_input(Fr::from(6));
-                let seven = composer.add_input(Fr::from(7));
-
-                let fourteen = composer.big_add(
-                    four.into(),
-                    five.into(),
-                    five.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
-
-                let twenty = composer.big_add(
-                    six.into(),
-                    seven.into(),
-                    seven.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
+                let (x, y) = (0xdead_beefu32, 0x1234_5678u32);
+                let a = UInt32::new(composer, x);
+                let b = UInt32::new(composer, y);
+
+                let results = [
+                    (a.xor(composer, &b), x ^ y),
+                    (a.and(composer, &b), x & y),
+                    (a.not(composer), !x),
+                    (a.rotate_left(composer, 7), x.rotate_left(7)),
+                    (a.rotate_right(composer, 13), x.rotate_right(13)),
+                    (a.rotate_right(composer, 32), x),
+                    (a.shl(composer, 5), x << 5),
+                    (a.shr(composer, 11), x >> 11),
+                    (a.shr(composer, 32), 0),
+                ];
+                for (result, expected) in results.iter() {
+                    assert_eq!(result.value(composer), *expected);
+                    composer.constrain_to_constant(
+                        result.variable(),
+                        Scalar::from(*expected as u64),
+                        None,
+                    );
+                }
 
-                // There are quite a few ways to check the equation is correct, depending on your circumstance
-                // If we already have the output wire, we can constrain the output of the mul_gate to be equal to it
-                // If we do not, we can compute it using the `mul`
-                // If the output is public, we can also constrain the output wire of the mul gate to it. This is what this test does
-                let output = composer.mul(
-                    Scalar::one(),
-                    fourteen,
-                    twenty,
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
-                composer.constrain_to_constant(output, Scalar::from(280), Scalar::zero());
+                let z = UInt16::new(composer, 0x8001);
+                let rotated = z.rotate_left(composer, 1);
+                composer.constrain_to_constant(rotated.variable(), Scalar::from(3u64), None);
             },
-            200,
+            1024,
         );
         assert!(ok);
     }
 
     #[test]
-    fn test_correct_add_gate() {
+    fn test_uint_bits_and_bytes() {
         let ok = test_gadget(
             |composer| {
-                let zero = composer.add_input(Fr::zero());
-                let one = composer.add_input(Fr::one());
+                let a = UInt32::new(composer, 0x0102_0304);
+
+                let bytes = a.to_bytes_be(composer);
+                for (byte, expected) in bytes.iter().zip(0x0102_0304u32.to_be_bytes().iter()) {
+                    composer.constrain_to_constant(
+                        byte.variable(),
+                        Scalar::from(*expected as u64),
+                        None,
+                    );
+                }
+                let b = UInt32::from_bytes_le(composer, &a.to_bytes_le(composer));
+                composer.assert_equal(a.variable(), b.variable());
 
-                let c = composer.add(
-                    (Scalar::one(), one),
-                    (Scalar::zero(), zero),
-                    Scalar::from(2u64),
-                    Scalar::zero(),
+                let bits = a.to_bits(composer);
+                assert_eq!(bits.len(), 32);
+                composer.constrain_to_constant(bits[0], Scalar::zero(), None);
+                composer.constrain_to_constant(bits[2], Scalar::one(), None);
+                let b = UInt32::from_bits(composer, &bits);
+                composer.assert_equal(a.variable(), b.variable());
+
+                let word = UInt64::from_bytes_be(composer, &[bytes.clone(), bytes].concat());
+                composer.constrain_to_constant(
+                    word.variable(),
+                    Scalar::from(0x0102_0304_0102_0304u64),
+                    None,
                 );
-                composer.constrain_to_constant(c, Scalar::from(3), Scalar::zero());
             },
-            32,
+            1024,
         );
-        assert!(ok)
+        assert!(ok);
     }
+
     #[test]
-    fn test_correct_big_add_mul_gate() {
+    fn test_incorrect_uint() {
+        // Should fail since the value does not fit in 16 bits
         let ok = test_gadget(
             |composer| {
-                // Verify that (4+5+5) * (6+7+7) + (8*9) = 352
-                let four = composer.add_input(Fr::from(4));
-                let five = composer.add_input(Fr::from(5));
-                let six = composer.add_input(Fr::from(6));
-                let seven = composer.add_input(Fr::from(7));
-                let nine = composer.add_input(Fr::from(9));
+                let var = composer.add_input(Scalar::from(1u64 << 16));
+                UInt16::from_variable(composer, var);
+            },
+            1024,
+        );
+        assert!(!ok);
 
-                let fourteen = composer.big_add(
-                    four.into(),
-                    five.into(),
-                    five.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
+        // Should fail since a bit is not boolean
+        let ok = test_gadget(
+            |composer| {
+                let mut bits = vec![composer.zero_var; 8];
+                bits[0] = composer.add_input(Scalar::from(2u64));
+                UInt8::from_bits(composer, &bits);
+            },
+            1024,
+        );
+        assert!(!ok);
+    }
 
-                let twenty = composer.big_add(
-                    six.into(),
-                    seven.into(),
-                    seven.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
+    // The modulus of the base field of secp256k1 and the coordinates of its
+    // generator
+    const SECP256K1_P: [u64; 4] = [
+        0xffff_fffe_ffff_fc2f,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+    ];
+    const SECP256K1_GX: [u64; 4] = [
+        0x59f2_815b_16f8_1798,
+        0x029b_fcdb_2dce_28d9,
+        0x55a0_6295_ce87_0b07,
+        0x79be_667e_f9dc_bbac,
+    ];
+    const SECP256K1_GY: [u64; 4] = [
+        0x9c47_d08f_fb10_d4b8,
+        0xfd17_b448_a685_5419,
+        0x5da4_fbfc_0e11_08a8,
+        0x483a_da77_26a3_c465,
+    ];
 
-                let output = composer.big_mul(
-                    Scalar::one(),
-                    fourteen,
-                    twenty,
-                    (Scalar::from(8), nine),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
-                composer.constrain_to_constant(output, Scalar::from(352), Scalar::zero());
+    #[test]
+    fn test_non_native_arithmetic() {
+        let ok = test_gadget(
+            |composer| {
+                let field = NonNativeField::new(SECP256K1_P);
+                let x = NonNativeFieldElement::new(composer, &field, SECP256K1_GX);
+                let y = NonNativeFieldElement::new(composer, &field, SECP256K1_GY);
+
+                // The generator lies on the curve `y^2 = x^3 + 7`
+                let y_2 = y.mul(composer, &y);
+                let x_2 = x.mul(composer, &x);
+                let x_3 = x_2.mul(composer, &x);
+                let seven = NonNativeFieldElement::constant(composer, &field, [7, 0, 0, 0]);
+                let rhs = x_3.add(composer, &seven);
+                y_2.assert_equal(composer, &rhs);
+
+                // (x - y) + y = x and x * x^-1 = 1
+                let difference = x.sub(composer, &y);
+                let sum = difference.add(composer, &y);
+                sum.assert_equal(composer, &x);
+
+                let inverse = x.inverse(composer);
+                let one = NonNativeFieldElement::constant(composer, &field, [1, 0, 0, 0]);
+                let product = x.mul(composer, &inverse);
+                product.assert_equal(composer, &one);
             },
-            200,
+            4096,
         );
         assert!(ok);
     }
 
     #[test]
-    fn test_incorrect_add_mul_gate() {
+    fn test_incorrect_non_native_arithmetic() {
+        // Should fail since the point does not lie on the curve
         let ok = test_gadget(
             |composer| {
-                // Verify that (5+5) * (6+7) != 117
-                let five = composer.add_input(Fr::from(5));
-                let six = composer.add_input(Fr::from(6));
-                let seven = composer.add_input(Fr::from(7));
-
-                let five_plus_five = composer.big_add(
-                    five.into(),
-                    five.into(),
-                    composer.zero_var.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
-
-                let six_plus_seven = composer.big_add(
-                    six.into(),
-                    seven.into(),
-                    composer.zero_var.into(),
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
+                let field = NonNativeField::new(SECP256K1_P);
+                let x = NonNativeFieldElement::new(composer, &field, SECP256K1_GX);
+                let y = NonNativeFieldElement::new(composer, &field, SECP256K1_GX);
+
+                let y_2 = y.mul(composer, &y);
+                let x_2 = x.mul(composer, &x);
+                let x_3 = x_2.mul(composer, &x);
+                let seven = NonNativeFieldElement::constant(composer, &field, [7, 0, 0, 0]);
+                let rhs = x_3.add(composer, &seven);
+                y_2.assert_equal(composer, &rhs);
+            },
+            2048,
+        );
+        assert!(!ok);
 
-                let output = composer.mul(
-                    Scalar::one(),
-                    five_plus_five,
-                    six_plus_seven,
-                    Scalar::zero(),
-                    Scalar::zero(),
-                );
-                composer.constrain_to_constant(output, Scalar::from(117), Scalar::zero());
+        // Should fail since zero has no inverse
+        let ok = test_gadget(
+            |composer| {
+                let field = NonNativeField::new(SECP256K1_P);
+                let zero = NonNativeFieldElement::new(composer, &field, [0; 4]);
+                zero.inverse(composer);
             },
-            200,
+            1024,
         );
         assert!(!ok);
     }
 
+    // Checks `a * b = c` when the first selector is set and `a * a` is the
+    // fourth wire of the next gate when the second one is
+    #[derive(Debug)]
+    struct TestWidget;
+
+    impl Widget for TestWidget {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn num_selectors(&self) -> usize {
+            2
+        }
+
+        fn compute_quotient_i(&self, selectors: &[Fr], wires: &WireEvaluations) -> Fr {
+            selectors[0] * (wires.a * wires.b - wires.c)
+                + selectors[1] * (wires.a.square() - wires.d_next)
+        }
+
+        fn compute_linearisation(
+            &self,
+            selectors: &[&Polynomial],
+            wires: &WireEvaluations,
+        ) -> Polynomial {
+            &(selectors[0] * &(wires.a * wires.b - wires.c))
+                + &(selectors[1] * &(wires.a.square() - wires.d_next))
+        }
+
+        fn compute_linearisation_commitment(
+            &self,
+            selectors: &[Commitment],
+            scalars: &mut Vec<Fr>,
+            points: &mut Vec<G1Affine>,
+            wires: &WireEvaluations,
+        ) {
+            scalars.push(wires.a * wires.b - wires.c);
+            points.push(selectors[0].0);
+            scalars.push(wires.a.square() - wires.d_next);
+            points.push(selectors[1].0);
+        }
+    }
+
+    // Has the same number of selectors as `TestWidget` but checks
+    // `a * b = c` alone
+    #[derive(Debug)]
+    struct OtherWidget;
+
+    impl Widget for OtherWidget {
+        fn name(&self) -> &'static str {
+            "other"
+        }
+
+        fn num_selectors(&self) -> usize {
+            2
+        }
+
+        fn compute_quotient_i(&self, selectors: &[Fr], wires: &WireEvaluations) -> Fr {
+            selectors[0] * (wires.a * wires.b - wires.c)
+        }
+
+        fn compute_linearisation(
+            &self,
+            selectors: &[&Polynomial],
+            wires: &WireEvaluations,
+        ) -> Polynomial {
+            selectors[0] * &(wires.a * wires.b - wires.c)
+        }
+
+        fn compute_linearisation_commitment(
+            &self,
+            selectors: &[Commitment],
+            scalars: &mut Vec<Fr>,
+            points: &mut Vec<G1Affine>,
+            wires: &WireEvaluations,
+        ) {
+            scalars.push(wires.a * wires.b - wires.c);
+            points.push(selectors[0].0);
+        }
+    }
+
     #[test]
-    fn test_correct_bool_gate() {
+    fn test_custom_gate() {
         let ok = test_gadget(
             |composer| {
-                let zero = composer.add_input(Fr::zero());
-                let one = composer.add_input(Fr::one());
-
-                composer.bool_gate(zero);
-                composer.bool_gate(one);
+                let widget = composer.add_widget(Box::new(TestWidget));
+                let zero = composer.zero_var;
+
+                let x = composer.add_input(Fr::from(3u64));
+                let y = composer.add_input(Fr::from(5u64));
+                let x_y = composer.add_input(Fr::from(15u64));
+                let x_sq = composer.add_input(Fr::from(9u64));
+
+                composer.custom_gate(widget, x, y, x_y, zero, &[Fr::one(), Fr::zero()]);
+                composer.custom_gate(widget, x, zero, zero, zero, &[Fr::zero(), Fr::one()]);
+                composer.custom_gate(widget, zero, zero, zero, x_sq, &[Fr::zero(), Fr::zero()]);
+                composer.constrain_to_constant(x_sq, Fr::from(9u64), None);
             },
-            32,
+            200,
         );
-        assert!(ok)
+        assert!(ok);
     }
 
     #[test]
-    fn test_incorrect_bool_gate() {
+    fn test_incorrect_custom_gate() {
         let ok = test_gadget(
             |composer| {
-                let zero = composer.add_input(Fr::from(5));
-                let one = composer.add_input(Fr::one());
+                let widget = composer.add_widget(Box::new(TestWidget));
+                let zero = composer.zero_var;
 
-                composer.bool_gate(zero);
-                composer.bool_gate(one);
+                let x = composer.add_input(Fr::from(3u64));
+                let y = composer.add_input(Fr::from(5u64));
+                let wrong = composer.add_input(Fr::from(16u64));
+
+                composer.custom_gate(widget, x, y, wrong, zero, &[Fr::one(), Fr::zero()]);
             },
-            32,
+            200,
         );
-        assert!(!ok)
+        assert!(!ok);
+    }
+
+    #[test]
+    fn test_verify_without_widgets() {
+        let public_parameters = PublicParameters::setup(256, &mut rand::thread_rng()).unwrap();
+        let mut composer: StandardComposer = add_dummy_composer(7);
+        let widget = composer.add_widget(Box::new(TestWidget));
+        let zero = composer.zero_var;
+        composer.custom_gate(widget, zero, zero, zero, zero, &[Fr::one(), Fr::zero()]);
+
+        let (ck, vk) = public_parameters.trim_for_circuit(&composer).unwrap();
+        let mut transcript = Transcript::new(b"");
+        let (preprocessed_circuit, verifier_circuit_key) =
+            composer.preprocess(&ck, &mut transcript).unwrap();
+        let mut verifier_transcript = transcript.clone();
+        let proof = composer
+            .prove(
+                &ck,
+                &preprocessed_circuit,
+                &mut transcript,
+                &mut rand::thread_rng(),
+            )
+            .unwrap();
+
+        // The verifier must be given the custom widgets of the circuit
+        match proof.verify(
+            &verifier_circuit_key,
+            &mut verifier_transcript.clone(),
+            &vk,
+            &[],
+        ) {
+            Err(Error::MismatchedCircuit) => {}
+            _ => panic!("expected the circuit to be mismatched"),
+        }
+
+        // A different widget with as many selectors is not accepted either
+        match proof.verify_with_widgets(
+            &verifier_circuit_key,
+            &mut verifier_transcript.clone(),
+            &vk,
+            &[],
+            &[&OtherWidget],
+        ) {
+            Err(Error::MismatchedCircuit) => {}
+            _ => panic!("expected the circuit to be mismatched"),
+        }
+
+        assert!(proof
+            .verify_with_widgets(
+                &verifier_circuit_key,
+                &mut verifier_transcript.clone(),
+                &vk,
+                &[],
+                &[&TestWidget],
+            )
+            .is_ok());
+
+        // Proofs of circuits with custom widgets can be batch verified
+        let mut t_0 = verifier_transcript.clone();
+        let mut batch: [(&Proof, &[Fr], &mut dyn TranscriptProtocol); 1] =
+            [(&proof, &[][..], &mut t_0)];
+        assert!(Proof::batch_verify(
+            &mut batch,
+            &verifier_circuit_key,
+            &vk,
+            &[&TestWidget],
+            &mut rand::thread_rng()
+        )
+        .is_ok());
     }
 
     fn test_gadget(gadget: fn(composer: &mut StandardComposer), n: usize) -> bool {
@@ -1939,17 +5396,21 @@ mod tests {
             let mut composer: StandardComposer = add_dummy_composer(7);
             gadget(&mut composer);
 
-            let (ck, _) = public_parameters
-                .trim(2 * composer.circuit_size().next_power_of_two())
-                .unwrap();
-            let domain = EvaluationDomain::new(composer.circuit_size()).unwrap();
+            let (ck, _) = public_parameters.trim_for_circuit(&composer).unwrap();
             let mut transcript = Transcript::new(b"");
 
             // Preprocess circuit
-            let preprocessed_circuit = composer.preprocess(&ck, &mut transcript, &domain);
+            let (preprocessed_circuit, _) = composer.preprocess(&ck, &mut transcript).unwrap();
             (
-                composer.prove(&ck, &preprocessed_circuit, &mut transcript),
-                composer.public_inputs,
+                composer
+                    .prove(
+                        &ck,
+                        &preprocessed_circuit,
+                        &mut transcript,
+                        &mut rand::thread_rng(),
+                    )
+                    .unwrap(),
+                composer.public_inputs(),
             )
         };
         // Verifiers view
@@ -1958,16 +5419,24 @@ mod tests {
         let mut composer: StandardComposer = add_dummy_composer(7);
         gadget(&mut composer);
 
-        let (ck, vk) = public_parameters
-            .trim(composer.circuit_size().next_power_of_two())
-            .unwrap();
-        let domain = EvaluationDomain::new(composer.circuit_size()).unwrap();
-        // setup transcript
+        let (ck, vk) = public_parameters.trim_for_circuit(&composer).unwrap();
+        // Preprocess circuit, keeping only the verifier side of it
+        let (_, verifier_circuit_key) =
+            composer.preprocess(&ck, &mut Transcript::new(b"")).unwrap();
+        // setup transcript from the circuit key alone
         let mut transcript = Transcript::new(b"");
-        // Preprocess circuit
-        let preprocessed_circuit = composer.preprocess(&ck, &mut transcript, &domain);
-        // Verify proof
-        proof.verify(&preprocessed_circuit, &mut transcript, &vk, &public_inputs)
+        verifier_circuit_key.seed_transcript(&mut transcript);
+        // Verify proof, along with the custom widgets the gadget added
+        let widgets: Vec<&dyn Widget> = composer.widgets.iter().map(|w| w.as_ref()).collect();
+        proof
+            .verify_with_widgets(
+                &verifier_circuit_key,
+                &mut transcript,
+                &vk,
+                &public_inputs,
+                &widgets,
+            )
+            .is_ok()
     }
 
     #[test]
@@ -1984,7 +5453,7 @@ mod tests {
                 var_one.into(),
                 composer.zero_var.into(),
                 Scalar::zero(),
-                Scalar::zero(),
+                None,
             );
         }
 
@@ -0,0 +1,119 @@
+//! Lookup tables that the `StandardComposer` checks lookup gates against.
+//!
+//! Every row of a table holds three `Scalar`s, which are matched against
+//! the left, right and output wires of a `lookup_gate`. Tables are added to
+//! the composer with `add_lookup_table`, which assigns them the id that the
+//! lookup gates refer to them with.
+
+use bls12_381::Scalar;
+
+/// A table of rows of three `Scalar`s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LookupTable {
+    rows: Vec<[Scalar; 3]>,
+}
+
+impl LookupTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        LookupTable { rows: Vec::new() }
+    }
+
+    /// Appends the row `(a, b, c)` to the table.
+    pub fn insert_row(&mut self, a: Scalar, b: Scalar, c: Scalar) {
+        self.rows.push([a, b, c]);
+    }
+
+    /// Builds the table holding `(x, y, x ^ y)` for every pair of
+    /// `num_bits`-bit integers.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits` is larger than `16`.
+    pub fn xor(num_bits: usize) -> Self {
+        LookupTable::from_binary_op(num_bits, |x, y| x ^ y)
+    }
+
+    /// Builds the table holding `(x, y, x & y)` for every pair of
+    /// `num_bits`-bit integers.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits` is larger than `16`.
+    pub fn and(num_bits: usize) -> Self {
+        LookupTable::from_binary_op(num_bits, |x, y| x & y)
+    }
+
+    /// Builds the table holding `(x, 0, 0)` for every `num_bits`-bit
+    /// integer, so that looking up `(x, 0, 0)` checks that `x < 2^num_bits`.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits` is larger than `32`.
+    pub fn range(num_bits: usize) -> Self {
+        assert!(num_bits <= 32);
+        let mut table = LookupTable::new();
+        for x in 0..(1u64 << num_bits) {
+            table.insert_row(Scalar::from(x), Scalar::zero(), Scalar::zero());
+        }
+        table
+    }
+
+    /// Returns the number of rows of the table.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns true if `(a, b, c)` is a row of the table.
+    pub fn contains(&self, a: &Scalar, b: &Scalar, c: &Scalar) -> bool {
+        self.rows.iter().any(|row| row == &[*a, *b, *c])
+    }
+
+    pub(crate) fn rows(&self) -> &[[Scalar; 3]] {
+        &self.rows
+    }
+
+    fn from_binary_op(num_bits: usize, op: fn(u64, u64) -> u64) -> Self {
+        assert!(num_bits <= 16);
+        let mut table = LookupTable::new();
+        for x in 0..(1u64 << num_bits) {
+            for y in 0..(1u64 << num_bits) {
+                table.insert_row(Scalar::from(x), Scalar::from(y), Scalar::from(op(x, y)));
+            }
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_op_tables() {
+        let xor = LookupTable::xor(2);
+        let and = LookupTable::and(2);
+        assert_eq!(xor.len(), 16);
+        assert_eq!(and.len(), 16);
+
+        let (a, b) = (Scalar::from(2), Scalar::from(3));
+        assert!(xor.contains(&a, &b, &Scalar::one()));
+        assert!(!xor.contains(&a, &b, &Scalar::from(2)));
+        assert!(and.contains(&a, &b, &Scalar::from(2)));
+        assert!(!and.contains(&Scalar::from(4), &b, &Scalar::zero()));
+    }
+
+    #[test]
+    fn test_range_table() {
+        let table = LookupTable::range(3);
+        assert_eq!(table.len(), 8);
+        assert!(table.contains(&Scalar::from(7), &Scalar::zero(), &Scalar::zero()));
+        assert!(!table.contains(&Scalar::from(8), &Scalar::zero(), &Scalar::zero()));
+        assert!(LookupTable::new().is_empty());
+    }
+}
@@ -5,5 +5,11 @@
 pub mod variable;
 pub use variable::{Variable, WireData};
 pub mod composer;
+pub mod lookup;
+pub mod non_native;
+pub mod uint;
 
 pub use composer::StandardComposer;
+pub use lookup::LookupTable;
+pub use non_native::{NonNativeField, NonNativeFieldElement};
+pub use uint::{UInt16, UInt32, UInt64, UInt8};
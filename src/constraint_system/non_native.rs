@@ -0,0 +1,670 @@
+//! Arithmetic modulo a prime `p` other than the order of the BLS12-381
+//! scalar field, such as the fields of secp256k1, as a gadget of the
+//! `StandardComposer`.
+//!
+//! The modulus is chosen at runtime with `NonNativeField` and may take up
+//! to `256` bits. A `NonNativeFieldElement` is split in `NUM_LIMBS` limbs of
+//! `LIMB_BITS` bits, least significant first, which are range checked so
+//! that every element is smaller than `2^256`. Elements are only reduced
+//! modulo `p` by the witness, so two elements may hold different integers
+//! while being equal in the field.
+//!
+//! Every operation witnesses its result `r` along with a quotient `q` and
+//! checks an integer identity such as `a * b = q * p + r`. The identity is
+//! checked modulo the order of the scalar field, by composing the limbs, and
+//! modulo `2^(NUM_LIMBS * LIMB_BITS)`, by summing the products of the limbs
+//! two positions at a time and carrying what overflows them. Both sides are
+//! smaller than the product of both moduli, so by the Chinese remainder
+//! theorem the identity holds over the integers.
+
+use super::{StandardComposer, Variable};
+use bls12_381::Scalar;
+use std::cmp::Ordering;
+
+/// Number of bits of every limb but the most significant one.
+pub const LIMB_BITS: usize = 68;
+
+/// Number of limbs of an element.
+pub const NUM_LIMBS: usize = 4;
+
+// Number of bits of the most significant limb, which bounds the elements by
+// `2^256`
+const TOP_LIMB_BITS: usize = 256 - (NUM_LIMBS - 1) * LIMB_BITS;
+
+// Bound on the absolute value of the carries between the positions of the
+// limbs, which are range checked once shifted by `2^CARRY_BITS`
+const CARRY_BITS: usize = 80;
+
+// Integers wide enough to hold the products of two elements, least
+// significant word first
+type Wide = [u64; 9];
+
+/// The prime field that a `NonNativeFieldElement` belongs to, given by its
+/// modulus as four 64-bit words, least significant first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonNativeField {
+    modulus: [u64; 4],
+}
+
+/// An element of a `NonNativeField`, held in `NUM_LIMBS` range checked limbs.
+#[derive(Debug, Clone, Copy)]
+pub struct NonNativeFieldElement {
+    limbs: [Variable; NUM_LIMBS],
+    field: NonNativeField,
+}
+
+impl NonNativeField {
+    /// Creates the field of integers modulo `modulus`.
+    ///
+    /// # Panics
+    ///
+    /// If the modulus is smaller than two.
+    pub fn new(modulus: [u64; 4]) -> Self {
+        assert!(modulus[1..].iter().any(|word| *word != 0) || modulus[0] > 1);
+        NonNativeField { modulus }
+    }
+
+    /// Returns the modulus of the field.
+    pub fn modulus(&self) -> [u64; 4] {
+        self.modulus
+    }
+
+    // Returns the smallest multiple of the modulus which is larger than any
+    // element, so that adding it to a difference keeps it positive
+    fn offset(&self) -> Wide {
+        let mut bound = [0u64; 9];
+        bound[4] = 1;
+        let (_, rem) = divrem(&bound, &self.modulus);
+        wide_sub(&wide_add(&bound, &widen(&self.modulus)), &widen(&rem))
+    }
+}
+
+impl NonNativeFieldElement {
+    /// Allocates a witness holding `value` modulo `p`, given as four 64-bit
+    /// words, least significant first. Its limbs are range checked.
+    pub fn new(composer: &mut StandardComposer, field: &NonNativeField, value: [u64; 4]) -> Self {
+        let (_, value) = divrem(&widen(&value), &field.modulus);
+        Self::alloc(composer, field, &value)
+    }
+
+    /// Allocates an element whose limbs are constrained to the ones of the
+    /// constant `value` modulo `p`.
+    pub fn constant(
+        composer: &mut StandardComposer,
+        field: &NonNativeField,
+        value: [u64; 4],
+    ) -> Self {
+        let (_, value) = divrem(&widen(&value), &field.modulus);
+        let mut limbs = [composer.zero_var; NUM_LIMBS];
+        for (limb, value) in limbs.iter_mut().zip(to_limbs(&value).iter()) {
+            *limb = composer.add_input(*value);
+            composer.constrain_to_constant(*limb, *value, None);
+        }
+        NonNativeFieldElement {
+            limbs,
+            field: *field,
+        }
+    }
+
+    /// Returns the `Variable`s holding the limbs, least significant first.
+    pub fn limbs(&self) -> [Variable; NUM_LIMBS] {
+        self.limbs
+    }
+
+    /// Returns the field of the element.
+    pub fn field(&self) -> NonNativeField {
+        self.field
+    }
+
+    /// Returns the integer that the composer holds for the element, which
+    /// is zero on a `witness_free` composer.
+    pub fn value(&self, composer: &StandardComposer) -> [u64; 4] {
+        let mut limbs = [Scalar::zero(); NUM_LIMBS];
+        for (value, limb) in limbs.iter_mut().zip(self.limbs.iter()) {
+            *value = composer.variables[limb];
+        }
+        from_limbs(&limbs)
+    }
+
+    /// Adds both elements modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// If the elements belong to different fields.
+    pub fn add(&self, composer: &mut StandardComposer, other: &Self) -> Self {
+        assert_eq!(self.field, other.field);
+        let sum = wide_add(
+            &widen(&self.value(composer)),
+            &widen(&other.value(composer)),
+        );
+        let one = Scalar::one();
+        self.reduce(composer, &[], &[(one, self), (one, other)], &[0; 9], &sum)
+    }
+
+    /// Subtracts `other` from the element modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// If the elements belong to different fields.
+    pub fn sub(&self, composer: &mut StandardComposer, other: &Self) -> Self {
+        assert_eq!(self.field, other.field);
+        let offset = self.field.offset();
+        let difference = wide_sub(
+            &wide_add(&widen(&self.value(composer)), &offset),
+            &widen(&other.value(composer)),
+        );
+        let terms = [(Scalar::one(), self), (-Scalar::one(), other)];
+        self.reduce(composer, &[], &terms, &offset, &difference)
+    }
+
+    /// Multiplies both elements modulo `p`.
+    ///
+    /// # Panics
+    ///
+    /// If the elements belong to different fields.
+    pub fn mul(&self, composer: &mut StandardComposer, other: &Self) -> Self {
+        assert_eq!(self.field, other.field);
+        let product = wide_mul(&self.value(composer), &other.value(composer));
+        let products = [(Scalar::one(), self, other)];
+        self.reduce(composer, &products, &[], &[0; 9], &product)
+    }
+
+    /// Computes the inverse of the element modulo `p`, which must be prime.
+    /// The circuit is not satisfied if the element is zero.
+    pub fn inverse(&self, composer: &mut StandardComposer) -> Self {
+        let modulus = self.field.modulus;
+        let value = self.value(composer);
+        let exponent = narrow(&wide_sub(&widen(&modulus), &[2, 0, 0, 0, 0, 0, 0, 0, 0]));
+        let inverse = Self::alloc(composer, &self.field, &pow_mod(&value, &exponent, &modulus));
+
+        // a * a^-1 - 1 = q * p
+        let (quotient, _) = divrem(&wide_mul(&value, &inverse.value(composer)), &modulus);
+        let quotient = Self::alloc(composer, &self.field, &quotient);
+        let mut one = [0u64; 9];
+        one[0] = 1;
+        assert_identity(
+            composer,
+            &self.field,
+            &[(Scalar::one(), self, &inverse)],
+            &[],
+            (-Scalar::one(), &one),
+            &quotient,
+        );
+        inverse
+    }
+
+    /// Asserts that both elements are equal modulo `p`, even if they hold
+    /// different integers.
+    ///
+    /// # Panics
+    ///
+    /// If the elements belong to different fields.
+    pub fn assert_equal(&self, composer: &mut StandardComposer, other: &Self) {
+        assert_eq!(self.field, other.field);
+        // a - b + offset = q * p
+        let offset = self.field.offset();
+        let difference = wide_sub(
+            &wide_add(&widen(&self.value(composer)), &offset),
+            &widen(&other.value(composer)),
+        );
+        let (quotient, _) = divrem(&difference, &self.field.modulus);
+        let quotient = Self::alloc(composer, &self.field, &quotient);
+        assert_identity(
+            composer,
+            &self.field,
+            &[],
+            &[(Scalar::one(), self), (-Scalar::one(), other)],
+            (Scalar::one(), &offset),
+            &quotient,
+        );
+    }
+
+    // Allocates an element holding `value`, whose limbs are range checked so
+    // that the circuit is not satisfied if it does not fit in `256` bits
+    fn alloc(composer: &mut StandardComposer, field: &NonNativeField, value: &[u64]) -> Self {
+        let mut limbs = [composer.zero_var; NUM_LIMBS];
+        for (i, (limb, value)) in limbs.iter_mut().zip(to_limbs(value).iter()).enumerate() {
+            *limb = composer.add_input(*value);
+            let num_bits = if i == NUM_LIMBS - 1 {
+                TOP_LIMB_BITS
+            } else {
+                LIMB_BITS
+            };
+            composer.range_gate(*limb, num_bits);
+        }
+        NonNativeFieldElement {
+            limbs,
+            field: *field,
+        }
+    }
+
+    // Witnesses `value` modulo `p`, where `value` is the integer that the
+    // products, the terms and the constant sum to, and checks that they sum
+    // to it plus a multiple of `p`
+    fn reduce(
+        &self,
+        composer: &mut StandardComposer,
+        products: &[(Scalar, &Self, &Self)],
+        terms: &[(Scalar, &Self)],
+        constant: &Wide,
+        value: &Wide,
+    ) -> Self {
+        let (quotient, remainder) = divrem(value, &self.field.modulus);
+        let quotient = Self::alloc(composer, &self.field, &quotient);
+        let remainder = Self::alloc(composer, &self.field, &remainder);
+
+        let mut terms = terms.to_vec();
+        terms.push((-Scalar::one(), &remainder));
+        assert_identity(
+            composer,
+            &self.field,
+            products,
+            &terms,
+            (Scalar::one(), constant),
+            &quotient,
+        );
+        remainder
+    }
+}
+
+// Constrains `sum(s * x * y) + sum(s * z) + s * c - q * p = 0` to hold over
+// the integers, where every sign `s` is one or minus one and the constant
+// `c` is smaller than `2^(NUM_LIMBS * LIMB_BITS)`.
+fn assert_identity(
+    composer: &mut StandardComposer,
+    field: &NonNativeField,
+    products: &[(Scalar, &NonNativeFieldElement, &NonNativeFieldElement)],
+    terms: &[(Scalar, &NonNativeFieldElement)],
+    constant: (Scalar, &Wide),
+    quotient: &NonNativeFieldElement,
+) {
+    let modulus = to_limbs(&field.modulus);
+    let constant_limbs = to_limbs(constant.1);
+    assert!((NUM_LIMBS * LIMB_BITS..64 * 9).all(|pos| !bit(constant.1, pos)));
+
+    // Modulo the order of the scalar field, with every element composed
+    let mut native_products = Vec::with_capacity(products.len());
+    for (sign, x, y) in products {
+        let x = compose(composer, &x.limbs);
+        let y = compose(composer, &y.limbs);
+        native_products.push((*sign, x, y));
+    }
+    let native_modulus = compose_constant(&modulus);
+    let mut native_terms = Vec::new();
+    for (sign, z) in terms {
+        for (i, limb) in z.limbs.iter().enumerate() {
+            native_terms.push((*sign * shift(i), *limb));
+        }
+    }
+    for (i, limb) in quotient.limbs.iter().enumerate() {
+        native_terms.push((-native_modulus * shift(i), *limb));
+    }
+    let sum = linear_combination(
+        composer,
+        &native_products,
+        &native_terms,
+        constant.0 * compose_constant(&constant_limbs),
+    );
+    composer.constrain_to_constant(sum, Scalar::zero(), None);
+
+    // Modulo `2^(NUM_LIMBS * LIMB_BITS)`, two positions of the limbs at a
+    // time. The positions `2g` and `2g + 1` together with the carry of the
+    // previous ones must sum to a multiple of `2^(2 * LIMB_BITS)`, whose
+    // quotient is carried to the next ones
+    let mut carry: Option<Variable> = None;
+    for g in 0..NUM_LIMBS / 2 {
+        let weight = |pos: usize| shift(pos - 2 * g);
+        let in_group = |pos: usize| pos / 2 == g;
+
+        let mut group_products = Vec::new();
+        for (sign, x, y) in products {
+            for (i, x) in x.limbs.iter().enumerate() {
+                for (j, y) in y.limbs.iter().enumerate() {
+                    if in_group(i + j) {
+                        group_products.push((*sign * weight(i + j), *x, *y));
+                    }
+                }
+            }
+        }
+
+        let mut group_terms = Vec::new();
+        for (sign, z) in terms {
+            for pos in 2 * g..2 * g + 2 {
+                group_terms.push((*sign * weight(pos), z.limbs[pos]));
+            }
+        }
+        for (i, limb) in quotient.limbs.iter().enumerate() {
+            let coefficient = modulus
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| in_group(i + j))
+                .fold(Scalar::zero(), |acc, (j, p)| acc - *p * weight(i + j));
+            if coefficient != Scalar::zero() {
+                group_terms.push((coefficient, *limb));
+            }
+        }
+
+        let mut group_constant =
+            constant.0 * (constant_limbs[2 * g] + constant_limbs[2 * g + 1] * shift(1));
+        if let Some(carry) = carry {
+            group_terms.push((Scalar::one(), carry));
+            group_constant -= pow2(CARRY_BITS);
+        }
+        let sum = linear_combination(composer, &group_products, &group_terms, group_constant);
+
+        // sum - 2^(2 * LIMB_BITS) * (next - 2^CARRY_BITS) = 0
+        let group_shift = shift(2);
+        let next_value =
+            composer.variables[&sum] * group_shift.invert().unwrap() + pow2(CARRY_BITS);
+        let next = composer.add_input(next_value);
+        composer.range_gate(next, CARRY_BITS + 1);
+        composer.big_add_gate(
+            sum,
+            next,
+            composer.zero_var,
+            composer.zero_var,
+            Scalar::one(),
+            -group_shift,
+            Scalar::zero(),
+            Scalar::zero(),
+            group_shift * pow2(CARRY_BITS),
+            None,
+        );
+        carry = Some(next);
+    }
+}
+
+// Returns a variable holding `sum(q_m * x * y) + sum(q * z) + constant`
+fn linear_combination(
+    composer: &mut StandardComposer,
+    products: &[(Scalar, Variable, Variable)],
+    terms: &[(Scalar, Variable)],
+    constant: Scalar,
+) -> Variable {
+    let mut acc = composer.zero_var;
+    let mut q_c = constant;
+    for (q_m, x, y) in products {
+        acc = composer.big_mul(*q_m, *x, *y, (Scalar::one(), acc), q_c, None);
+        q_c = Scalar::zero();
+    }
+    for pair in terms.chunks(2) {
+        let second = pair
+            .get(1)
+            .copied()
+            .unwrap_or((Scalar::zero(), composer.zero_var));
+        acc = composer.big_add((Scalar::one(), acc), pair[0], second, q_c, None);
+        q_c = Scalar::zero();
+    }
+    if q_c != Scalar::zero() {
+        acc = composer.add(
+            (Scalar::one(), acc),
+            (Scalar::zero(), composer.zero_var),
+            q_c,
+            None,
+        );
+    }
+    acc
+}
+
+// Composes the limbs of an element into a single variable, which holds the
+// element modulo the order of the scalar field
+fn compose(composer: &mut StandardComposer, limbs: &[Variable; NUM_LIMBS]) -> Variable {
+    let terms: Vec<(Scalar, Variable)> = limbs
+        .iter()
+        .enumerate()
+        .map(|(i, limb)| (shift(i), *limb))
+        .collect();
+    linear_combination(composer, &[], &terms, Scalar::zero())
+}
+
+// Composes constant limbs into a scalar
+fn compose_constant(limbs: &[Scalar; NUM_LIMBS]) -> Scalar {
+    limbs
+        .iter()
+        .enumerate()
+        .fold(Scalar::zero(), |acc, (i, limb)| acc + *limb * shift(i))
+}
+
+// Returns `2^n` as a `Scalar`
+fn pow2(n: usize) -> Scalar {
+    Scalar::from(2u64).pow(&[n as u64, 0, 0, 0])
+}
+
+// Returns the weight `2^(i * LIMB_BITS)` of the limb at position `i`
+fn shift(i: usize) -> Scalar {
+    pow2(i * LIMB_BITS)
+}
+
+// Returns the bit of `value` at position `pos`
+fn bit(value: &[u64], pos: usize) -> bool {
+    value
+        .get(pos >> 6)
+        .map_or(false, |word| (word >> (pos & 63)) & 1 == 1)
+}
+
+// Splits the lowest `NUM_LIMBS * LIMB_BITS` bits of `value` in limbs
+fn to_limbs(value: &[u64]) -> [Scalar; NUM_LIMBS] {
+    let mut limbs = [Scalar::zero(); NUM_LIMBS];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let mut words = [0u64; 2];
+        for j in (0..LIMB_BITS).filter(|j| bit(value, i * LIMB_BITS + j)) {
+            words[j >> 6] |= 1 << (j & 63);
+        }
+        *limb = Scalar::from_raw([words[0], words[1], 0, 0]);
+    }
+    limbs
+}
+
+// Composes limbs back into the integer they were split from, which must
+// fit in `256` bits
+fn from_limbs(limbs: &[Scalar; NUM_LIMBS]) -> [u64; 4] {
+    let mut value = [0u64; 4];
+    for (i, limb) in limbs.iter().enumerate() {
+        let bytes = limb.to_bytes();
+        for j in (0..LIMB_BITS).filter(|j| (bytes[j >> 3] >> (j & 7)) & 1 == 1) {
+            let pos = i * LIMB_BITS + j;
+            if pos < 256 {
+                value[pos >> 6] |= 1 << (pos & 63);
+            }
+        }
+    }
+    value
+}
+
+fn widen(value: &[u64; 4]) -> Wide {
+    let mut wide = [0u64; 9];
+    wide[..4].copy_from_slice(value);
+    wide
+}
+
+fn narrow(value: &Wide) -> [u64; 4] {
+    assert!(value[4..].iter().all(|word| *word == 0));
+    let mut narrow = [0u64; 4];
+    narrow.copy_from_slice(&value[..4]);
+    narrow
+}
+
+fn wide_cmp(a: &Wide, b: &Wide) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+fn wide_add(a: &Wide, b: &Wide) -> Wide {
+    let mut sum = [0u64; 9];
+    let mut carry = 0u128;
+    for (i, word) in sum.iter_mut().enumerate() {
+        let total = u128::from(a[i]) + u128::from(b[i]) + carry;
+        *word = total as u64;
+        carry = total >> 64;
+    }
+    sum
+}
+
+// Computes `a - b`, where `a` must not be smaller than `b`
+fn wide_sub(a: &Wide, b: &Wide) -> Wide {
+    let mut difference = [0u64; 9];
+    let mut borrow = false;
+    for (i, word) in difference.iter_mut().enumerate() {
+        let (value, borrow_1) = a[i].overflowing_sub(b[i]);
+        let (value, borrow_2) = value.overflowing_sub(borrow as u64);
+        *word = value;
+        borrow = borrow_1 || borrow_2;
+    }
+    difference
+}
+
+fn wide_mul(a: &[u64; 4], b: &[u64; 4]) -> Wide {
+    let mut product = [0u64; 9];
+    for (i, a) in a.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, b) in b.iter().enumerate() {
+            let total = u128::from(product[i + j]) + u128::from(*a) * u128::from(*b) + carry;
+            product[i + j] = total as u64;
+            carry = total >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    product
+}
+
+// Divides `value` by `modulus` bit by bit, returning the quotient and the
+// remainder
+fn divrem(value: &Wide, modulus: &[u64; 4]) -> (Wide, [u64; 4]) {
+    let modulus = widen(modulus);
+    let mut quotient = [0u64; 9];
+    let mut remainder = [0u64; 9];
+    for pos in (0..64 * 9).rev() {
+        remainder = wide_add(&remainder, &remainder);
+        remainder[0] |= bit(value, pos) as u64;
+        if wide_cmp(&remainder, &modulus) != Ordering::Less {
+            remainder = wide_sub(&remainder, &modulus);
+            quotient[pos >> 6] |= 1 << (pos & 63);
+        }
+    }
+    (quotient, narrow(&remainder))
+}
+
+// Computes `base^exponent` modulo `modulus`
+fn pow_mod(base: &[u64; 4], exponent: &[u64; 4], modulus: &[u64; 4]) -> [u64; 4] {
+    let (_, mut result) = divrem(&[1, 0, 0, 0, 0, 0, 0, 0, 0], modulus);
+    for pos in (0..256).rev() {
+        result = divrem(&wide_mul(&result, &result), modulus).1;
+        if bit(exponent, pos) {
+            result = divrem(&wide_mul(&result, base), modulus).1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The base field of secp256k1
+    const SECP256K1_P: [u64; 4] = [
+        0xffff_fffe_ffff_fc2f,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+        0xffff_ffff_ffff_ffff,
+    ];
+
+    // The coordinates of the generator of secp256k1
+    const X: [u64; 4] = [
+        0x59f2_815b_16f8_1798,
+        0x029b_fcdb_2dce_28d9,
+        0x55a0_6295_ce87_0b07,
+        0x79be_667e_f9dc_bbac,
+    ];
+    const Y: [u64; 4] = [
+        0x9c47_d08f_fb10_d4b8,
+        0xfd17_b448_a685_5419,
+        0x5da4_fbfc_0e11_08a8,
+        0x483a_da77_26a3_c465,
+    ];
+
+    #[test]
+    fn test_native_arithmetic() {
+        let product = wide_mul(&X, &Y);
+        let (quotient, remainder) = divrem(&product, &SECP256K1_P);
+        let recomposed = wide_add(
+            &wide_mul(&narrow(&quotient), &SECP256K1_P),
+            &widen(&remainder),
+        );
+        assert_eq!(recomposed, product);
+        assert_eq!(
+            remainder,
+            [
+                0x56d7_e1ce_0179_fd9b,
+                0x7232_4aa9_dfd3_428a,
+                0x9d16_6034_cf3c_1a5a,
+                0xfd3d_c529_c6eb_60fb,
+            ]
+        );
+
+        let limbs = to_limbs(&X);
+        assert_eq!(from_limbs(&limbs), X);
+        assert_eq!(
+            limbs[0],
+            Scalar::from(X[0]) + Scalar::from(X[1] & 0xf) * pow2(64)
+        );
+    }
+
+    #[test]
+    fn test_element_values() {
+        let field = NonNativeField::new(SECP256K1_P);
+        let mut composer = StandardComposer::new();
+        let x = NonNativeFieldElement::new(&mut composer, &field, X);
+        let y = NonNativeFieldElement::new(&mut composer, &field, Y);
+
+        let sum = x.add(&mut composer, &y);
+        assert_eq!(
+            sum.value(&composer),
+            [
+                0xf63a_51eb_1208_ec50,
+                0xffb3_b123_d453_7cf2,
+                0xb345_5e91_dc98_13af,
+                0xc1f9_40f6_2080_8011,
+            ]
+        );
+
+        // Both differences, one of which wraps around the modulus
+        let difference = x.sub(&mut composer, &y);
+        assert_eq!(
+            difference.value(&composer),
+            [
+                0xbdaa_b0cb_1be7_42e0,
+                0x0584_4892_8748_d4bf,
+                0xf7fb_6699_c076_025e,
+                0x3183_8c07_d338_f746,
+            ]
+        );
+        let difference = y.sub(&mut composer, &x);
+        assert_eq!(
+            difference.value(&composer),
+            [
+                0x4255_4f33_e418_b94f,
+                0xfa7b_b76d_78b7_2b40,
+                0x0804_9966_3f89_fda1,
+                0xce7c_73f8_2cc7_08b9,
+            ]
+        );
+
+        let inverse = x.inverse(&mut composer);
+        assert_eq!(
+            inverse.value(&composer),
+            [
+                0xe61d_0036_87ca_9ef6,
+                0x67b8_e794_abfb_076b,
+                0x870a_aeb8_ad77_626a,
+                0x237a_fdf1_d293_8d86,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_reduces() {
+        let field = NonNativeField::new([13, 0, 0, 0]);
+        let mut composer = StandardComposer::new();
+        let x = NonNativeFieldElement::new(&mut composer, &field, [100, 0, 0, 0]);
+        assert_eq!(x.value(&composer), [9, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Unsigned integers of a fixed number of bits, held in a single `Variable`
+//! of the `StandardComposer`.
+//!
+//! Every `UInt8`, `UInt16`, `UInt32` and `UInt64` is constrained to fit in
+//! its number of bits when it is created, and every operation returns an
+//! integer which is constrained to fit as well. Arithmetic wraps around like
+//! the `wrapping_*` methods of the native integers, while the bitwise
+//! operations are built on the logic gates and rotations and shifts split
+//! the integer in two range checked parts.
+
+use super::{StandardComposer, Variable};
+use crate::util;
+use bls12_381::Scalar;
+
+macro_rules! uint_impl {
+    ($name:ident, $native:ty, $bits:expr, $doc:expr) => {
+        #[doc = $doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name {
+            var: Variable,
+        }
+
+        impl $name {
+            /// Number of bits of the integer.
+            pub const BITS: usize = $bits;
+
+            /// Allocates a witness holding `value`, which is range checked.
+            pub fn new(composer: &mut StandardComposer, value: $native) -> Self {
+                let var = composer.add_input(Scalar::from(value as u64));
+                composer.range_gate(var, Self::BITS);
+                $name { var }
+            }
+
+            /// Allocates a variable constrained to the constant `value`.
+            pub fn constant(composer: &mut StandardComposer, value: $native) -> Self {
+                let var = composer.add_input(Scalar::from(value as u64));
+                composer.constrain_to_constant(var, Scalar::from(value as u64), None);
+                $name { var }
+            }
+
+            /// Wraps a `Variable`, constraining it to fit in `BITS` bits.
+            pub fn from_variable(composer: &mut StandardComposer, var: Variable) -> Self {
+                composer.range_gate(var, Self::BITS);
+                $name { var }
+            }
+
+            /// Returns the `Variable` holding the integer.
+            pub fn variable(&self) -> Variable {
+                self.var
+            }
+
+            /// Returns the value that the composer holds for the integer,
+            /// which is zero on a `witness_free` composer.
+            pub fn value(&self, composer: &StandardComposer) -> $native {
+                value_of(composer, self.var) as $native
+            }
+
+            /// Adds both integers modulo `2^BITS`, returning the sum along with
+            /// the carry bit.
+            pub fn wrapping_add(
+                &self,
+                composer: &mut StandardComposer,
+                other: &Self,
+            ) -> (Self, Variable) {
+                let (var, carry) = wrapping_sum(composer, &[self.var, other.var], Self::BITS);
+                ($name { var }, carry)
+            }
+
+            /// Adds all of the integers modulo `2^BITS`, which only range
+            /// checks a single sum.
+            ///
+            /// # Panics
+            ///
+            /// If no integer is given.
+            pub fn wrapping_sum(composer: &mut StandardComposer, values: &[Self]) -> Self {
+                let vars: Vec<Variable> = values.iter().map(|value| value.var).collect();
+                let (var, _) = wrapping_sum(composer, &vars, Self::BITS);
+                $name { var }
+            }
+
+            /// Computes the bitwise XOR of both integers.
+            pub fn xor(&self, composer: &mut StandardComposer, other: &Self) -> Self {
+                // The bits of the result are not needed, so they are not split
+                let (var, _) = composer.logic_gate(self.var, other.var, Self::BITS, true);
+                $name { var }
+            }
+
+            /// Computes the bitwise AND of both integers.
+            pub fn and(&self, composer: &mut StandardComposer, other: &Self) -> Self {
+                // The bits of the result are not needed, so they are not split
+                let (var, _) = composer.logic_gate(self.var, other.var, Self::BITS, false);
+                $name { var }
+            }
+
+            /// Computes the bitwise NOT of the integer, which is `2^BITS - 1 - x`.
+            pub fn not(&self, composer: &mut StandardComposer) -> Self {
+                let var = composer.add(
+                    (-Scalar::one(), self.var),
+                    (Scalar::zero(), composer.zero_var),
+                    pow2(Self::BITS) - Scalar::one(),
+                    None,
+                );
+                $name { var }
+            }
+
+            /// Rotates the bits of the integer to the left by `n` positions.
+            pub fn rotate_left(&self, composer: &mut StandardComposer, n: usize) -> Self {
+                self.rotate_right(composer, Self::BITS - (n % Self::BITS))
+            }
+
+            /// Rotates the bits of the integer to the right by `n` positions.
+            pub fn rotate_right(&self, composer: &mut StandardComposer, n: usize) -> Self {
+                let n = n % Self::BITS;
+                if n == 0 {
+                    return *self;
+                }
+                let (high, low) = split(composer, self.var, Self::BITS, n);
+                let var = composer.add(
+                    (pow2(Self::BITS - n), low),
+                    (Scalar::one(), high),
+                    Scalar::zero(),
+                    None,
+                );
+                $name { var }
+            }
+
+            /// Shifts the integer to the left by `n` bits, discarding the bits
+            /// shifted out.
+            pub fn shl(&self, composer: &mut StandardComposer, n: usize) -> Self {
+                if n == 0 {
+                    return *self;
+                }
+                if n >= Self::BITS {
+                    return Self::constant(composer, 0);
+                }
+                let (_, low) = split(composer, self.var, Self::BITS, Self::BITS - n);
+                let var = composer.add(
+                    (pow2(n), low),
+                    (Scalar::zero(), composer.zero_var),
+                    Scalar::zero(),
+                    None,
+                );
+                $name { var }
+            }
+
+            /// Shifts the integer to the right by `n` bits, discarding the
+            /// bits shifted out.
+            pub fn shr(&self, composer: &mut StandardComposer, n: usize) -> Self {
+                if n == 0 {
+                    return *self;
+                }
+                if n >= Self::BITS {
+                    return Self::constant(composer, 0);
+                }
+                let (high, _) = split(composer, self.var, Self::BITS, n);
+                $name { var: high }
+            }
+
+            /// Decomposes the integer in its `BITS` boolean constrained bits,
+            /// least significant first.
+            pub fn to_bits(&self, composer: &mut StandardComposer) -> Vec<Variable> {
+                decompose(composer, self.var, Self::BITS, 1)
+            }
+
+            /// Composes an integer from its bits, least significant first,
+            /// which are boolean constrained.
+            ///
+            /// # Panics
+            ///
+            /// If the number of bits is not `BITS`.
+            pub fn from_bits(composer: &mut StandardComposer, bits: &[Variable]) -> Self {
+                assert_eq!(bits.len(), Self::BITS);
+                for bit in bits {
+                    composer.bool_gate(*bit);
+                }
+                let var = compose(composer, bits, 1);
+                $name { var }
+            }
+
+            /// Decomposes the integer in its bytes, least significant first.
+            pub fn to_bytes_le(&self, composer: &mut StandardComposer) -> Vec<UInt8> {
+                decompose(composer, self.var, Self::BITS, 8)
+                    .into_iter()
+                    .map(|var| UInt8 { var })
+                    .collect()
+            }
+
+            /// Decomposes the integer in its bytes, most significant first.
+            pub fn to_bytes_be(&self, composer: &mut StandardComposer) -> Vec<UInt8> {
+                let mut bytes = self.to_bytes_le(composer);
+                bytes.reverse();
+                bytes
+            }
+
+            /// Composes an integer from its bytes, least significant first.
+            ///
+            /// # Panics
+            ///
+            /// If the number of bytes is not `BITS / 8`.
+            pub fn from_bytes_le(composer: &mut StandardComposer, bytes: &[UInt8]) -> Self {
+                assert_eq!(bytes.len() * 8, Self::BITS);
+                let vars: Vec<Variable> = bytes.iter().map(|byte| byte.var).collect();
+                let var = compose(composer, &vars, 8);
+                $name { var }
+            }
+
+            /// Composes an integer from its bytes, most significant first.
+            ///
+            /// # Panics
+            ///
+            /// If the number of bytes is not `BITS / 8`.
+            pub fn from_bytes_be(composer: &mut StandardComposer, bytes: &[UInt8]) -> Self {
+                let mut bytes = bytes.to_vec();
+                bytes.reverse();
+                Self::from_bytes_le(composer, &bytes)
+            }
+        }
+    };
+}
+
+uint_impl!(UInt8, u8, 8, "An 8-bit unsigned integer.");
+uint_impl!(UInt16, u16, 16, "A 16-bit unsigned integer.");
+uint_impl!(UInt32, u32, 32, "A 32-bit unsigned integer.");
+uint_impl!(UInt64, u64, 64, "A 64-bit unsigned integer.");
+
+// Returns `2^n` as a `Scalar`.
+fn pow2(n: usize) -> Scalar {
+    Scalar::from(2u64).pow(&[n as u64, 0, 0, 0])
+}
+
+// Returns the lowest 64 bits of the value of a variable.
+fn value_of(composer: &StandardComposer, var: Variable) -> u64 {
+    let bytes = composer.variables[&var].to_bytes();
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(low)
+}
+
+// Adds the `num_bits`-bit values of `vars`, returning their sum modulo
+// `2^num_bits`, which is range checked, along with the carry, which is
+// range checked to the number of bits that the carry of that many values
+// can take.
+fn wrapping_sum(
+    composer: &mut StandardComposer,
+    vars: &[Variable],
+    num_bits: usize,
+) -> (Variable, Variable) {
+    assert!(!vars.is_empty());
+    let total = vars[1..].iter().fold(vars[0], |acc, var| {
+        composer.add(
+            (Scalar::one(), acc),
+            (Scalar::one(), *var),
+            Scalar::zero(),
+            None,
+        )
+    });
+
+    let value = vars
+        .iter()
+        .map(|var| u128::from(value_of(composer, *var)))
+        .sum::<u128>();
+    let sum = composer.add_input(Scalar::from((value & ((1u128 << num_bits) - 1)) as u64));
+    let carry = composer.add_input(Scalar::from((value >> num_bits) as u64));
+
+    // total - sum - 2^num_bits * carry = 0
+    composer.big_add_gate(
+        total,
+        sum,
+        carry,
+        composer.zero_var,
+        Scalar::one(),
+        -Scalar::one(),
+        -pow2(num_bits),
+        Scalar::zero(),
+        Scalar::zero(),
+        None,
+    );
+    composer.range_gate(sum, num_bits);
+    match util::num_bits(&Scalar::from((vars.len() - 1) as u64)) {
+        0 | 1 => {
+            composer.bool_gate(carry);
+        }
+        carry_bits => composer.range_gate(carry, carry_bits),
+    };
+
+    (sum, carry)
+}
+
+// Splits the `num_bits`-bit value of `var` in its bits above and below
+// position `at`, both of which are range checked.
+fn split(
+    composer: &mut StandardComposer,
+    var: Variable,
+    num_bits: usize,
+    at: usize,
+) -> (Variable, Variable) {
+    let value = value_of(composer, var);
+    let high = composer.add_input(Scalar::from(value >> at));
+    let low = composer.add_input(Scalar::from(value & ((1u64 << at) - 1)));
+
+    // 2^at * high + low - var = 0
+    composer.big_add_gate(
+        high,
+        low,
+        var,
+        composer.zero_var,
+        pow2(at),
+        Scalar::one(),
+        -Scalar::one(),
+        Scalar::zero(),
+        Scalar::zero(),
+        None,
+    );
+    composer.range_gate(high, num_bits - at);
+    composer.range_gate(low, at);
+
+    (high, low)
+}
+
+// Composes the limbs of `limb_bits` bits each, least significant first,
+// into a single variable.
+fn compose(composer: &mut StandardComposer, limbs: &[Variable], limb_bits: usize) -> Variable {
+    let shift = pow2(limb_bits);
+    limbs.iter().rev().fold(composer.zero_var, |acc, limb| {
+        composer.add((shift, acc), (Scalar::one(), *limb), Scalar::zero(), None)
+    })
+}
+
+// Decomposes the `num_bits`-bit value of `var` in limbs of `limb_bits` bits
+// each, least significant first, which are range checked and composed back
+// into `var`.
+fn decompose(
+    composer: &mut StandardComposer,
+    var: Variable,
+    num_bits: usize,
+    limb_bits: usize,
+) -> Vec<Variable> {
+    let value = value_of(composer, var);
+    let mask = (1u64 << limb_bits) - 1;
+    let limbs: Vec<Variable> = (0..num_bits / limb_bits)
+        .map(|i| {
+            let limb = composer.add_input(Scalar::from((value >> (i * limb_bits)) & mask));
+            match limb_bits {
+                1 => {
+                    composer.bool_gate(limb);
+                }
+                _ => composer.range_gate(limb, limb_bits),
+            };
+            limb
+        })
+        .collect();
+
+    let composed = compose(composer, &limbs, limb_bits);
+    composer.assert_equal(composed, var);
+    limbs
+}
@@ -0,0 +1,94 @@
+//! Errors returned by the PLONK proving system.
+
+use crate::commitment_scheme::kzg10::errors::Error as KZG10Error;
+
+/// Represents an error when preprocessing a circuit, proving it or
+/// verifying one of its proofs.
+#[derive(Fail, Debug)]
+pub enum Error {
+    /// This error occurs when the commitment scheme fails.
+    #[fail(display = "commitment scheme error: {}", _0)]
+    KZG10(#[fail(cause)] KZG10Error),
+    /// This error occurs when the `ProverKey` used to commit to the
+    /// polynomials of the circuit is not large enough.
+    #[fail(
+        display = "srs supports polynomials up to degree {} but degree {} is required",
+        available, required
+    )]
+    SRSTooSmall {
+        /// The degree of the polynomial that could not be committed to.
+        required: usize,
+        /// The maximum degree supported by the `ProverKey`.
+        available: usize,
+    },
+    /// This error occurs when the circuit is too large for an
+    /// `EvaluationDomain` to be built over the scalar field.
+    #[fail(display = "cannot build an evaluation domain of size {}", size)]
+    DomainTooLarge {
+        /// The number of coefficients the domain was requested for.
+        size: usize,
+    },
+    /// This error occurs when the selector and wire vectors of the
+    /// composer do not all have the same length.
+    #[fail(display = "the selector and wire vectors have different lengths")]
+    MismatchedPolyLen,
+    /// This error occurs when the circuit built by the composer is not the
+    /// one described by the `ProverCircuitKey` used to prove it.
+    #[fail(display = "the circuit does not match the circuit key")]
+    MismatchedCircuit,
+    /// This error occurs when a wire refers to a `Variable` that was not
+    /// allocated by the composer.
+    #[fail(display = "variable is not part of the composer")]
+    UnknownVariable,
+    /// This error occurs when trying to prove with a composer created
+    /// with `StandardComposer::witness_free`.
+    #[fail(display = "a witness-free composer can only be used to preprocess circuits")]
+    WitnessFreeComposer,
+    /// This error occurs when the number of public inputs given to the
+    /// verifier differs from the number of public inputs of the circuit.
+    #[fail(
+        display = "circuit has {} public inputs but {} were provided",
+        expected, found
+    )]
+    PublicInputsMismatch {
+        /// The number of public inputs of the circuit.
+        expected: usize,
+        /// The number of public inputs provided.
+        found: usize,
+    },
+    /// This error occurs when the openings of a proof do not pass the
+    /// pairing check, ie. the proof is not valid for the given circuit and
+    /// public inputs.
+    #[fail(display = "proof verification failed")]
+    ProofVerificationFailed,
+    /// This error occurs when the bytes given do not have the length of a
+    /// serialized `Proof`.
+    #[fail(
+        display = "proof must be {} bytes long, or {} bytes with a lookup argument",
+        expected, expected_with_lookup
+    )]
+    InvalidProofLength {
+        /// The length a serialized `Proof` without a lookup argument must have.
+        expected: usize,
+        /// The length a serialized `Proof` with a lookup argument must have.
+        expected_with_lookup: usize,
+    },
+    /// This error occurs when a commitment is not the encoding of a point
+    /// that lies on the curve.
+    #[fail(display = "commitment is not a valid point on the curve")]
+    PointNotOnCurve,
+    /// This error occurs when a commitment is a point on the curve which
+    /// does not belong to the prime order subgroup.
+    #[fail(display = "commitment is not a point of the prime order subgroup")]
+    PointNotInSubgroup,
+    /// This error occurs when an evaluation is not the canonical encoding
+    /// of a `Scalar`.
+    #[fail(display = "evaluation is not a canonically encoded scalar")]
+    NonCanonicalScalar,
+}
+
+impl From<KZG10Error> for Error {
+    fn from(err: KZG10Error) -> Self {
+        Error::KZG10(err)
+    }
+}
@@ -0,0 +1,249 @@
+//! Implementation of the BLAKE2s hash function with a `32`-byte digest,
+//! both natively and as a gadget of the `StandardComposer` built on the
+//! 32-bit words of `UInt32`.
+//!
+//! Both of them take an `8`-byte personalization, which is written in the
+//! parameter block and separates the digests of different applications.
+//! Keyed hashing and salts are not supported.
+//!
+//! Reference: https://tools.ietf.org/html/rfc7693
+
+use super::sha256::IV;
+use crate::constraint_system::{StandardComposer, UInt32, UInt8};
+
+// Number of bytes of a message block
+const BLOCK_SIZE: usize = 64;
+
+// Number of bytes of the digest
+const DIGEST_SIZE: usize = 32;
+
+// Message word permutations of every round
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+// Positions of the state words mixed by every call to `G`, first the
+// columns and then the diagonals
+const MIX: [[usize; 4]; 8] = [
+    [0, 4, 8, 12],
+    [1, 5, 9, 13],
+    [2, 6, 10, 14],
+    [3, 7, 11, 15],
+    [0, 5, 10, 15],
+    [1, 6, 11, 12],
+    [2, 7, 8, 13],
+    [3, 4, 9, 14],
+];
+
+/// Hashes `bytes` with BLAKE2s under the given personalization.
+pub fn hash(bytes: &[u8], personalization: &[u8; 8]) -> [u8; DIGEST_SIZE] {
+    let mut h = initial_state(personalization);
+
+    let num_blocks = num_blocks(bytes.len());
+    for i in 0..num_blocks {
+        let mut block = [0u8; BLOCK_SIZE];
+        let chunk = &bytes[(i * BLOCK_SIZE).min(bytes.len())..];
+        let len = chunk.len().min(BLOCK_SIZE);
+        block[..len].copy_from_slice(&chunk[..len]);
+
+        let mut m = [0u32; 16];
+        for (word, bytes) in m.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+
+        let mut v = [0u32; 16];
+        v[..8].copy_from_slice(&h);
+        v[8..].copy_from_slice(&tail(counter(i, bytes.len()), i == num_blocks - 1));
+        for sigma in SIGMA.iter() {
+            for (j, &[a, b, c, d]) in MIX.iter().enumerate() {
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[sigma[2 * j]]);
+                v[d] = (v[d] ^ v[a]).rotate_right(16);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(12);
+                v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[sigma[2 * j + 1]]);
+                v[d] = (v[d] ^ v[a]).rotate_right(8);
+                v[c] = v[c].wrapping_add(v[d]);
+                v[b] = (v[b] ^ v[c]).rotate_right(7);
+            }
+        }
+
+        for (j, word) in h.iter_mut().enumerate() {
+            *word ^= v[j] ^ v[j + 8];
+        }
+    }
+
+    let mut digest = [0u8; DIGEST_SIZE];
+    for (bytes, word) in digest.chunks_mut(4).zip(h.iter()) {
+        bytes.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+/// Hashes `bytes` with BLAKE2s under the given personalization inside of
+/// the circuit, returning the `32` bytes of the digest.
+///
+/// The length of the message and the personalization are part of the
+/// circuit description, so the counters and the padding of the blocks are
+/// constants. The result matches the one of the native `hash`.
+pub fn hash_gadget(
+    composer: &mut StandardComposer,
+    bytes: &[UInt8],
+    personalization: &[u8; 8],
+) -> Vec<UInt8> {
+    let mut h: Vec<UInt32> = initial_state(personalization)
+        .iter()
+        .map(|word| UInt32::constant(composer, *word))
+        .collect();
+
+    let zero = UInt8::constant(composer, 0);
+    let num_blocks = num_blocks(bytes.len());
+    for i in 0..num_blocks {
+        let mut block = [zero; BLOCK_SIZE];
+        let chunk = &bytes[(i * BLOCK_SIZE).min(bytes.len())..];
+        let len = chunk.len().min(BLOCK_SIZE);
+        block[..len].copy_from_slice(&chunk[..len]);
+
+        let m: Vec<UInt32> = block
+            .chunks(4)
+            .map(|bytes| UInt32::from_bytes_le(composer, bytes))
+            .collect();
+
+        let mut v = h.clone();
+        for word in tail(counter(i, bytes.len()), i == num_blocks - 1).iter() {
+            v.push(UInt32::constant(composer, *word));
+        }
+        for sigma in SIGMA.iter() {
+            for (j, &[a, b, c, d]) in MIX.iter().enumerate() {
+                v[a] = UInt32::wrapping_sum(composer, &[v[a], v[b], m[sigma[2 * j]]]);
+                v[d] = xor_rotate(composer, &v[d], &v[a], 16);
+                v[c] = v[c].wrapping_add(composer, &v[d]).0;
+                v[b] = xor_rotate(composer, &v[b], &v[c], 12);
+                v[a] = UInt32::wrapping_sum(composer, &[v[a], v[b], m[sigma[2 * j + 1]]]);
+                v[d] = xor_rotate(composer, &v[d], &v[a], 8);
+                v[c] = v[c].wrapping_add(composer, &v[d]).0;
+                v[b] = xor_rotate(composer, &v[b], &v[c], 7);
+            }
+        }
+
+        for (j, word) in h.iter_mut().enumerate() {
+            let mixed = v[j].xor(composer, &v[j + 8]);
+            *word = word.xor(composer, &mixed);
+        }
+    }
+
+    h.iter()
+        .flat_map(|word| word.to_bytes_le(composer))
+        .collect()
+}
+
+// Computes `rotr(x ^ y, r)`
+fn xor_rotate(composer: &mut StandardComposer, x: &UInt32, y: &UInt32, r: usize) -> UInt32 {
+    let x = x.xor(composer, y);
+    x.rotate_right(composer, r)
+}
+
+// Returns the initial state, which is the IV combined with the parameter
+// block: a `32`-byte digest, no key, a fanout and depth of one and the
+// personalization.
+fn initial_state(personalization: &[u8; 8]) -> [u32; 8] {
+    let mut h = IV;
+    h[0] ^= 0x0101_0000 ^ (DIGEST_SIZE as u32);
+    for (word, bytes) in h[6..].iter_mut().zip(personalization.chunks(4)) {
+        *word ^= u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    h
+}
+
+// Number of blocks that a message of `len` bytes is padded to, which is at
+// least one
+fn num_blocks(len: usize) -> usize {
+    ((len + BLOCK_SIZE - 1) / BLOCK_SIZE).max(1)
+}
+
+// Number of bytes of a message of `len` bytes that have been hashed once
+// the block `i` is compressed
+fn counter(i: usize, len: usize) -> u64 {
+    ((i + 1) * BLOCK_SIZE).min(len) as u64
+}
+
+// Returns the last eight words of the working vector, which hold the IV
+// combined with the counter and, for the last block, the finalization flag
+fn tail(counter: u64, is_last: bool) -> [u32; 8] {
+    let mut v = IV;
+    v[4] ^= counter as u32;
+    v[5] ^= (counter >> 32) as u32;
+    if is_last {
+        v[6] ^= u32::max_value();
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint_system::Variable;
+    use bls12_381::Scalar;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_reference_vectors() {
+        let vectors = [
+            (
+                &b""[..],
+                "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            ),
+            (
+                &b"abc"[..],
+                "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            ),
+        ];
+        for (message, digest) in vectors.iter() {
+            assert_eq!(hash(message, &[0u8; 8]).to_vec(), from_hex(digest));
+        }
+    }
+
+    #[test]
+    fn test_personalization() {
+        let digest = "08e45664fe334fb302d65e6072d9f343967a9670bf8ed31c1a44f89282a3bdbe";
+        assert_eq!(hash(b"abc", b"Zcash_PH").to_vec(), from_hex(digest));
+        assert_ne!(hash(b"abc", b"Zcash_PH"), hash(b"abc", b"Zcash_ph"));
+    }
+
+    #[test]
+    fn test_gadget_matches_native() {
+        // Cover the empty message, partial blocks and messages that fill
+        // a whole number of blocks
+        for len in [0usize, 3, 64, 65].iter() {
+            let message: Vec<u8> = (0..*len).map(|i| (i * 7) as u8).collect();
+            for personalization in [[0u8; 8], *b"Zcash_PH"].iter() {
+                let mut composer = StandardComposer::new();
+                let bytes: Vec<Variable> = message
+                    .iter()
+                    .map(|byte| composer.add_input(Scalar::from(u64::from(*byte))))
+                    .collect();
+                let digest: Vec<u8> = composer
+                    .blake2s(&bytes, personalization)
+                    .iter()
+                    .map(|byte| composer.variables[byte].to_bytes()[0])
+                    .collect();
+
+                assert_eq!(digest, hash(&message, personalization).to_vec());
+            }
+        }
+    }
+}
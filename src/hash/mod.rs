@@ -0,0 +1,7 @@
+//! Hash functions that can be computed both natively and inside of a
+//! circuit built with the `StandardComposer`.
+pub mod blake2s;
+pub mod poseidon;
+pub mod sha256;
+
+pub use poseidon::{Poseidon, Sponge, SpongeGadget};
@@ -0,0 +1,360 @@
+//! Implementation of the Poseidon hash function over the scalar field of
+//! BLS12-381, both natively and as a gadget of the `StandardComposer`.
+//!
+//! The permutation works over a state of `WIDTH` elements, the first one
+//! being the capacity of the sponge and the rest of them its rate. It uses
+//! the `x^5` S-box, `8` full rounds and `59` partial rounds.
+//!
+//! Reference: https://eprint.iacr.org/2019/458.pdf
+
+use crate::constraint_system::{StandardComposer, Variable};
+use crate::util;
+use bls12_381::Scalar;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+/// Number of elements of the state of the permutation.
+pub const WIDTH: usize = 5;
+// Number of elements absorbed by every call to the permutation
+const RATE: usize = WIDTH - 1;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 59;
+const TOTAL_ROUNDS: usize = FULL_ROUNDS + PARTIAL_ROUNDS;
+
+// Seed of the stream the round constants are drawn from
+const ROUND_CONSTANTS_SEED: [u8; 32] = *b"plonk-poseidon-bls12_381-width-5";
+
+/// Parameters of the Poseidon permutation: the round constants and the
+/// MDS matrix used in the linear layer of every round.
+#[derive(Debug, Clone)]
+pub struct Poseidon {
+    round_constants: Vec<[Scalar; WIDTH]>,
+    mds: [[Scalar; WIDTH]; WIDTH],
+}
+
+impl Default for Poseidon {
+    fn default() -> Self {
+        Poseidon::new()
+    }
+}
+
+impl Poseidon {
+    /// Generates the parameters of the permutation.
+    ///
+    /// The round constants are drawn from a ChaCha stream with a fixed seed
+    /// and the MDS matrix is the Cauchy matrix `1 / (i + WIDTH + j)`, so the
+    /// same parameters are obtained every time.
+    pub fn new() -> Self {
+        let mut rng = ChaChaRng::from_seed(ROUND_CONSTANTS_SEED);
+        let round_constants = (0..TOTAL_ROUNDS)
+            .map(|_| {
+                let mut constants = [Scalar::zero(); WIDTH];
+                for c in constants.iter_mut() {
+                    *c = util::random_scalar(&mut rng);
+                }
+                constants
+            })
+            .collect();
+
+        let mut mds = [[Scalar::zero(); WIDTH]; WIDTH];
+        for (i, row) in mds.iter_mut().enumerate() {
+            for (j, m) in row.iter_mut().enumerate() {
+                *m = Scalar::from((i + WIDTH + j) as u64).invert().unwrap();
+            }
+        }
+
+        Poseidon {
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Applies the Poseidon permutation to the given `state`.
+    pub fn permute(&self, state: &mut [Scalar; WIDTH]) {
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            for (s, c) in state.iter_mut().zip(constants.iter()) {
+                *s += c;
+            }
+
+            if is_full_round(round) {
+                for s in state.iter_mut() {
+                    *s = sbox(*s);
+                }
+            } else {
+                state[0] = sbox(state[0]);
+            }
+
+            let mut next = [Scalar::zero(); WIDTH];
+            for (n, row) in next.iter_mut().zip(self.mds.iter()) {
+                *n = row
+                    .iter()
+                    .zip(state.iter())
+                    .fold(Scalar::zero(), |acc, (m, s)| acc + m * s);
+            }
+            *state = next;
+        }
+    }
+
+    /// Hashes a variable-length slice of `Scalar`s into a single `Scalar`.
+    pub fn hash(&self, inputs: &[Scalar]) -> Scalar {
+        let mut sponge = Sponge::with_params(self.clone());
+        for input in inputs {
+            sponge.absorb(input);
+        }
+        sponge.squeeze()
+    }
+}
+
+/// Returns true if every element of the state goes through the S-box in
+/// the given round. Half of the full rounds go before the partial ones
+/// and the other half after them.
+fn is_full_round(round: usize) -> bool {
+    round < FULL_ROUNDS / 2 || round >= FULL_ROUNDS / 2 + PARTIAL_ROUNDS
+}
+
+fn sbox(x: Scalar) -> Scalar {
+    x.square().square() * x
+}
+
+/// A sponge built on the Poseidon permutation which absorbs any number of
+/// `Scalar`s and then squeezes a single one out of them.
+///
+/// Once every input is absorbed, a `1` is appended to them so that inputs
+/// of different lengths never produce the same state.
+#[derive(Debug, Clone)]
+pub struct Sponge {
+    poseidon: Poseidon,
+    state: [Scalar; WIDTH],
+    absorbed: usize,
+}
+
+impl Default for Sponge {
+    fn default() -> Self {
+        Sponge::new()
+    }
+}
+
+impl Sponge {
+    /// Creates an empty sponge.
+    pub fn new() -> Self {
+        Sponge::with_params(Poseidon::new())
+    }
+
+    /// Creates an empty sponge which uses the given parameters.
+    pub fn with_params(poseidon: Poseidon) -> Self {
+        Sponge {
+            poseidon,
+            state: [Scalar::zero(); WIDTH],
+            absorbed: 0,
+        }
+    }
+
+    /// Absorbs a `Scalar` into the rate of the sponge, permuting the state
+    /// first if the rate is already full.
+    pub fn absorb(&mut self, input: &Scalar) {
+        if self.absorbed == RATE {
+            self.poseidon.permute(&mut self.state);
+            self.absorbed = 0;
+        }
+        self.state[1 + self.absorbed] += input;
+        self.absorbed += 1;
+    }
+
+    /// Pads the absorbed inputs and squeezes the resulting hash.
+    pub fn squeeze(mut self) -> Scalar {
+        self.absorb(&Scalar::one());
+        self.poseidon.permute(&mut self.state);
+        self.state[1]
+    }
+}
+
+/// The in-circuit counterpart of `Sponge`, which absorbs `Variable`s into
+/// a `StandardComposer`, producing the same hash as the native sponge.
+///
+/// Every full round of the permutation costs `25` gates and every partial
+/// round `13`: the `x^5` S-box takes three multiplication gates and every
+/// element of the linear layer takes two `big_add_gate`s, which also add
+/// the round constants of the next round.
+#[derive(Debug, Clone)]
+pub struct SpongeGadget {
+    poseidon: Poseidon,
+    state: [Variable; WIDTH],
+    absorbed: usize,
+}
+
+impl SpongeGadget {
+    /// Creates an empty sponge gadget for the given composer.
+    pub fn new(composer: &StandardComposer) -> Self {
+        SpongeGadget {
+            poseidon: Poseidon::new(),
+            state: [composer.zero_var; WIDTH],
+            absorbed: 0,
+        }
+    }
+
+    /// Absorbs a `Variable` into the rate of the sponge, permuting the
+    /// state first if the rate is already full.
+    pub fn absorb(&mut self, composer: &mut StandardComposer, input: Variable) {
+        self.add_to_rate(composer, (Scalar::one(), input), Scalar::zero());
+    }
+
+    /// Pads the absorbed inputs and squeezes the `Variable` holding the
+    /// resulting hash.
+    pub fn squeeze(mut self, composer: &mut StandardComposer) -> Variable {
+        let zero = composer.zero_var;
+        self.add_to_rate(composer, (Scalar::zero(), zero), Scalar::one());
+        self.permute(composer);
+        self.state[1]
+    }
+
+    // Adds `q * input + constant` to the next element of the rate
+    fn add_to_rate(
+        &mut self,
+        composer: &mut StandardComposer,
+        input: (Scalar, Variable),
+        constant: Scalar,
+    ) {
+        if self.absorbed == RATE {
+            self.permute(composer);
+            self.absorbed = 0;
+        }
+        let s = &mut self.state[1 + self.absorbed];
+        *s = composer.add((Scalar::one(), *s), input, constant, None);
+        self.absorbed += 1;
+    }
+
+    fn permute(&mut self, composer: &mut StandardComposer) {
+        let zero = composer.zero_var;
+        let constants = &self.poseidon.round_constants;
+
+        // The constants of the first round are added on their own, the ones
+        // of the following rounds are added by the linear layer before them
+        for (s, c) in self.state.iter_mut().zip(constants[0].iter()) {
+            *s = composer.add((Scalar::one(), *s), (Scalar::zero(), zero), *c, None);
+        }
+
+        for round in 0..TOTAL_ROUNDS {
+            if is_full_round(round) {
+                for s in self.state.iter_mut() {
+                    *s = sbox_gadget(composer, *s);
+                }
+            } else {
+                self.state[0] = sbox_gadget(composer, self.state[0]);
+            }
+
+            let next_constants = constants
+                .get(round + 1)
+                .cloned()
+                .unwrap_or([Scalar::zero(); WIDTH]);
+
+            // Each element of the linear layer sums five terms, which takes
+            // two width-4 gates
+            let state = self.state;
+            for (s, (row, c)) in self
+                .state
+                .iter_mut()
+                .zip(self.poseidon.mds.iter().zip(next_constants.iter()))
+            {
+                let partial = composer.big_add(
+                    (row[0], state[0]),
+                    (row[1], state[1]),
+                    (row[2], state[2]),
+                    *c,
+                    None,
+                );
+                *s = composer.big_add(
+                    (Scalar::one(), partial),
+                    (row[3], state[3]),
+                    (row[4], state[4]),
+                    Scalar::zero(),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+// Computes `x^5` with three multiplication gates
+fn sbox_gadget(composer: &mut StandardComposer, x: Variable) -> Variable {
+    let x_2 = composer.mul(Scalar::one(), x, x, Scalar::zero(), None);
+    let x_4 = composer.mul(Scalar::one(), x_2, x_2, Scalar::zero(), None);
+    composer.mul(Scalar::one(), x_4, x, Scalar::zero(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_inputs(n: u64) -> Vec<Scalar> {
+        (0..n).map(|i| Scalar::from(i * 7 + 3)).collect()
+    }
+
+    #[test]
+    fn test_parameters_are_deterministic() {
+        let (a, b) = (Poseidon::new(), Poseidon::new());
+        assert_eq!(a.round_constants, b.round_constants);
+        assert_eq!(a.mds, b.mds);
+        assert_eq!(a.round_constants.len(), TOTAL_ROUNDS);
+    }
+
+    #[test]
+    fn test_permutation() {
+        let poseidon = Poseidon::new();
+        let mut a = [Scalar::zero(); WIDTH];
+        let mut b = [Scalar::zero(); WIDTH];
+        b[1] = Scalar::one();
+
+        poseidon.permute(&mut a);
+        poseidon.permute(&mut b);
+        assert_ne!(a, [Scalar::zero(); WIDTH]);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_hash_padding() {
+        let poseidon = Poseidon::new();
+        let inputs = test_inputs(RATE as u64);
+
+        // Appending zeros or ones must change the hash
+        let mut with_zero = inputs.clone();
+        with_zero.push(Scalar::zero());
+        let mut with_one = inputs.clone();
+        with_one.push(Scalar::one());
+
+        let hash = poseidon.hash(&inputs);
+        assert_ne!(hash, poseidon.hash(&with_zero));
+        assert_ne!(hash, poseidon.hash(&with_one));
+        assert_ne!(poseidon.hash(&[]), poseidon.hash(&[Scalar::zero()]));
+    }
+
+    #[test]
+    fn test_sponge_matches_hash() {
+        let poseidon = Poseidon::new();
+        let inputs = test_inputs(11);
+
+        let mut sponge = Sponge::new();
+        for input in inputs.iter() {
+            sponge.absorb(input);
+        }
+        assert_eq!(sponge.squeeze(), poseidon.hash(&inputs));
+    }
+
+    #[test]
+    fn test_sponge_gadget_matches_native() {
+        let poseidon = Poseidon::new();
+
+        // Cover empty inputs as well as inputs that fill the rate exactly
+        for len in 0..=(2 * RATE as u64 + 1) {
+            let inputs = test_inputs(len);
+            let mut composer = StandardComposer::new();
+            let mut sponge = SpongeGadget::new(&composer);
+            for input in inputs.iter() {
+                let input = composer.add_input(*input);
+                sponge.absorb(&mut composer, input);
+            }
+            let hash = sponge.squeeze(&mut composer);
+
+            assert_eq!(composer.variables[&hash], poseidon.hash(&inputs));
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Implementation of the SHA-256 hash function as a gadget of the
+//! `StandardComposer`, built on the 32-bit words of `UInt32`.
+//!
+//! The message is padded when the circuit is built, so its length is part of
+//! the circuit description while its bytes are part of the witness.
+//!
+//! Reference: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf
+
+use crate::constraint_system::{StandardComposer, UInt32, UInt8};
+
+/// Number of gates that a single call to `compress` adds to the circuit.
+pub const COMPRESSION_GATES: usize = 27_320;
+
+// Number of bytes of a message block
+const BLOCK_SIZE: usize = 64;
+
+// Initial hash value, which BLAKE2s uses as well
+pub(crate) const IV: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+// Round constants
+const K: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// Hashes `bytes` with SHA-256, returning the `32` bytes of the digest.
+///
+/// The message is padded with constant bytes and split in blocks of `64`
+/// bytes, each of them costing a call to `compress`.
+pub fn hash(composer: &mut StandardComposer, bytes: &[UInt8]) -> Vec<UInt8> {
+    // The message is followed by a one bit, zeros and its length in bits,
+    // filling a whole number of blocks
+    let bit_len = (bytes.len() as u64) * 8;
+    let num_zeros = (BLOCK_SIZE - ((bytes.len() + 9) % BLOCK_SIZE)) % BLOCK_SIZE;
+    let zero = UInt8::constant(composer, 0);
+    let mut message = bytes.to_vec();
+    message.push(UInt8::constant(composer, 0x80));
+    message.extend(std::iter::repeat(zero).take(num_zeros));
+    for byte in bit_len.to_be_bytes().iter() {
+        message.push(UInt8::constant(composer, *byte));
+    }
+
+    let iv: Vec<UInt32> = IV
+        .iter()
+        .map(|value| UInt32::constant(composer, *value))
+        .collect();
+    let mut state = [iv[0]; 8];
+    state.copy_from_slice(&iv);
+
+    for block in message.chunks(BLOCK_SIZE) {
+        let mut words = [state[0]; 16];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = UInt32::from_bytes_be(composer, bytes);
+        }
+        state = compress(composer, &state, &words);
+    }
+
+    state
+        .iter()
+        .flat_map(|word| word.to_bytes_be(composer))
+        .collect()
+}
+
+/// Applies the SHA-256 compression function to the `state` and a block of
+/// `16` words, returning the next state.
+///
+/// Each call adds `COMPRESSION_GATES` gates to the circuit, most of them
+/// taken by the logic gates of the `64` rounds and by the range checks of
+/// the rotations and the wrapping additions.
+pub fn compress(
+    composer: &mut StandardComposer,
+    state: &[UInt32; 8],
+    block: &[UInt32; 16],
+) -> [UInt32; 8] {
+    // Message schedule
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = small_sigma(composer, &w[t - 15], 7, 18, 3);
+        let s1 = small_sigma(composer, &w[t - 2], 17, 19, 10);
+        let word = UInt32::wrapping_sum(composer, &[s1, w[t - 7], s0, w[t - 16]]);
+        w.push(word);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (k, w) in K.iter().zip(w.iter()) {
+        let s1 = big_sigma(composer, &e, 6, 11, 25);
+        let ch = {
+            let e_and_f = e.and(composer, &f);
+            let not_e = e.not(composer);
+            let not_e_and_g = not_e.and(composer, &g);
+            e_and_f.xor(composer, &not_e_and_g)
+        };
+        let k = UInt32::constant(composer, *k);
+
+        let s0 = big_sigma(composer, &a, 2, 13, 22);
+        let maj = {
+            let a_and_b = a.and(composer, &b);
+            let a_and_c = a.and(composer, &c);
+            let b_and_c = b.and(composer, &c);
+            let maj = a_and_b.xor(composer, &a_and_c);
+            maj.xor(composer, &b_and_c)
+        };
+
+        // Both new words are computed with a single wrapping sum, so that
+        // `T1 = h + S1 + ch + k + w` is never range checked on its own
+        let new_e = UInt32::wrapping_sum(composer, &[d, h, s1, ch, k, *w]);
+        let new_a = UInt32::wrapping_sum(composer, &[h, s1, ch, k, *w, s0, maj]);
+
+        h = g;
+        g = f;
+        f = e;
+        e = new_e;
+        d = c;
+        c = b;
+        b = a;
+        a = new_a;
+    }
+
+    let mut next = *state;
+    for (word, working) in next.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *word = word.wrapping_add(composer, working).0;
+    }
+    next
+}
+
+// Computes `rotr(x, r1) ^ rotr(x, r2) ^ rotr(x, r3)`
+fn big_sigma(
+    composer: &mut StandardComposer,
+    x: &UInt32,
+    r1: usize,
+    r2: usize,
+    r3: usize,
+) -> UInt32 {
+    let x1 = x.rotate_right(composer, r1);
+    let x2 = x.rotate_right(composer, r2);
+    let x3 = x.rotate_right(composer, r3);
+    let x = x1.xor(composer, &x2);
+    x.xor(composer, &x3)
+}
+
+// Computes `rotr(x, r1) ^ rotr(x, r2) ^ (x >> s)`
+fn small_sigma(
+    composer: &mut StandardComposer,
+    x: &UInt32,
+    r1: usize,
+    r2: usize,
+    s: usize,
+) -> UInt32 {
+    let x1 = x.rotate_right(composer, r1);
+    let x2 = x.rotate_right(composer, r2);
+    let x3 = x.shr(composer, s);
+    let x = x1.xor(composer, &x2);
+    x.xor(composer, &x3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint_system::Variable;
+    use bls12_381::Scalar;
+
+    fn hash_bytes(message: &[u8]) -> Vec<u8> {
+        let mut composer = StandardComposer::new();
+        let bytes: Vec<Variable> = message
+            .iter()
+            .map(|byte| composer.add_input(Scalar::from(u64::from(*byte))))
+            .collect();
+        composer
+            .sha256(&bytes)
+            .iter()
+            .map(|byte| composer.variables[byte].to_bytes()[0])
+            .collect()
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_nist_vectors() {
+        let vectors = [
+            (
+                &b""[..],
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                &b"abc"[..],
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                &b"abcdbcdecdefdefgefghfghighijhijkijkljklmjklmnklmnomnopnopq"[..],
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+        ];
+        for (message, digest) in vectors.iter() {
+            assert_eq!(hash_bytes(message), from_hex(digest));
+        }
+    }
+
+    #[test]
+    fn test_compression_gates() {
+        let mut composer = StandardComposer::witness_free();
+        let state = [UInt32::new(&mut composer, 0); 8];
+        let block = [UInt32::new(&mut composer, 0); 16];
+
+        let size = composer.circuit_size();
+        compress(&mut composer, &state, &block);
+        assert_eq!(composer.circuit_size() - size, COMPRESSION_GATES);
+    }
+}
@@ -27,11 +27,17 @@
 #![deny(unsafe_code)]
 
 mod bit_iterator;
+pub mod circuit;
 pub mod commitment_scheme;
 pub mod constraint_system;
+pub mod errors;
 pub mod fft;
+pub mod hash;
+pub mod merkle;
+pub mod pedersen;
 mod permutation;
 pub mod proof_system;
+pub mod schnorr;
 pub mod transcript;
 mod util;
 
@@ -0,0 +1,188 @@
+//! Merkle trees hashed with Poseidon, along with the authentication paths
+//! used by `StandardComposer::merkle_membership` to prove in a circuit that
+//! a leaf belongs to a tree.
+//!
+//! Trees have a fixed depth and are filled from the left. The leaves that
+//! have not been inserted yet are zero, so the root of a tree can be
+//! computed without hashing its empty subtrees.
+
+use crate::hash::Poseidon;
+use bls12_381::Scalar;
+
+/// A Merkle tree of a fixed depth whose nodes are the Poseidon hash of
+/// their two children.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    depth: usize,
+    leaves: Vec<Scalar>,
+    // Root of an empty subtree of every height, from the leaves up
+    empty_roots: Vec<Scalar>,
+    poseidon: Poseidon,
+}
+
+/// The authentication path of a leaf: the siblings of every node from the
+/// leaf up to the root, along with the side on which each node lies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerklePath {
+    pub(crate) siblings: Vec<Scalar>,
+    // Set when the node is the right child of its parent
+    pub(crate) directions: Vec<bool>,
+}
+
+impl MerkleTree {
+    /// Creates an empty tree which can hold up to `2^depth` leaves.
+    ///
+    /// # Panics
+    ///
+    /// If `depth` is not smaller than the number of bits of a `usize`.
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < 8 * std::mem::size_of::<usize>());
+        let poseidon = Poseidon::new();
+
+        let mut empty_roots = Vec::with_capacity(depth + 1);
+        empty_roots.push(Scalar::zero());
+        for height in 0..depth {
+            let empty = empty_roots[height];
+            empty_roots.push(poseidon.hash(&[empty, empty]));
+        }
+
+        MerkleTree {
+            depth,
+            leaves: Vec::new(),
+            empty_roots,
+            poseidon,
+        }
+    }
+
+    /// Returns the depth of the tree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Inserts a leaf in the next empty position of the tree, returning
+    /// its index or `None` if the tree is already full.
+    pub fn insert(&mut self, leaf: Scalar) -> Option<usize> {
+        if self.leaves.len() == 1 << self.depth {
+            return None;
+        }
+        self.leaves.push(leaf);
+        Some(self.leaves.len() - 1)
+    }
+
+    /// Computes the root of the tree.
+    pub fn root(&self) -> Scalar {
+        self.levels()[self.depth]
+            .first()
+            .cloned()
+            .unwrap_or(self.empty_roots[self.depth])
+    }
+
+    /// Returns the authentication path of the leaf at `index`, or `None` if
+    /// no leaf has been inserted in that position.
+    pub fn path(&self, index: usize) -> Option<MerklePath> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+
+        let levels = self.levels();
+        let (siblings, directions) = (0..self.depth)
+            .map(|height| {
+                let position = index >> height;
+                let sibling = levels[height]
+                    .get(position ^ 1)
+                    .cloned()
+                    .unwrap_or(self.empty_roots[height]);
+                (sibling, position & 1 == 1)
+            })
+            .unzip();
+
+        Some(MerklePath {
+            siblings,
+            directions,
+        })
+    }
+
+    // Computes the non-empty nodes of every level, from the leaves up
+    fn levels(&self) -> Vec<Vec<Scalar>> {
+        let mut levels = Vec::with_capacity(self.depth + 1);
+        levels.push(self.leaves.clone());
+        for height in 0..self.depth {
+            let level: Vec<Scalar> = levels[height]
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).cloned().unwrap_or(self.empty_roots[height]);
+                    self.poseidon.hash(&[pair[0], right])
+                })
+                .collect();
+            levels.push(level);
+        }
+        levels
+    }
+}
+
+impl MerklePath {
+    /// Returns the number of levels of the path.
+    pub fn depth(&self) -> usize {
+        self.siblings.len()
+    }
+
+    /// Computes the root of the tree that `leaf` belongs to according to
+    /// this path.
+    pub fn root(&self, leaf: Scalar) -> Scalar {
+        let poseidon = Poseidon::new();
+        self.siblings
+            .iter()
+            .zip(self.directions.iter())
+            .fold(leaf, |node, (sibling, is_right)| match *is_right {
+                true => poseidon.hash(&[*sibling, node]),
+                false => poseidon.hash(&[node, *sibling]),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = MerkleTree::new(3);
+        let poseidon = Poseidon::new();
+
+        let mut root = Scalar::zero();
+        for _ in 0..3 {
+            root = poseidon.hash(&[root, root]);
+        }
+        assert_eq!(tree.root(), root);
+        assert!(tree.path(0).is_none());
+    }
+
+    #[test]
+    fn test_paths_lead_to_root() {
+        let mut tree = MerkleTree::new(3);
+        let leaves: Vec<Scalar> = (1..=5u64).map(Scalar::from).collect();
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.insert(*leaf), Some(i));
+        }
+
+        let root = tree.root();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = tree.path(i).unwrap();
+            assert_eq!(path.depth(), 3);
+            assert_eq!(path.root(*leaf), root);
+            // The path does not hold for any other leaf
+            assert_ne!(path.root(*leaf + Scalar::one()), root);
+        }
+        assert!(tree.path(leaves.len()).is_none());
+    }
+
+    #[test]
+    fn test_full_tree() {
+        let mut tree = MerkleTree::new(1);
+        let root = tree.root();
+        assert_eq!(tree.insert(Scalar::one()), Some(0));
+        assert_ne!(tree.root(), root);
+        assert_eq!(tree.insert(Scalar::one()), Some(1));
+        assert_eq!(tree.insert(Scalar::one()), None);
+    }
+}
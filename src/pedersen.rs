@@ -0,0 +1,101 @@
+//! Pedersen commitments over the Jubjub curve, which
+//! `StandardComposer::pedersen_commitment` opens inside of a circuit.
+//!
+//! A commitment to a value `v` with the blinder `r` is the point
+//! `v * G + r * H`, where `G` is the generator of the curve and `H` is a
+//! second generator whose discrete logarithm with respect to `G` is
+//! unknown. `H` is derived by hashing a counter with BLAKE2s until the
+//! digest is the encoding of a point, which is then multiplied by the
+//! cofactor of the curve.
+
+use crate::hash::blake2s;
+use jubjub::{AffinePoint, ExtendedPoint, Fr as JubJubScalar, GENERATOR};
+use lazy_static::lazy_static;
+
+// Personalization of the hashes that the blinding generator is derived from
+const PERSONALIZATION: &[u8; 8] = b"plonk_PH";
+
+lazy_static! {
+    // The blinding generator, which is only derived once
+    static ref BLINDING_GENERATOR: AffinePoint = derive_blinding_generator();
+}
+
+/// Returns the generator that the values are committed with, which is the
+/// generator of the curve.
+pub fn value_generator() -> AffinePoint {
+    GENERATOR
+}
+
+/// Returns the generator that the blinders are committed with.
+pub fn blinding_generator() -> AffinePoint {
+    *BLINDING_GENERATOR
+}
+
+// Hashes a counter until the digest is the encoding of a point that is not
+// of small order, and returns the point with the cofactor cleared
+fn derive_blinding_generator() -> AffinePoint {
+    let mut counter = 0u64;
+    loop {
+        let digest = blake2s::hash(&counter.to_le_bytes(), PERSONALIZATION);
+        let point = AffinePoint::from_bytes(digest);
+        if bool::from(point.is_some()) {
+            // Clear the cofactor so that the point lies in the prime order
+            // subgroup
+            let point = ExtendedPoint::from(point.unwrap())
+                .double()
+                .double()
+                .double();
+            if point != ExtendedPoint::identity() {
+                return AffinePoint::from(point);
+            }
+        }
+        counter += 1;
+    }
+}
+
+/// Commits to `value` with the given `blinder`.
+pub fn commit(value: &JubJubScalar, blinder: &JubJubScalar) -> AffinePoint {
+    let value_point = ExtendedPoint::from(value_generator()) * value;
+    let blinding_point = ExtendedPoint::from(blinding_generator()) * blinder;
+    AffinePoint::from(value_point + blinding_point)
+}
+
+/// Returns true if `commitment` is the commitment to `value` with the
+/// given `blinder`.
+pub fn open(commitment: &AffinePoint, value: &JubJubScalar, blinder: &JubJubScalar) -> bool {
+    commit(value, blinder) == *commitment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators() {
+        let h = blinding_generator();
+        assert_eq!(h, blinding_generator());
+        assert_eq!(h, derive_blinding_generator());
+        assert_ne!(h, value_generator());
+        assert_ne!(ExtendedPoint::from(h), ExtendedPoint::identity());
+    }
+
+    #[test]
+    fn test_commit_and_open() {
+        let value = JubJubScalar::from(100u64);
+        let blinder = JubJubScalar::from(0xdead_beefu64);
+        let commitment = commit(&value, &blinder);
+
+        assert!(open(&commitment, &value, &blinder));
+        assert!(!open(&commitment, &(value + JubJubScalar::one()), &blinder));
+        assert!(!open(&commitment, &value, &(blinder + JubJubScalar::one())));
+    }
+
+    #[test]
+    fn test_homomorphism() {
+        let (v_1, r_1) = (JubJubScalar::from(3u64), JubJubScalar::from(11u64));
+        let (v_2, r_2) = (JubJubScalar::from(4u64), JubJubScalar::from(13u64));
+
+        let sum = ExtendedPoint::from(commit(&v_1, &r_1)) + ExtendedPoint::from(commit(&v_2, &r_2));
+        assert!(open(&AffinePoint::from(sum), &(v_1 + v_2), &(r_1 + r_2)));
+    }
+}
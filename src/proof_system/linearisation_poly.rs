@@ -1,5 +1,7 @@
 use crate::fft::{EvaluationDomain, Polynomial};
-use crate::proof_system::PreProcessedCircuit;
+use crate::proof_system::widget::lookup::LookupPolynomials;
+use crate::proof_system::widget::Widget;
+use crate::proof_system::ProverCircuitKey;
 use bls12_381::Scalar;
 #[cfg(feature = "serde")]
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
@@ -7,6 +9,8 @@ use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serial
 /// Evaluations at points `z` or and `z * root of unity`
 pub struct Evaluations {
     pub proof: ProofEvaluations,
+    // Evaluations of the lookup polynomials, for circuits with lookup tables
+    pub lookup: Option<LookupEvaluations>,
     // Evaluation of the linearisation sigma polynomial at `z`
     pub quot_eval: Scalar,
 }
@@ -26,12 +30,16 @@ pub struct ProofEvaluations {
     pub a_next_eval: Scalar,
     //
     pub b_next_eval: Scalar,
+    // Evaluation of the witness polynomial for the output wire at `z * root of unity`
+    pub c_next_eval: Scalar,
     // Evaluation of the witness polynomial for the fourth wire at `z * root of unity`
     pub d_next_eval: Scalar,
     // Evaluation of the arithmetic selector polynomial at `z`
     pub q_arith_eval: Scalar,
     //
     pub q_c_eval: Scalar,
+    // Evaluation of the fixed base scalar multiplication selector polynomial at `z`
+    pub q_fixed_base_eval: Scalar,
     // Evaluation of the left sigma polynomial at `z`
     pub left_sigma_eval: Scalar,
     // Evaluation of the right sigma polynomial at `z`
@@ -46,22 +54,70 @@ pub struct ProofEvaluations {
     pub perm_eval: Scalar,
 }
 
+impl ProofEvaluations {
+    /// Returns the evaluations in the order they are serialized.
+    pub(crate) fn to_vec(&self) -> Vec<Scalar> {
+        vec![
+            self.a_eval,
+            self.b_eval,
+            self.c_eval,
+            self.d_eval,
+            self.a_next_eval,
+            self.b_next_eval,
+            self.c_next_eval,
+            self.d_next_eval,
+            self.q_arith_eval,
+            self.q_c_eval,
+            self.q_fixed_base_eval,
+            self.left_sigma_eval,
+            self.right_sigma_eval,
+            self.out_sigma_eval,
+            self.lin_poly_eval,
+            self.perm_eval,
+        ]
+    }
+
+    /// Builds the evaluations back from the order given by `to_vec`.
+    pub(crate) fn from_slice(evals: &[Scalar]) -> ProofEvaluations {
+        ProofEvaluations {
+            a_eval: evals[0],
+            b_eval: evals[1],
+            c_eval: evals[2],
+            d_eval: evals[3],
+            a_next_eval: evals[4],
+            b_next_eval: evals[5],
+            c_next_eval: evals[6],
+            d_next_eval: evals[7],
+            q_arith_eval: evals[8],
+            q_c_eval: evals[9],
+            q_fixed_base_eval: evals[10],
+            left_sigma_eval: evals[11],
+            right_sigma_eval: evals[12],
+            out_sigma_eval: evals[13],
+            lin_poly_eval: evals[14],
+            perm_eval: evals[15],
+        }
+    }
+}
+
 #[cfg(feature = "serde")]
 impl Serialize for ProofEvaluations {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut proof_evals = serializer.serialize_struct("struct ProofEvaluations", 14)?;
+        let mut proof_evals = serializer.serialize_struct("struct ProofEvaluations", 16)?;
         proof_evals.serialize_field("a_eval", &self.a_eval)?;
         proof_evals.serialize_field("b_eval", &self.b_eval)?;
         proof_evals.serialize_field("c_eval", &self.c_eval)?;
         proof_evals.serialize_field("d_eval", &self.d_eval)?;
         proof_evals.serialize_field("a_next_eval", &self.a_next_eval)?;
         proof_evals.serialize_field("b_next_eval", &self.b_next_eval)?;
+        proof_evals.serialize_field("c_next_eval", &self.c_next_eval)?;
         proof_evals.serialize_field("d_next_eval", &self.d_next_eval)?;
         proof_evals.serialize_field("q_arith_eval", &self.q_arith_eval)?;
         proof_evals.serialize_field("q_c_eval", &self.q_c_eval)?;
+        proof_evals.serialize_field("q_fixed_base_eval", &self.q_fixed_base_eval)?;
         proof_evals.serialize_field("left_sig_eval", &self.left_sigma_eval)?;
         proof_evals.serialize_field("right_sig_eval", &self.right_sigma_eval)?;
         proof_evals.serialize_field("out_sig_eval", &self.out_sigma_eval)?;
@@ -84,9 +140,11 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
             Deval,
             ANextEval,
             BNextEval,
+            CNextEval,
             DNextEval,
             QArithEval,
             QCEval,
+            QFixedBaseEval,
             LeftSigEval,
             RightSigEval,
             OutSigEval,
@@ -122,9 +180,11 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
                             "d_eval" => Ok(Field::Deval),
                             "a_next_eval" => Ok(Field::ANextEval),
                             "b_next_eval" => Ok(Field::BNextEval),
+                            "c_next_eval" => Ok(Field::CNextEval),
                             "d_next_eval" => Ok(Field::DNextEval),
                             "q_arith_eval" => Ok(Field::QArithEval),
                             "q_c_eval" => Ok(Field::QCEval),
+                            "q_fixed_base_eval" => Ok(Field::QFixedBaseEval),
                             "left_sig_eval" => Ok(Field::LeftSigEval),
                             "right_sig_eval" => Ok(Field::RightSigEval),
                             "out_sig_eval" => Ok(Field::OutSigEval),
@@ -170,6 +230,9 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
                 let b_next_eval = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let c_next_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
                 let d_next_eval = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
@@ -179,6 +242,9 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
                 let q_c_eval = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let q_fixed_base_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
                 let left_sigma_eval = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
@@ -201,9 +267,11 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
                     d_eval,
                     a_next_eval,
                     b_next_eval,
+                    c_next_eval,
                     d_next_eval,
                     q_arith_eval,
                     q_c_eval,
+                    q_fixed_base_eval,
                     left_sigma_eval,
                     right_sigma_eval,
                     out_sigma_eval,
@@ -220,9 +288,11 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
             "d_eval",
             "a_next_eval",
             "b_next_eval",
+            "c_next_eval",
             "d_next_eval",
             "q_arith_eval",
             "q_c_eval",
+            "q_fixed_base_eval",
             "left_sig_eval",
             "right_sig_eval",
             "out_sig_eval",
@@ -233,18 +303,201 @@ impl<'de> Deserialize<'de> for ProofEvaluations {
     }
 }
 
+/// Lookup Evaluations are the evaluations of the lookup polynomials added to
+/// the proof. Only the proofs of circuits with lookup tables hold them.
+#[derive(Debug, Eq, PartialEq)]
+pub struct LookupEvaluations {
+    // Evaluation of the lookup query polynomial at `z`
+    pub f_eval: Scalar,
+    // Evaluation of the first half of the sorted list at `z`
+    pub h_1_eval: Scalar,
+    // Evaluation of the first half of the sorted list at `z * root of unity`
+    pub h_1_next_eval: Scalar,
+    // Evaluation of the second half of the sorted list at `z * root of unity`
+    pub h_2_next_eval: Scalar,
+    // Evaluation of the compressed lookup table at `z`
+    pub table_eval: Scalar,
+    // Evaluation of the compressed lookup table at `z * root of unity`
+    pub table_next_eval: Scalar,
+    // (Shifted) Evaluation of the lookup accumulator polynomial at `z * root of unity`
+    pub lookup_perm_eval: Scalar,
+}
+
+impl LookupEvaluations {
+    /// Returns the evaluations in the order they are serialized.
+    pub(crate) fn to_vec(&self) -> Vec<Scalar> {
+        vec![
+            self.f_eval,
+            self.h_1_eval,
+            self.h_1_next_eval,
+            self.h_2_next_eval,
+            self.table_eval,
+            self.table_next_eval,
+            self.lookup_perm_eval,
+        ]
+    }
+
+    /// Builds the evaluations back from the order given by `to_vec`.
+    pub(crate) fn from_slice(evals: &[Scalar]) -> LookupEvaluations {
+        LookupEvaluations {
+            f_eval: evals[0],
+            h_1_eval: evals[1],
+            h_1_next_eval: evals[2],
+            h_2_next_eval: evals[3],
+            table_eval: evals[4],
+            table_next_eval: evals[5],
+            lookup_perm_eval: evals[6],
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LookupEvaluations {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut lookup_evals = serializer.serialize_struct("struct LookupEvaluations", 7)?;
+        lookup_evals.serialize_field("f_eval", &self.f_eval)?;
+        lookup_evals.serialize_field("h_1_eval", &self.h_1_eval)?;
+        lookup_evals.serialize_field("h_1_next_eval", &self.h_1_next_eval)?;
+        lookup_evals.serialize_field("h_2_next_eval", &self.h_2_next_eval)?;
+        lookup_evals.serialize_field("table_eval", &self.table_eval)?;
+        lookup_evals.serialize_field("table_next_eval", &self.table_next_eval)?;
+        lookup_evals.serialize_field("lookup_perm_eval", &self.lookup_perm_eval)?;
+        lookup_evals.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LookupEvaluations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            FEval,
+            H1Eval,
+            H1NextEval,
+            H2NextEval,
+            TableEval,
+            TableNextEval,
+            LookupPermEval,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct LookupEvaluations")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "f_eval" => Ok(Field::FEval),
+                            "h_1_eval" => Ok(Field::H1Eval),
+                            "h_1_next_eval" => Ok(Field::H1NextEval),
+                            "h_2_next_eval" => Ok(Field::H2NextEval),
+                            "table_eval" => Ok(Field::TableEval),
+                            "table_next_eval" => Ok(Field::TableNextEval),
+                            "lookup_perm_eval" => Ok(Field::LookupPermEval),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct LookupEvaluationsVisitor;
+
+        impl<'de> Visitor<'de> for LookupEvaluationsVisitor {
+            type Value = LookupEvaluations;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct LookupEvaluations")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<LookupEvaluations, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let f_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let h_1_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let h_1_next_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let h_2_next_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_next_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let lookup_perm_eval = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(LookupEvaluations {
+                    f_eval,
+                    h_1_eval,
+                    h_1_next_eval,
+                    h_2_next_eval,
+                    table_eval,
+                    table_next_eval,
+                    lookup_perm_eval,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &[
+            "f_eval",
+            "h_1_eval",
+            "h_1_next_eval",
+            "h_2_next_eval",
+            "table_eval",
+            "table_next_eval",
+            "lookup_perm_eval",
+        ];
+        deserializer.deserialize_struct("LookupEvaluations", FIELDS, LookupEvaluationsVisitor)
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 /// Compute the linearisation polynomial
 pub fn compute(
     domain: &EvaluationDomain,
-    preprocessed_circuit: &PreProcessedCircuit,
+    preprocessed_circuit: &ProverCircuitKey,
     (alpha, beta, gamma, z_challenge): &(Scalar, Scalar, Scalar, Scalar),
+    ecc_separation_challenge: &Scalar,
+    fixed_base_separation_challenge: &Scalar,
     w_l_poly: &Polynomial,
     w_r_poly: &Polynomial,
     w_o_poly: &Polynomial,
     w_4_poly: &Polynomial,
     t_x_poly: &Polynomial,
     z_poly: &Polynomial,
+    lookup: Option<&LookupPolynomials>,
+    (widgets, custom_separation_challenges): (&[Box<dyn Widget>], &[Scalar]),
 ) -> (Polynomial, Evaluations) {
     // Compute evaluations
     let quot_eval = t_x_poly.evaluate(z_challenge);
@@ -277,9 +530,15 @@ pub fn compute(
         .q_c
         .polynomial
         .evaluate(z_challenge);
+    let q_fixed_base_eval = preprocessed_circuit
+        .fixed_base
+        .q_fixed_base
+        .polynomial
+        .evaluate(z_challenge);
 
     let a_next_eval = w_l_poly.evaluate(&(z_challenge * domain.group_gen));
     let b_next_eval = w_r_poly.evaluate(&(z_challenge * domain.group_gen));
+    let c_next_eval = w_o_poly.evaluate(&(z_challenge * domain.group_gen));
     let d_next_eval = w_4_poly.evaluate(&(z_challenge * domain.group_gen));
     let perm_eval = z_poly.evaluate(&(z_challenge * domain.group_gen));
 
@@ -290,13 +549,18 @@ pub fn compute(
         &d_eval,
         &a_next_eval,
         &b_next_eval,
+        &c_next_eval,
         &d_next_eval,
         &q_arith_eval,
         &q_c_eval,
+        &q_fixed_base_eval,
+        ecc_separation_challenge,
+        fixed_base_separation_challenge,
         preprocessed_circuit,
     );
 
     let f_2 = preprocessed_circuit.permutation.compute_linearisation(
+        domain,
         z_challenge,
         (alpha, beta, gamma),
         (&a_eval, &b_eval, &c_eval, &d_eval),
@@ -305,30 +569,75 @@ pub fn compute(
         z_poly,
     );
 
-    let lin_poly = &f_1 + &f_2;
+    let mut proof_evaluations = ProofEvaluations {
+        a_eval,
+        b_eval,
+        c_eval,
+        d_eval,
+        a_next_eval,
+        b_next_eval,
+        c_next_eval,
+        d_next_eval,
+        q_arith_eval,
+        q_c_eval,
+        q_fixed_base_eval,
+        left_sigma_eval,
+        right_sigma_eval,
+        out_sigma_eval,
+        // Filled in once the linearisation polynomial is known
+        lin_poly_eval: Scalar::zero(),
+        perm_eval,
+    };
+
+    // The lookup argument only exists for circuits with lookup tables
+    let (lookup_evaluations, f_3) = match (&preprocessed_circuit.lookup, lookup) {
+        (Some(lookup_widget), Some(lookup)) => {
+            let shifted_z_challenge = z_challenge * domain.group_gen;
+            let lookup_evaluations = LookupEvaluations {
+                f_eval: lookup.f_poly.evaluate(z_challenge),
+                h_1_eval: lookup.h_1_poly.evaluate(z_challenge),
+                h_1_next_eval: lookup.h_1_poly.evaluate(&shifted_z_challenge),
+                h_2_next_eval: lookup.h_2_poly.evaluate(&shifted_z_challenge),
+                table_eval: lookup.table_poly.evaluate(z_challenge),
+                table_next_eval: lookup.table_poly.evaluate(&shifted_z_challenge),
+                lookup_perm_eval: lookup.z_2_poly.evaluate(&shifted_z_challenge),
+            };
+
+            // The lookup accumulator is checked at the first and last rows
+            let lagrange_evals = domain.evaluate_all_lagrange_coefficients(*z_challenge);
+            let (zeta, delta, epsilon) = &lookup.challenges;
+            let f_3 = lookup_widget.compute_linearisation(
+                &proof_evaluations,
+                &lookup_evaluations,
+                (zeta, delta, epsilon),
+                &lookup.separation_challenge,
+                (z_challenge, &domain.group_gen_inv),
+                (&lagrange_evals[0], &lagrange_evals[domain.size() - 1]),
+                (&lookup.h_2_poly, &lookup.z_2_poly),
+            );
+            (Some(lookup_evaluations), f_3)
+        }
+        _ => (None, Polynomial::zero()),
+    };
+
+    let f_4 = preprocessed_circuit
+        .custom
+        .iter()
+        .zip(widgets.iter().zip(custom_separation_challenges.iter()))
+        .fold(Polynomial::zero(), |acc, (custom, (widget, challenge))| {
+            &acc + &custom.compute_linearisation(widget.as_ref(), &proof_evaluations, challenge)
+        });
+
+    let lin_poly = &(&(&f_1 + &f_2) + &f_3) + &f_4;
 
     // Evaluate linearisation polynomial at z_challenge
-    let lin_poly_eval = lin_poly.evaluate(z_challenge);
+    proof_evaluations.lin_poly_eval = lin_poly.evaluate(z_challenge);
 
     (
         lin_poly,
         Evaluations {
-            proof: ProofEvaluations {
-                a_eval,
-                b_eval,
-                c_eval,
-                d_eval,
-                a_next_eval,
-                b_next_eval,
-                d_next_eval,
-                q_arith_eval,
-                q_c_eval,
-                left_sigma_eval,
-                right_sigma_eval,
-                out_sigma_eval,
-                lin_poly_eval,
-                perm_eval,
-            },
+            proof: proof_evaluations,
+            lookup: lookup_evaluations,
             quot_eval,
         },
     )
@@ -342,10 +651,14 @@ fn compute_circuit_satisfiability(
     d_eval: &Scalar,
     a_next_eval: &Scalar,
     b_next_eval: &Scalar,
+    c_next_eval: &Scalar,
     d_next_eval: &Scalar,
     q_arith_eval: &Scalar,
     q_c_eval: &Scalar,
-    preprocessed_circuit: &PreProcessedCircuit,
+    q_fixed_base_eval: &Scalar,
+    ecc_separation_challenge: &Scalar,
+    fixed_base_separation_challenge: &Scalar,
+    preprocessed_circuit: &ProverCircuitKey,
 ) -> Polynomial {
     let a = preprocessed_circuit.arithmetic.compute_linearisation(
         a_eval,
@@ -372,7 +685,30 @@ fn compute_circuit_satisfiability(
         d_next_eval,
         q_c_eval,
     );
-    &(&a + &b) + &c
+    let d = preprocessed_circuit.ecc_add.compute_linearisation(
+        a_eval,
+        a_next_eval,
+        b_eval,
+        b_next_eval,
+        c_eval,
+        c_next_eval,
+        d_eval,
+        d_next_eval,
+        ecc_separation_challenge,
+    );
+    let e = preprocessed_circuit.fixed_base.compute_linearisation(
+        &preprocessed_circuit.arithmetic,
+        a_eval,
+        a_next_eval,
+        b_eval,
+        b_next_eval,
+        c_eval,
+        d_eval,
+        d_next_eval,
+        q_fixed_base_eval,
+        fixed_base_separation_challenge,
+    );
+    &(&(&(&a + &b) + &c) + &d) + &e
 }
 
 #[cfg(test)]
@@ -394,9 +730,11 @@ mod tests {
             d_eval: one,
             a_next_eval: one,
             b_next_eval: one,
+            c_next_eval: one,
             d_next_eval: one,
             q_arith_eval: one,
             q_c_eval: one,
+            q_fixed_base_eval: one,
             left_sigma_eval: one,
             right_sigma_eval: one,
             out_sigma_eval: one,
@@ -6,5 +6,6 @@ pub(crate) mod proof;
 pub(crate) mod quotient_poly;
 pub(crate) mod widget;
 
-pub use preprocessed_circuit::PreProcessedCircuit;
-pub use proof::Proof;
+pub use preprocessed_circuit::{ProverCircuitKey, VerifierCircuitKey};
+pub use proof::{LookupProof, Proof};
+pub use widget::{Widget, WireEvaluations};
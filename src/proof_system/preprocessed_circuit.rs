@@ -1,18 +1,28 @@
-use crate::fft::Evaluations;
-use crate::proof_system::widget::{ArithmeticWidget, LogicWidget, PermutationWidget, RangeWidget};
+use crate::fft::{EvaluationDomain, Evaluations};
+use crate::proof_system::widget::{
+    ArithmeticVerifierKey, ArithmeticWidget, CustomVerifierKey, CustomWidget, EccAddVerifierKey,
+    EccAddWidget, FixedBaseVerifierKey, FixedBaseWidget, LogicVerifierKey, LogicWidget,
+    LookupVerifierKey, LookupWidget, PermutationVerifierKey, PermutationWidget, RangeVerifierKey,
+    RangeWidget,
+};
+use crate::transcript::TranscriptProtocol;
+use merlin::Transcript;
 #[cfg(feature = "serde")]
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
-/// `PreProcessedCircuit` is a data structure that holds the commitments to
-/// the selector and sigma polynomials.
+/// `ProverCircuitKey` is the prover side of the preprocessed circuit.
 ///
-/// By doing this, we can see the `PreProcessedCircuit` as a "circuit-shape descriptor"
-/// since it only stores the commitments that describe the operations that we will perform
-/// innside the circuit.
+/// Besides the commitments to the selector and sigma polynomials, it holds
+/// the polynomials themselves and their 8n coset evaluations, which the
+/// prover needs to compute the quotient and linearisation polynomials.
 #[derive(Debug, Eq, PartialEq)]
-pub struct PreProcessedCircuit {
-    /// The number of gates in the circuit
-    pub n: usize,
+pub struct ProverCircuitKey {
+    /// The evaluation domain of the circuit. Its size is the number
+    /// of gates in the circuit once padded to a power of two.
+    pub domain: EvaluationDomain,
+    /// Indexes of the gates that hold a public input, in the
+    /// same order as the public inputs are given to the verifier.
+    pub pi_pos: Vec<usize>,
     /// Holds the polynomials, commitments and evaluations
     /// of all of the arithmetic-related gates.
     pub arithmetic: ArithmeticWidget,
@@ -23,47 +33,71 @@ pub struct PreProcessedCircuit {
     /// of all of the logic_constraint gates.
     pub logic: LogicWidget,
     /// Holds the polynomials, commitments and evaluations
+    /// of all of the point addition gates.
+    pub ecc_add: EccAddWidget,
+    /// Holds the polynomials, commitments and evaluations
+    /// of all of the fixed base scalar multiplication gates.
+    pub fixed_base: FixedBaseWidget,
+    /// Holds the polynomials, commitments and evaluations
+    /// of all of the lookup gates and of the lookup tables.
+    /// It is `None` for circuits without lookup tables, whose
+    /// proofs skip the lookup argument.
+    pub lookup: Option<LookupWidget>,
+    /// Holds the polynomials, commitments and evaluations of the
+    /// selectors of every custom widget, in the order they were added.
+    pub custom: Vec<CustomWidget>,
+    /// Holds the polynomials, commitments and evaluations
     /// related to the sigmas and also stores the linear
     /// evaluations.
     pub permutation: PermutationWidget,
 
-    // Pre-processes the 4n Evaluations for the vanishing polynomial, so they do not
+    // Pre-processes the 8n Evaluations for the vanishing polynomial, so they do not
     // need to be computed at the proving stage.
     // Note: With this, we can combine all parts of the quotient polynomial in their evaluation phase and
     // divide by the quotient polynomial without having to perform IFFT
-    pub(crate) v_h_coset_4n: Evaluations,
+    pub(crate) v_h_coset_8n: Evaluations,
 }
 
 #[cfg(feature = "serde")]
-impl Serialize for PreProcessedCircuit {
+impl Serialize for ProverCircuitKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut prep_circ = serializer.serialize_struct("struct PreProcessedCircuit", 6)?;
-        prep_circ.serialize_field("n", &self.n)?;
+        let mut prep_circ = serializer.serialize_struct("struct ProverCircuitKey", 11)?;
+        prep_circ.serialize_field("domain", &self.domain)?;
+        prep_circ.serialize_field("pi_pos", &self.pi_pos)?;
         prep_circ.serialize_field("arith_widg", &self.arithmetic)?;
         prep_circ.serialize_field("logic_widg", &self.logic)?;
         prep_circ.serialize_field("range_widg", &self.range)?;
+        prep_circ.serialize_field("ecc_add_widg", &self.ecc_add)?;
+        prep_circ.serialize_field("fixed_base_widg", &self.fixed_base)?;
+        prep_circ.serialize_field("lookup_widg", &self.lookup)?;
+        prep_circ.serialize_field("custom_widgs", &self.custom)?;
         prep_circ.serialize_field("perm_widg", &self.permutation)?;
-        prep_circ.serialize_field("v_h_coset_4n", &self.v_h_coset_4n)?;
+        prep_circ.serialize_field("v_h_coset_8n", &self.v_h_coset_8n)?;
         prep_circ.end()
     }
 }
 
 #[cfg(feature = "serde")]
-impl<'de> Deserialize<'de> for PreProcessedCircuit {
+impl<'de> Deserialize<'de> for ProverCircuitKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         enum Field {
-            N,
+            Domain,
+            PiPos,
             ArithWidget,
             LogicWidget,
             RangeWidget,
+            EccAddWidget,
+            FixedBaseWidget,
+            LookupWidget,
+            CustomWidgets,
             PermWidget,
-            VhCoset4n,
+            VhCoset8n,
         };
 
         impl<'de> Deserialize<'de> for Field {
@@ -80,7 +114,7 @@ impl<'de> Deserialize<'de> for PreProcessedCircuit {
                         &self,
                         formatter: &mut ::core::fmt::Formatter,
                     ) -> ::core::fmt::Result {
-                        formatter.write_str("struct PreProcessedCircuit")
+                        formatter.write_str("struct ProverCircuitKey")
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -88,12 +122,17 @@ impl<'de> Deserialize<'de> for PreProcessedCircuit {
                         E: serde::de::Error,
                     {
                         match value {
-                            "n" => Ok(Field::N),
+                            "domain" => Ok(Field::Domain),
+                            "pi_pos" => Ok(Field::PiPos),
                             "arith_widg" => Ok(Field::ArithWidget),
                             "logic_widg" => Ok(Field::LogicWidget),
                             "range_widg" => Ok(Field::RangeWidget),
+                            "ecc_add_widg" => Ok(Field::EccAddWidget),
+                            "fixed_base_widg" => Ok(Field::FixedBaseWidget),
+                            "lookup_widg" => Ok(Field::LookupWidget),
+                            "custom_widgs" => Ok(Field::CustomWidgets),
                             "perm_widg" => Ok(Field::PermWidget),
-                            "v_h_coset_4n" => Ok(Field::VhCoset4n),
+                            "v_h_coset_8n" => Ok(Field::VhCoset8n),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -103,20 +142,23 @@ impl<'de> Deserialize<'de> for PreProcessedCircuit {
             }
         }
 
-        struct PreProcessedCircuitVisitor;
+        struct ProverCircuitKeyVisitor;
 
-        impl<'de> Visitor<'de> for PreProcessedCircuitVisitor {
-            type Value = PreProcessedCircuit;
+        impl<'de> Visitor<'de> for ProverCircuitKeyVisitor {
+            type Value = ProverCircuitKey;
 
             fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                formatter.write_str("struct PreProcessedCircuit")
+                formatter.write_str("struct ProverCircuitKey")
             }
 
-            fn visit_seq<V>(self, mut seq: V) -> Result<PreProcessedCircuit, V::Error>
+            fn visit_seq<V>(self, mut seq: V) -> Result<ProverCircuitKey, V::Error>
             where
                 V: serde::de::SeqAccess<'de>,
             {
-                let n = seq
+                let domain = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let pi_pos = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
                 let arith_widg = seq
@@ -128,39 +170,339 @@ impl<'de> Deserialize<'de> for PreProcessedCircuit {
                 let range_widg = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let ecc_add_widg = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let fixed_base_widg = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let lookup_widg = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let custom_widgs = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
                 let perm_widg = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-                let v_h_coset_4n = seq
+                let v_h_coset_8n = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
-                Ok(PreProcessedCircuit {
-                    n,
+                Ok(ProverCircuitKey {
+                    domain,
+                    pi_pos,
                     arithmetic: arith_widg,
                     logic: logic_widg,
                     range: range_widg,
+                    ecc_add: ecc_add_widg,
+                    fixed_base: fixed_base_widg,
+                    lookup: lookup_widg,
+                    custom: custom_widgs,
                     permutation: perm_widg,
-                    v_h_coset_4n,
+                    v_h_coset_8n,
                 })
             }
         }
 
         const FIELDS: &[&str] = &[
-            "n",
+            "domain",
+            "pi_pos",
             "arith_widg",
             "logic_widg",
             "range_widg",
+            "ecc_add_widg",
+            "fixed_base_widg",
+            "lookup_widg",
+            "custom_widgs",
             "perm_widg",
-            "v_h_coset_4n",
+            "v_h_coset_8n",
             "q_arith",
         ];
-        deserializer.deserialize_struct("PreProcessedCircuit", FIELDS, PreProcessedCircuitVisitor)
+        deserializer.deserialize_struct("ProverCircuitKey", FIELDS, ProverCircuitKeyVisitor)
+    }
+}
+
+/// `VerifierCircuitKey` is the verifier side of the preprocessed circuit.
+///
+/// It only stores the commitments to the selector and sigma polynomials
+/// along with the evaluation domain and the positions of the public inputs,
+/// which is everything needed to verify a `Proof`. It can be seen as a "circuit-shape descriptor".
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct VerifierCircuitKey {
+    /// The evaluation domain of the circuit. Its size is the number
+    /// of gates in the circuit once padded to a power of two.
+    pub domain: EvaluationDomain,
+    /// Indexes of the gates that hold a public input, in the
+    /// same order as the public inputs are given to the verifier.
+    pub pi_pos: Vec<usize>,
+    /// Commitments to the arithmetic selector polynomials.
+    pub arithmetic: ArithmeticVerifierKey,
+    /// Commitment to the range selector polynomial.
+    pub range: RangeVerifierKey,
+    /// Commitments to the logic selector polynomials.
+    pub logic: LogicVerifierKey,
+    /// Commitment to the point addition selector polynomial.
+    pub ecc_add: EccAddVerifierKey,
+    /// Commitment to the fixed base scalar multiplication selector polynomial.
+    pub fixed_base: FixedBaseVerifierKey,
+    /// Commitments to the lookup selector polynomial and to the lookup tables.
+    /// It is `None` for circuits without lookup tables, whose
+    /// proofs skip the lookup argument.
+    pub lookup: Option<LookupVerifierKey>,
+    /// Commitments to the selector polynomials of every custom widget.
+    pub custom: Vec<CustomVerifierKey>,
+    /// Commitments to the sigma polynomials.
+    pub permutation: PermutationVerifierKey,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for VerifierCircuitKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut verifier_circuit_key =
+            serializer.serialize_struct("struct VerifierCircuitKey", 10)?;
+        verifier_circuit_key.serialize_field("domain", &self.domain)?;
+        verifier_circuit_key.serialize_field("pi_pos", &self.pi_pos)?;
+        verifier_circuit_key.serialize_field("arith_widg", &self.arithmetic)?;
+        verifier_circuit_key.serialize_field("range_widg", &self.range)?;
+        verifier_circuit_key.serialize_field("logic_widg", &self.logic)?;
+        verifier_circuit_key.serialize_field("ecc_add_widg", &self.ecc_add)?;
+        verifier_circuit_key.serialize_field("fixed_base_widg", &self.fixed_base)?;
+        verifier_circuit_key.serialize_field("lookup_widg", &self.lookup)?;
+        verifier_circuit_key.serialize_field("custom_widgs", &self.custom)?;
+        verifier_circuit_key.serialize_field("perm_widg", &self.permutation)?;
+        verifier_circuit_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for VerifierCircuitKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Domain,
+            PiPos,
+            ArithWidg,
+            RangeWidg,
+            LogicWidg,
+            EccAddWidg,
+            FixedBaseWidg,
+            LookupWidg,
+            CustomWidgs,
+            PermWidg,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct VerifierCircuitKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "domain" => Ok(Field::Domain),
+                            "pi_pos" => Ok(Field::PiPos),
+                            "arith_widg" => Ok(Field::ArithWidg),
+                            "range_widg" => Ok(Field::RangeWidg),
+                            "logic_widg" => Ok(Field::LogicWidg),
+                            "ecc_add_widg" => Ok(Field::EccAddWidg),
+                            "fixed_base_widg" => Ok(Field::FixedBaseWidg),
+                            "lookup_widg" => Ok(Field::LookupWidg),
+                            "custom_widgs" => Ok(Field::CustomWidgs),
+                            "perm_widg" => Ok(Field::PermWidg),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct VerifierCircuitKeyVisitor;
+
+        impl<'de> Visitor<'de> for VerifierCircuitKeyVisitor {
+            type Value = VerifierCircuitKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct VerifierCircuitKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<VerifierCircuitKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let domain = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let pi_pos = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                let arithmetic = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
+                let range = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(3, &self))?;
+                let logic = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(4, &self))?;
+                let ecc_add = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(5, &self))?;
+                let fixed_base = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(6, &self))?;
+                let lookup = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(7, &self))?;
+                let custom = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(8, &self))?;
+                let permutation = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(9, &self))?;
+                Ok(VerifierCircuitKey {
+                    domain,
+                    pi_pos,
+                    arithmetic,
+                    range,
+                    logic,
+                    ecc_add,
+                    fixed_base,
+                    lookup,
+                    custom,
+                    permutation,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &[
+            "domain",
+            "pi_pos",
+            "arith_widg",
+            "range_widg",
+            "logic_widg",
+            "ecc_add_widg",
+            "fixed_base_widg",
+            "lookup_widg",
+            "custom_widgs",
+            "perm_widg",
+        ];
+        deserializer.deserialize_struct("VerifierCircuitKey", FIELDS, VerifierCircuitKeyVisitor)
     }
 }
 
-impl PreProcessedCircuit {
-    pub(crate) fn v_h_coset_4n(&self) -> &Evaluations {
-        &self.v_h_coset_4n
+impl ProverCircuitKey {
+    pub(crate) fn v_h_coset_8n(&self) -> &Evaluations {
+        &self.v_h_coset_8n
+    }
+
+    /// Extracts the `VerifierCircuitKey` out of the `ProverCircuitKey`.
+    pub fn verifier_circuit_key(&self) -> VerifierCircuitKey {
+        VerifierCircuitKey {
+            domain: self.domain,
+            pi_pos: self.pi_pos.clone(),
+            arithmetic: self.arithmetic.verifier_key(),
+            range: self.range.verifier_key(),
+            logic: self.logic.verifier_key(),
+            ecc_add: self.ecc_add.verifier_key(),
+            fixed_base: self.fixed_base.verifier_key(),
+            lookup: self.lookup.as_ref().map(|lookup| lookup.verifier_key()),
+            custom: self
+                .custom
+                .iter()
+                .map(|widget| widget.verifier_key())
+                .collect(),
+            permutation: self.permutation.verifier_key(),
+        }
+    }
+}
+
+impl VerifierCircuitKey {
+    /// Computes a digest of the circuit described by the key.
+    ///
+    /// It commits to the circuit size, the positions of the public inputs
+    /// and every selector, lookup table and sigma commitment, including the
+    /// names and selectors of the custom widgets, so two different circuits
+    /// never share the same id.
+    pub fn circuit_id(&self) -> [u8; 32] {
+        let mut transcript = Transcript::new(b"plonk-circuit-id");
+
+        transcript.circuit_domain_sep(self.domain.size() as u64);
+        transcript.append_u64(b"num_pi", self.pi_pos.len() as u64);
+        for pos in self.pi_pos.iter() {
+            transcript.append_u64(b"pi_pos", *pos as u64);
+        }
+
+        transcript.append_commitment(b"q_m", &self.arithmetic.q_m);
+        transcript.append_commitment(b"q_l", &self.arithmetic.q_l);
+        transcript.append_commitment(b"q_r", &self.arithmetic.q_r);
+        transcript.append_commitment(b"q_o", &self.arithmetic.q_o);
+        transcript.append_commitment(b"q_c", &self.arithmetic.q_c);
+        transcript.append_commitment(b"q_4", &self.arithmetic.q_4);
+        transcript.append_commitment(b"q_arith", &self.arithmetic.q_arith);
+        transcript.append_commitment(b"q_range", &self.range.q_range);
+        transcript.append_commitment(b"q_logic", &self.logic.q_logic);
+        transcript.append_commitment(b"q_ecc_add", &self.ecc_add.q_ecc_add);
+        transcript.append_commitment(b"q_fixed_base", &self.fixed_base.q_fixed_base);
+
+        transcript.append_u64(b"has_lookup", u64::from(self.lookup.is_some()));
+        if let Some(lookup) = &self.lookup {
+            transcript.append_commitment(b"q_lookup", &lookup.q_lookup);
+            transcript.append_commitment(b"table_1", &lookup.table_1);
+            transcript.append_commitment(b"table_2", &lookup.table_2);
+            transcript.append_commitment(b"table_3", &lookup.table_3);
+            transcript.append_commitment(b"table_4", &lookup.table_4);
+        }
+
+        transcript.append_u64(b"num_custom", self.custom.len() as u64);
+        for widget in self.custom.iter() {
+            transcript.append_message(b"custom_name", widget.name.as_bytes());
+            transcript.append_u64(b"num_custom_selectors", widget.selectors.len() as u64);
+            for selector in widget.selectors.iter() {
+                transcript.append_commitment(b"q_custom", selector);
+            }
+        }
+
+        transcript.append_commitment(b"left_sigma", &self.permutation.left_sigma);
+        transcript.append_commitment(b"right_sigma", &self.permutation.right_sigma);
+        transcript.append_commitment(b"out_sigma", &self.permutation.out_sigma);
+        transcript.append_commitment(b"fourth_sigma", &self.permutation.fourth_sigma);
+
+        let mut circuit_id = [0u8; 32];
+        transcript.challenge_bytes(b"circuit_id", &mut circuit_id);
+        circuit_id
+    }
+
+    /// Appends the circuit description to the `transcript`.
+    ///
+    /// `StandardComposer::preprocess` does this on the transcript it is
+    /// given, so a verifier that only loads the `VerifierCircuitKey` must call
+    /// this on a fresh transcript to obtain the same challenges.
+    pub fn seed_transcript(&self, transcript: &mut dyn TranscriptProtocol) {
+        // Append circuit size to transcript
+        transcript.circuit_domain_sep(self.domain.size() as u64);
+        // Bind the proof to this exact circuit
+        transcript.append_circuit_id(&self.circuit_id());
     }
 }
 
@@ -175,7 +517,6 @@ mod tests {
     #[test]
     fn prep_circuit_serde_roundtrip() {
         use bincode;
-        let n = 24562352usize;
         let coeffs = vec![
             Scalar::one(),
             Scalar::one(),
@@ -239,25 +580,282 @@ mod tests {
         // Build directly the widget since the `new()` impl doesn't check any
         // correctness on the inputs.
         let range_widget = RangeWidget {
-            q_range: prep_poly_w_evals,
+            q_range: prep_poly_w_evals.clone(),
         };
 
         // Build directly the widget since the `new()` impl doesn't check any
         // correctness on the inputs.
-        let v_h_coset_4n = evals;
+        let ecc_add_widget = EccAddWidget {
+            q_ecc_add: prep_poly_w_evals.clone(),
+        };
 
-        let prep_circ = PreProcessedCircuit {
-            n,
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let fixed_base_widget = FixedBaseWidget {
+            q_fixed_base: prep_poly_w_evals.clone(),
+        };
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let lookup_widget = LookupWidget {
+            q_lookup: prep_poly_w_evals.clone(),
+            q_c: prep_poly_w_evals.clone(),
+            table_1: prep_poly_w_evals.clone(),
+            table_2: prep_poly_without_evals.clone(),
+            table_3: prep_poly_without_evals.clone(),
+            table_4: prep_poly_w_evals.clone(),
+        };
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let custom_widget = CustomWidget {
+            name: "custom".to_string(),
+            selectors: vec![prep_poly_w_evals, prep_poly_without_evals],
+        };
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let v_h_coset_8n = evals;
+
+        let prep_circ = ProverCircuitKey {
+            domain: dom,
+            pi_pos: vec![0, 3, 7],
             arithmetic: arith_widget,
             logic: logic_widget,
             range: range_widget,
+            ecc_add: ecc_add_widget,
+            fixed_base: fixed_base_widget,
+            lookup: Some(lookup_widget),
+            custom: vec![custom_widget],
             permutation: perm_widget,
-            v_h_coset_4n,
+            v_h_coset_8n,
         };
 
         // Roundtrip with evals
         let ser = bincode::serialize(&prep_circ).unwrap();
-        let deser: PreProcessedCircuit = bincode::deserialize(&ser).unwrap();
+        let deser: ProverCircuitKey = bincode::deserialize(&ser).unwrap();
         assert_eq!(prep_circ, deser);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn verifier_circuit_key_serde_roundtrip() {
+        use bincode;
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+
+        // Build directly the keys since there's not any `new()` impl
+        // for them.
+        let verifier_circuit_key = VerifierCircuitKey {
+            domain: EvaluationDomain::new(1 << 10).unwrap(),
+            pi_pos: vec![1, 2, 1000],
+            arithmetic: ArithmeticVerifierKey {
+                q_m: comm,
+                q_l: comm,
+                q_r: comm,
+                q_o: comm,
+                q_c: comm,
+                q_4: comm,
+                q_arith: comm,
+            },
+            range: RangeVerifierKey { q_range: comm },
+            logic: LogicVerifierKey { q_logic: comm },
+            ecc_add: EccAddVerifierKey { q_ecc_add: comm },
+            fixed_base: FixedBaseVerifierKey { q_fixed_base: comm },
+            lookup: Some(LookupVerifierKey {
+                q_lookup: comm,
+                table_1: comm,
+                table_2: comm,
+                table_3: comm,
+                table_4: comm,
+            }),
+            custom: vec![CustomVerifierKey {
+                name: "custom".to_string(),
+                selectors: vec![comm, comm],
+            }],
+            permutation: PermutationVerifierKey {
+                left_sigma: comm,
+                right_sigma: comm,
+                out_sigma: comm,
+                fourth_sigma: comm,
+            },
+        };
+
+        let ser = bincode::serialize(&verifier_circuit_key).unwrap();
+        let deser: VerifierCircuitKey = bincode::deserialize(&ser).unwrap();
+        assert_eq!(verifier_circuit_key, deser);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn verifier_circuit_key_size() {
+        use bincode;
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+        let domain = EvaluationDomain::new(1 << 10).unwrap();
+
+        let mut verifier_circuit_key = VerifierCircuitKey {
+            domain,
+            pi_pos: vec![1, 2, 1000],
+            arithmetic: ArithmeticVerifierKey {
+                q_m: comm,
+                q_l: comm,
+                q_r: comm,
+                q_o: comm,
+                q_c: comm,
+                q_4: comm,
+                q_arith: comm,
+            },
+            range: RangeVerifierKey { q_range: comm },
+            logic: LogicVerifierKey { q_logic: comm },
+            ecc_add: EccAddVerifierKey { q_ecc_add: comm },
+            fixed_base: FixedBaseVerifierKey { q_fixed_base: comm },
+            lookup: None,
+            custom: vec![],
+            permutation: PermutationVerifierKey {
+                left_sigma: comm,
+                right_sigma: comm,
+                out_sigma: comm,
+                fourth_sigma: comm,
+            },
+        };
+
+        // The key only holds the domain, the positions of the public inputs
+        // and one commitment per selector, so its size does not depend on
+        // the number of gates. Vectors are prefixed with their length and
+        // options with a tag.
+        let commitment_size = bincode::serialize(&comm).unwrap().len();
+        let domain_size = bincode::serialize(&domain).unwrap().len();
+        let pi_pos_size = 8 + 3 * 8;
+        let size = domain_size + pi_pos_size + 1 + 8 + 15 * commitment_size;
+        assert_eq!(
+            bincode::serialize(&verifier_circuit_key).unwrap().len(),
+            size
+        );
+
+        // The lookup argument adds five commitments, and a custom widget
+        // adds its name and one commitment per selector
+        verifier_circuit_key.lookup = Some(LookupVerifierKey {
+            q_lookup: comm,
+            table_1: comm,
+            table_2: comm,
+            table_3: comm,
+            table_4: comm,
+        });
+        verifier_circuit_key.custom = vec![CustomVerifierKey {
+            name: "custom".to_string(),
+            selectors: vec![comm, comm],
+        }];
+        let custom_size = (8 + 6) + 8 + 2 * commitment_size;
+        assert_eq!(
+            bincode::serialize(&verifier_circuit_key).unwrap().len(),
+            size + 5 * commitment_size + custom_size
+        );
+    }
+
+    // Lists every commitment of the key, in the order `circuit_id` reads them
+    fn commitments_mut(
+        key: &mut VerifierCircuitKey,
+    ) -> Vec<&mut crate::commitment_scheme::kzg10::Commitment> {
+        let mut commitments = vec![
+            &mut key.arithmetic.q_m,
+            &mut key.arithmetic.q_l,
+            &mut key.arithmetic.q_r,
+            &mut key.arithmetic.q_o,
+            &mut key.arithmetic.q_c,
+            &mut key.arithmetic.q_4,
+            &mut key.arithmetic.q_arith,
+            &mut key.range.q_range,
+            &mut key.logic.q_logic,
+            &mut key.ecc_add.q_ecc_add,
+            &mut key.fixed_base.q_fixed_base,
+        ];
+        if let Some(lookup) = key.lookup.as_mut() {
+            commitments.push(&mut lookup.q_lookup);
+            commitments.push(&mut lookup.table_1);
+            commitments.push(&mut lookup.table_2);
+            commitments.push(&mut lookup.table_3);
+            commitments.push(&mut lookup.table_4);
+        }
+        for widget in key.custom.iter_mut() {
+            commitments.extend(widget.selectors.iter_mut());
+        }
+        commitments.push(&mut key.permutation.left_sigma);
+        commitments.push(&mut key.permutation.right_sigma);
+        commitments.push(&mut key.permutation.out_sigma);
+        commitments.push(&mut key.permutation.fourth_sigma);
+        commitments
+    }
+
+    #[test]
+    fn test_circuit_id() {
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+        let other_comm = crate::commitment_scheme::kzg10::Commitment::from_affine(
+            (G1Affine::generator() * Scalar::from(2u64)).into(),
+        );
+
+        let verifier_circuit_key = VerifierCircuitKey {
+            domain: EvaluationDomain::new(1 << 10).unwrap(),
+            pi_pos: vec![1, 2],
+            arithmetic: ArithmeticVerifierKey {
+                q_m: comm,
+                q_l: comm,
+                q_r: comm,
+                q_o: comm,
+                q_c: comm,
+                q_4: comm,
+                q_arith: comm,
+            },
+            range: RangeVerifierKey { q_range: comm },
+            logic: LogicVerifierKey { q_logic: comm },
+            ecc_add: EccAddVerifierKey { q_ecc_add: comm },
+            fixed_base: FixedBaseVerifierKey { q_fixed_base: comm },
+            lookup: Some(LookupVerifierKey {
+                q_lookup: comm,
+                table_1: comm,
+                table_2: comm,
+                table_3: comm,
+                table_4: comm,
+            }),
+            custom: vec![CustomVerifierKey {
+                name: "custom".to_string(),
+                selectors: vec![comm, comm],
+            }],
+            permutation: PermutationVerifierKey {
+                left_sigma: comm,
+                right_sigma: comm,
+                out_sigma: comm,
+                fourth_sigma: comm,
+            },
+        };
+        let circuit_id = verifier_circuit_key.circuit_id();
+        assert_eq!(circuit_id, verifier_circuit_key.clone().circuit_id());
+
+        let mut moved_pi = verifier_circuit_key.clone();
+        moved_pi.pi_pos = vec![1, 3];
+        assert_ne!(circuit_id, moved_pi.circuit_id());
+
+        // Every commitment the verifier reads must be bound to the id
+        let num_commitments = commitments_mut(&mut verifier_circuit_key.clone()).len();
+        assert_eq!(num_commitments, 22);
+        for i in 0..num_commitments {
+            let mut other_commitment = verifier_circuit_key.clone();
+            *commitments_mut(&mut other_commitment)[i] = other_comm;
+            assert_ne!(circuit_id, other_commitment.circuit_id());
+        }
+
+        let mut no_lookup = verifier_circuit_key.clone();
+        no_lookup.lookup = None;
+        assert_ne!(circuit_id, no_lookup.circuit_id());
+
+        let mut other_name = verifier_circuit_key.clone();
+        other_name.custom[0].name = "other".to_string();
+        assert_ne!(circuit_id, other_name.circuit_id());
+
+        let mut no_custom = verifier_circuit_key.clone();
+        no_custom.custom.clear();
+        assert_ne!(circuit_id, no_custom.circuit_id());
+
+        let mut other_size = verifier_circuit_key;
+        other_size.domain = EvaluationDomain::new(1 << 11).unwrap();
+        assert_ne!(circuit_id, other_size.circuit_id());
+    }
 }
@@ -3,13 +3,19 @@
 //!
 //! This module contains the implementation of the `StandardComposer`s
 //! `Proof` structure and it's methods.
-use super::linearisation_poly::ProofEvaluations;
-use super::PreProcessedCircuit;
+use super::linearisation_poly::{LookupEvaluations, ProofEvaluations};
+use super::widget::lookup::{self, LookupVerifierKey};
+use super::widget::Widget;
+use super::VerifierCircuitKey;
+use crate::commitment_scheme::kzg10;
 use crate::commitment_scheme::kzg10::AggregateProof;
 use crate::commitment_scheme::kzg10::{Commitment, VerifierKey};
+use crate::errors::Error;
 use crate::fft::EvaluationDomain;
 use crate::transcript::TranscriptProtocol;
+use crate::util;
 use bls12_381::{multiscalar_mul::msm_variable_base, G1Affine, Scalar};
+use rand_core::{CryptoRng, RngCore};
 #[cfg(feature = "serde")]
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -49,6 +55,26 @@ pub struct Proof {
     pub w_zw_comm: Commitment,
     /// Subset of all of the evaluations added to the proof.
     pub evaluations: ProofEvaluations,
+
+    /// Commitments and evaluations of the lookup argument. It is `None` for
+    /// circuits without lookup tables.
+    pub lookup: Option<LookupProof>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+/// A LookupProof holds the `Commitments` to the polynomials of the lookup
+/// argument, as well as their `LookupEvaluations`.
+pub struct LookupProof {
+    /// Commitment to the lookup query polynomial.
+    pub f_comm: Commitment,
+    /// Commitment to the first half of the sorted list.
+    pub h_1_comm: Commitment,
+    /// Commitment to the second half of the sorted list.
+    pub h_2_comm: Commitment,
+    /// Commitment to the lookup accumulator polynomial.
+    pub z_2_comm: Commitment,
+    /// Evaluations of the lookup polynomials.
+    pub evaluations: LookupEvaluations,
 }
 
 #[cfg(feature = "serde")]
@@ -57,7 +83,7 @@ impl Serialize for Proof {
     where
         S: Serializer,
     {
-        let mut proof = serializer.serialize_struct("struct Proof", 12)?;
+        let mut proof = serializer.serialize_struct("struct Proof", 13)?;
         proof.serialize_field("a_comm", &self.a_comm)?;
         proof.serialize_field("b_comm", &self.b_comm)?;
         proof.serialize_field("c_comm", &self.c_comm)?;
@@ -70,6 +96,7 @@ impl Serialize for Proof {
         proof.serialize_field("w_z_comm", &self.w_z_comm)?;
         proof.serialize_field("w_zw_comm", &self.w_zw_comm)?;
         proof.serialize_field("evaluations", &self.evaluations)?;
+        proof.serialize_field("lookup", &self.lookup)?;
         proof.end()
     }
 }
@@ -93,6 +120,7 @@ impl<'de> Deserialize<'de> for Proof {
             WZcomm,
             WZWcomm,
             Evals,
+            Lookup,
         };
 
         impl<'de> Deserialize<'de> for Field {
@@ -129,6 +157,7 @@ impl<'de> Deserialize<'de> for Proof {
                             "w_z_comm" => Ok(Field::WZcomm),
                             "w_zw_comm" => Ok(Field::WZWcomm),
                             "evaluations" => Ok(Field::Evals),
+                            "lookup" => Ok(Field::Lookup),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -187,6 +216,9 @@ impl<'de> Deserialize<'de> for Proof {
                 let evaluations = seq
                     .next_element()?
                     .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let lookup = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
                 Ok(Proof {
                     a_comm,
                     b_comm,
@@ -200,6 +232,7 @@ impl<'de> Deserialize<'de> for Proof {
                     w_z_comm,
                     w_zw_comm,
                     evaluations,
+                    lookup,
                 })
             }
         }
@@ -217,23 +250,401 @@ impl<'de> Deserialize<'de> for Proof {
             "w_z_comm",
             "w_zw_comm",
             "evaluations",
+            "lookup",
         ];
         deserializer.deserialize_struct("Proof", FIELDS, ProofVisitor)
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for LookupProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut lookup = serializer.serialize_struct("struct LookupProof", 5)?;
+        lookup.serialize_field("f_comm", &self.f_comm)?;
+        lookup.serialize_field("h_1_comm", &self.h_1_comm)?;
+        lookup.serialize_field("h_2_comm", &self.h_2_comm)?;
+        lookup.serialize_field("z_2_comm", &self.z_2_comm)?;
+        lookup.serialize_field("evaluations", &self.evaluations)?;
+        lookup.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LookupProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Fcomm,
+            H1comm,
+            H2comm,
+            Z2comm,
+            Evals,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct LookupProof")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "f_comm" => Ok(Field::Fcomm),
+                            "h_1_comm" => Ok(Field::H1comm),
+                            "h_2_comm" => Ok(Field::H2comm),
+                            "z_2_comm" => Ok(Field::Z2comm),
+                            "evaluations" => Ok(Field::Evals),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct LookupProofVisitor;
+
+        impl<'de> Visitor<'de> for LookupProofVisitor {
+            type Value = LookupProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct LookupProof")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<LookupProof, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let f_comm = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let h_1_comm = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let h_2_comm = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let z_2_comm = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let evaluations = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(LookupProof {
+                    f_comm,
+                    h_1_comm,
+                    h_2_comm,
+                    z_2_comm,
+                    evaluations,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["f_comm", "h_1_comm", "h_2_comm", "z_2_comm", "evaluations"];
+        deserializer.deserialize_struct("LookupProof", FIELDS, LookupProofVisitor)
+    }
+}
+
 impl Proof {
-    /// Performs the verification of a `Proof` returning a boolean result.
+    /// Size of the byte representation of a `Proof` without a lookup
+    /// argument: 11 compressed `G1Affine` commitments followed by 16 `Scalar`
+    /// evaluations.
+    pub const SIZE: usize = 11 * COMMITMENT_SIZE + 16 * SCALAR_SIZE;
+
+    /// Number of bytes that the lookup argument adds to the byte
+    /// representation of a `Proof`: 4 commitments followed by 7 evaluations.
+    pub const LOOKUP_SIZE: usize = 4 * COMMITMENT_SIZE + 7 * SCALAR_SIZE;
+
+    /// Serializes the `Proof` into its canonical byte representation.
+    ///
+    /// The commitments are written first, in the order they are declared in
+    /// the struct, using the compressed encoding of their points. They are
+    /// followed by the evaluations, each one as a little-endian `Scalar`.
+    /// The lookup argument, if any, is appended in the same way.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Proof::SIZE + Proof::LOOKUP_SIZE);
+
+        for comm in self.commitments().iter() {
+            bytes.extend_from_slice(&comm.0.to_compressed());
+        }
+        for eval in self.evaluations.to_vec().iter() {
+            bytes.extend_from_slice(&eval.to_bytes());
+        }
+
+        if let Some(lookup) = &self.lookup {
+            for comm in [
+                lookup.f_comm,
+                lookup.h_1_comm,
+                lookup.h_2_comm,
+                lookup.z_2_comm,
+            ]
+            .iter()
+            {
+                bytes.extend_from_slice(&comm.0.to_compressed());
+            }
+            for eval in lookup.evaluations.to_vec().iter() {
+                bytes.extend_from_slice(&eval.to_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Deserializes a `Proof` from the byte representation given by
+    /// `to_bytes`.
+    ///
+    /// Every commitment is checked to be a point on the curve which lies in
+    /// the prime order subgroup, and every evaluation to be a canonically
+    /// encoded `Scalar`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof, Error> {
+        if bytes.len() != Proof::SIZE && bytes.len() != Proof::SIZE + Proof::LOOKUP_SIZE {
+            return Err(Error::InvalidProofLength {
+                expected: Proof::SIZE,
+                expected_with_lookup: Proof::SIZE + Proof::LOOKUP_SIZE,
+            });
+        }
+
+        let (bytes, lookup_bytes) = bytes.split_at(Proof::SIZE);
+        let (comms, evals) = read_section(bytes, 11)?;
+
+        let lookup = if lookup_bytes.is_empty() {
+            None
+        } else {
+            let (lookup_comms, lookup_evals) = read_section(lookup_bytes, 4)?;
+            Some(LookupProof {
+                f_comm: lookup_comms[0],
+                h_1_comm: lookup_comms[1],
+                h_2_comm: lookup_comms[2],
+                z_2_comm: lookup_comms[3],
+                evaluations: LookupEvaluations::from_slice(&lookup_evals),
+            })
+        };
+
+        Ok(Proof {
+            a_comm: comms[0],
+            b_comm: comms[1],
+            c_comm: comms[2],
+            d_comm: comms[3],
+            z_comm: comms[4],
+            t_1_comm: comms[5],
+            t_2_comm: comms[6],
+            t_3_comm: comms[7],
+            t_4_comm: comms[8],
+            w_z_comm: comms[9],
+            w_zw_comm: comms[10],
+            evaluations: ProofEvaluations::from_slice(&evals),
+            lookup,
+        })
+    }
+
+    // Returns the commitments of the proof in their serialization order.
+    fn commitments(&self) -> [Commitment; 11] {
+        [
+            self.a_comm,
+            self.b_comm,
+            self.c_comm,
+            self.d_comm,
+            self.z_comm,
+            self.t_1_comm,
+            self.t_2_comm,
+            self.t_3_comm,
+            self.t_4_comm,
+            self.w_z_comm,
+            self.w_zw_comm,
+        ]
+    }
+
+    /// Performs the verification of a `Proof`, returning the reason it was
+    /// rejected if it is not valid.
+    ///
+    /// The `transcript` must have been seeded with the circuit description,
+    /// either by `StandardComposer::preprocess` or by
+    /// `VerifierCircuitKey::seed_transcript`.
+    ///
+    /// `pub_inputs` holds only the values of the public inputs, in the
+    /// order in which they were added to the circuit. Their positions are
+    /// taken from the `VerifierCircuitKey`.
+    ///
+    /// Circuits that use custom widgets must be verified with
+    /// `verify_with_widgets` instead.
     pub fn verify(
         &self,
-        preprocessed_circuit: &PreProcessedCircuit,
+        verifier_circuit_key: &VerifierCircuitKey,
         transcript: &mut dyn TranscriptProtocol,
         verifier_key: &VerifierKey,
         pub_inputs: &[Scalar],
-    ) -> bool {
-        let domain = EvaluationDomain::new(preprocessed_circuit.n).unwrap();
+    ) -> Result<(), Error> {
+        self.verify_with_widgets(
+            verifier_circuit_key,
+            transcript,
+            verifier_key,
+            pub_inputs,
+            &[],
+        )
+    }
 
-        // subgroup checks are done when the proof is deserialised.
+    /// Performs the verification of a `Proof` of a circuit that uses custom
+    /// widgets, in the same way as `verify`.
+    ///
+    /// `widgets` must hold the same widgets that were added to the composer,
+    /// in the same order, or `Error::MismatchedCircuit` is returned.
+    pub fn verify_with_widgets(
+        &self,
+        verifier_circuit_key: &VerifierCircuitKey,
+        transcript: &mut dyn TranscriptProtocol,
+        verifier_key: &VerifierKey,
+        pub_inputs: &[Scalar],
+        widgets: &[&dyn Widget],
+    ) -> Result<(), Error> {
+        let (points, openings) =
+            self.compute_openings(verifier_circuit_key, transcript, pub_inputs, widgets)?;
+
+        if !verifier_key.batch_check(&points, &openings, transcript) {
+            return Err(Error::ProofVerificationFailed);
+        }
+        Ok(())
+    }
+
+    /// Verifies a batch of `Proof`s of the same circuit with a single
+    /// pairing check.
+    ///
+    /// Each entry of the `batch` holds a proof, its public inputs and the
+    /// transcript to verify it with, seeded in the same way as for `verify`.
+    /// As with `verify`, every transcript is left in the state reached at the
+    /// end of the verification of its proof.
+    /// The openings of every proof are folded together with weights drawn
+    /// from `rng`, so that invalid proofs cannot cancel each other out.
+    ///
+    /// When the aggregated check fails, the openings of every proof are
+    /// checked on their own and the indexes of the proofs that failed are
+    /// returned.
+    ///
+    /// `widgets` must hold the custom widgets of the circuit, as for
+    /// `verify_with_widgets`, and is empty for circuits without them.
+    pub fn batch_verify<R: RngCore + CryptoRng>(
+        batch: &mut [(&Proof, &[Scalar], &mut dyn TranscriptProtocol)],
+        verifier_circuit_key: &VerifierCircuitKey,
+        verifier_key: &VerifierKey,
+        widgets: &[&dyn Widget],
+        rng: &mut R,
+    ) -> Result<(), Vec<usize>> {
+        let mut failed = Vec::new();
+
+        // Run the transcript work of every proof, keeping its index, its
+        // openings and the challenge used to combine them.
+        let mut prepared = Vec::with_capacity(batch.len());
+        for (i, (proof, pub_inputs, transcript)) in batch.iter_mut().enumerate() {
+            match proof.compute_openings(
+                verifier_circuit_key,
+                &mut **transcript,
+                pub_inputs,
+                widgets,
+            ) {
+                Ok((points, openings)) => {
+                    let challenge = transcript.challenge_scalar(b"batch");
+                    prepared.push((i, points, openings, challenge));
+                }
+                Err(_) => failed.push(i),
+            }
+        }
+
+        // Fold all of the openings, scaling the ones of each proof by a random weight
+        let mut points = Vec::with_capacity(2 * prepared.len());
+        let mut openings = Vec::with_capacity(2 * prepared.len());
+        let mut weights = Vec::with_capacity(2 * prepared.len());
+        for (_, proof_points, proof_openings, challenge) in prepared.iter() {
+            let weight = util::random_scalar(rng);
+            points.extend_from_slice(proof_points);
+            openings.extend_from_slice(proof_openings);
+            weights.push(weight);
+            weights.push(weight * challenge);
+        }
+
+        if !prepared.is_empty()
+            && !verifier_key.batch_check_with_weights(&points, &openings, &weights)
+        {
+            // Find out which proofs made the aggregated check fail
+            for (i, proof_points, proof_openings, challenge) in prepared.iter() {
+                if !verifier_key.batch_check_with_weights(
+                    proof_points,
+                    proof_openings,
+                    &[Scalar::one(), *challenge],
+                ) {
+                    failed.push(*i);
+                }
+            }
+            failed.sort_unstable();
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed)
+        }
+    }
+
+    // Runs all of the transcript work of the verifier, returning the points
+    // and the KZG openings that must be checked for the proof to be valid.
+    //
+    // Returns an error if the public inputs or the custom widgets do not
+    // match the circuit.
+    fn compute_openings(
+        &self,
+        verifier_circuit_key: &VerifierCircuitKey,
+        transcript: &mut dyn TranscriptProtocol,
+        pub_inputs: &[Scalar],
+        widgets: &[&dyn Widget],
+    ) -> Result<([Scalar; 2], [kzg10::Proof; 2]), Error> {
+        let domain = verifier_circuit_key.domain;
+
+        // Every custom widget of the circuit must be provided, in the same order
+        if widgets.len() != verifier_circuit_key.custom.len()
+            || widgets
+                .iter()
+                .zip(verifier_circuit_key.custom.iter())
+                .any(|(widget, custom)| {
+                    widget.name() != custom.name || widget.num_selectors() != custom.selectors.len()
+                })
+        {
+            return Err(Error::MismatchedCircuit);
+        }
+
+        // Every public input must be provided
+        if pub_inputs.len() != verifier_circuit_key.pi_pos.len() {
+            return Err(Error::PublicInputsMismatch {
+                expected: verifier_circuit_key.pi_pos.len(),
+                found: pub_inputs.len(),
+            });
+        }
+
+        // The proof carries a lookup argument exactly when the circuit has lookup tables
+        let lookup = match (&verifier_circuit_key.lookup, &self.lookup) {
+            (Some(lookup_key), Some(lookup)) => Some((lookup_key, lookup)),
+            (None, None) => None,
+            _ => return Err(Error::ProofVerificationFailed),
+        };
+
+        // subgroup checks are done when the proof is decoded with `Proof::from_bytes`.
 
         // In order for the Verifier and Prover to have the same view in the non-interactive setting
         // Both parties must commit the same elements into the transcript
@@ -246,6 +657,28 @@ impl Proof {
         transcript.append_commitment(b"w_o", &self.c_comm);
         transcript.append_commitment(b"w_4", &self.d_comm);
 
+        // Add public inputs to transcript
+        transcript.append_public_inputs(pub_inputs);
+
+        // Compute the challenges of the lookup argument, if there is one
+        let lookup_challenges = lookup.map(|(_, lookup)| {
+            // Compute table compression challenge
+            let zeta = transcript.challenge_scalar(b"zeta");
+            // Add commitments to lookup query and sorted list polynomials to transcript
+            transcript.append_commitment(b"f", &lookup.f_comm);
+            transcript.append_commitment(b"h_1", &lookup.h_1_comm);
+            transcript.append_commitment(b"h_2", &lookup.h_2_comm);
+            // Compute lookup accumulator challenges
+            let delta = transcript.challenge_scalar(b"delta");
+            let epsilon = transcript.challenge_scalar(b"epsilon");
+            // Add commitment to the lookup accumulator to transcript
+            transcript.append_commitment(b"z_2", &lookup.z_2_comm);
+            // Compute lookup separation challenge
+            let separation_challenge = transcript.challenge_scalar(b"lookup_separation_challenge");
+
+            ((zeta, delta, epsilon), separation_challenge)
+        });
+
         // Compute beta and gamma challenges
         let beta = transcript.challenge_scalar(b"beta");
         transcript.append_scalar(b"beta", &beta);
@@ -255,6 +688,16 @@ impl Proof {
 
         // Compute quotient challenge
         let alpha = transcript.challenge_scalar(b"alpha");
+        // Compute point addition separation challenge
+        let ecc_separation_challenge = transcript.challenge_scalar(b"ecc_separation_challenge");
+        // Compute fixed base scalar multiplication separation challenge
+        let fixed_base_separation_challenge =
+            transcript.challenge_scalar(b"fixed_base_separation_challenge");
+        // Compute the separation challenge of every custom widget
+        let custom_separation_challenges: Vec<Scalar> = widgets
+            .iter()
+            .map(|_| transcript.challenge_scalar(b"custom_separation_challenge"))
+            .collect();
 
         // Add commitment to quotient polynomial to transcript
         transcript.append_commitment(b"t_1", &self.t_1_comm);
@@ -271,9 +714,13 @@ impl Proof {
         // Compute first lagrange polynomial evaluated at `z_challenge`
         let l1_eval = compute_first_lagrange_evaluation(&domain, &z_h_eval, &z_challenge);
 
+        // Compute last lagrange polynomial evaluated at `z_challenge`
+        let ln_eval = compute_last_lagrange_evaluation(&domain, &z_h_eval, &z_challenge);
+
         // Compute quotient polynomial evaluated at `z_challenge`
         let t_eval = self.compute_quotient_evaluation(
             &domain,
+            &verifier_circuit_key.pi_pos,
             pub_inputs,
             &alpha,
             &beta,
@@ -282,6 +729,18 @@ impl Proof {
             &z_h_eval,
             &l1_eval,
             &self.evaluations.perm_eval,
+            match (lookup, &lookup_challenges) {
+                (Some((_, lookup)), Some(((zeta, delta, epsilon), separation_challenge))) => {
+                    lookup::compute_constant_term(
+                        &lookup.evaluations,
+                        (zeta, delta, epsilon),
+                        separation_challenge,
+                        (&z_challenge, &domain.group_gen_inv),
+                        (&l1_eval, &ln_eval),
+                    )
+                }
+                _ => Scalar::zero(),
+            },
         );
 
         // Compute commitment to quotient polynomial
@@ -295,13 +754,25 @@ impl Proof {
         transcript.append_scalar(b"d_eval", &self.evaluations.d_eval);
         transcript.append_scalar(b"a_next_eval", &self.evaluations.a_next_eval);
         transcript.append_scalar(b"b_next_eval", &self.evaluations.b_next_eval);
+        transcript.append_scalar(b"c_next_eval", &self.evaluations.c_next_eval);
         transcript.append_scalar(b"d_next_eval", &self.evaluations.d_next_eval);
         transcript.append_scalar(b"left_sig_eval", &self.evaluations.left_sigma_eval);
         transcript.append_scalar(b"right_sig_eval", &self.evaluations.right_sigma_eval);
         transcript.append_scalar(b"out_sig_eval", &self.evaluations.out_sigma_eval);
         transcript.append_scalar(b"q_arith_eval", &self.evaluations.q_arith_eval);
         transcript.append_scalar(b"q_c_eval", &self.evaluations.q_c_eval);
+        transcript.append_scalar(b"q_fixed_base_eval", &self.evaluations.q_fixed_base_eval);
         transcript.append_scalar(b"perm_eval", &self.evaluations.perm_eval);
+        if let Some((_, lookup)) = lookup {
+            let evaluations = &lookup.evaluations;
+            transcript.append_scalar(b"f_eval", &evaluations.f_eval);
+            transcript.append_scalar(b"h_1_eval", &evaluations.h_1_eval);
+            transcript.append_scalar(b"h_1_next_eval", &evaluations.h_1_next_eval);
+            transcript.append_scalar(b"h_2_next_eval", &evaluations.h_2_next_eval);
+            transcript.append_scalar(b"table_eval", &evaluations.table_eval);
+            transcript.append_scalar(b"table_next_eval", &evaluations.table_next_eval);
+            transcript.append_scalar(b"lookup_perm_eval", &evaluations.lookup_perm_eval);
+        }
         transcript.append_scalar(b"t_eval", &t_eval);
         transcript.append_scalar(b"r_eval", &self.evaluations.lin_poly_eval);
 
@@ -310,9 +781,13 @@ impl Proof {
             &alpha,
             &beta,
             &gamma,
+            &ecc_separation_challenge,
+            &fixed_base_separation_challenge,
+            lookup.zip(lookup_challenges.as_ref()),
             &z_challenge,
-            l1_eval,
-            &preprocessed_circuit,
+            (l1_eval, ln_eval),
+            (widgets, &custom_separation_challenges),
+            &verifier_circuit_key,
         );
 
         // Commitment Scheme
@@ -331,43 +806,75 @@ impl Proof {
         aggregate_proof.add_part((self.evaluations.d_eval, self.d_comm));
         aggregate_proof.add_part((
             self.evaluations.left_sigma_eval,
-            preprocessed_circuit.permutation.left_sigma.commitment,
+            verifier_circuit_key.permutation.left_sigma,
         ));
         aggregate_proof.add_part((
             self.evaluations.right_sigma_eval,
-            preprocessed_circuit.permutation.right_sigma.commitment,
+            verifier_circuit_key.permutation.right_sigma,
         ));
         aggregate_proof.add_part((
             self.evaluations.out_sigma_eval,
-            preprocessed_circuit.permutation.out_sigma.commitment,
+            verifier_circuit_key.permutation.out_sigma,
+        ));
+        // The selector evaluations used in the linearisation commitment
+        // must be opened as well, or the prover could choose them freely
+        aggregate_proof.add_part((
+            self.evaluations.q_arith_eval,
+            verifier_circuit_key.arithmetic.q_arith,
+        ));
+        aggregate_proof.add_part((
+            self.evaluations.q_c_eval,
+            verifier_circuit_key.arithmetic.q_c,
+        ));
+        aggregate_proof.add_part((
+            self.evaluations.q_fixed_base_eval,
+            verifier_circuit_key.fixed_base.q_fixed_base,
         ));
-        // Flatten proof with opening challenge
-        let flattened_proof_a = aggregate_proof.flatten(transcript);
 
         // Compose the shifted aggregate proof
         let mut shifted_aggregate_proof = AggregateProof::with_witness(self.w_zw_comm);
         shifted_aggregate_proof.add_part((self.evaluations.perm_eval, self.z_comm));
         shifted_aggregate_proof.add_part((self.evaluations.a_next_eval, self.a_comm));
         shifted_aggregate_proof.add_part((self.evaluations.b_next_eval, self.b_comm));
+        shifted_aggregate_proof.add_part((self.evaluations.c_next_eval, self.c_comm));
         shifted_aggregate_proof.add_part((self.evaluations.d_next_eval, self.d_comm));
+
+        // The lookup polynomials are opened at both points
+        if let (Some((lookup_key, lookup)), Some(((zeta, _, _), _))) = (lookup, &lookup_challenges)
+        {
+            // Compute commitment to the compressed lookup table
+            let table_comm = lookup_key.compute_table_commitment(zeta);
+            let evaluations = &lookup.evaluations;
+
+            aggregate_proof.add_part((evaluations.f_eval, lookup.f_comm));
+            aggregate_proof.add_part((evaluations.h_1_eval, lookup.h_1_comm));
+            aggregate_proof.add_part((evaluations.table_eval, table_comm));
+
+            shifted_aggregate_proof.add_part((evaluations.lookup_perm_eval, lookup.z_2_comm));
+            shifted_aggregate_proof.add_part((evaluations.h_1_next_eval, lookup.h_1_comm));
+            shifted_aggregate_proof.add_part((evaluations.h_2_next_eval, lookup.h_2_comm));
+            shifted_aggregate_proof.add_part((evaluations.table_next_eval, table_comm));
+        }
+
+        // Flatten proofs with opening challenge
+        let flattened_proof_a = aggregate_proof.flatten(transcript);
         let flattened_proof_b = shifted_aggregate_proof.flatten(transcript);
 
         // Add commitment to openings to transcript
         transcript.append_commitment(b"w_z", &self.w_z_comm);
         transcript.append_commitment(b"w_z_w", &self.w_zw_comm);
 
-        // Batch check
-        verifier_key.batch_check(
-            &[z_challenge, (z_challenge * domain.group_gen)],
-            &[flattened_proof_a, flattened_proof_b],
-            transcript,
-        )
+        Ok((
+            [z_challenge, (z_challenge * domain.group_gen)],
+            [flattened_proof_a, flattened_proof_b],
+        ))
     }
 
     #[allow(clippy::too_many_arguments)]
     fn compute_quotient_evaluation(
         &self,
         domain: &EvaluationDomain,
+        pi_pos: &[usize],
         pub_inputs: &[Scalar],
         alpha: &Scalar,
         beta: &Scalar,
@@ -376,14 +883,15 @@ impl Proof {
         z_h_eval: &Scalar,
         l1_eval: &Scalar,
         z_hat_eval: &Scalar,
+        lookup_constant: Scalar,
     ) -> Scalar {
         // Compute the public input polynomial evaluated at `z_challenge`
-        let pi_eval = compute_barycentric_eval(pub_inputs, z_challenge, domain);
+        let pi_eval = compute_barycentric_eval(pi_pos, pub_inputs, z_challenge, domain);
 
         let alpha_sq = alpha.square();
 
-        // r + PI(z)
-        let a = self.evaluations.lin_poly_eval + pi_eval;
+        // r + PI(z) + the constant term of the lookup identity
+        let a = self.evaluations.lin_poly_eval + pi_eval + lookup_constant;
 
         // a + beta * sigma_1 + gamma
         let beta_sig1 = beta * self.evaluations.left_sigma_eval;
@@ -409,6 +917,8 @@ impl Proof {
         (a - b - c) * z_h_eval.invert().unwrap()
     }
 
+    // The blinding scalars that the prover adds when splitting `t(X)`
+    // cancel out in this combination.
     fn compute_quotient_commitment(&self, z_challenge: &Scalar, n: usize) -> Commitment {
         let z_n = z_challenge.pow(&[n as u64, 0, 0, 0]);
         let z_two_n = z_challenge.pow(&[2 * n as u64, 0, 0, 0]);
@@ -421,35 +931,88 @@ impl Proof {
     }
 
     // Commitment to [r]_1
+    #[allow(clippy::too_many_arguments)]
     fn compute_linearisation_commitment(
         &self,
         alpha: &Scalar,
         beta: &Scalar,
         gamma: &Scalar,
+        ecc_separation_challenge: &Scalar,
+        fixed_base_separation_challenge: &Scalar,
+        lookup: Option<((&LookupVerifierKey, &LookupProof), &LookupChallenges)>,
         z_challenge: &Scalar,
-        l1_eval: Scalar,
-        preprocessed_circuit: &PreProcessedCircuit,
+        (l1_eval, ln_eval): (Scalar, Scalar),
+        (widgets, custom_separation_challenges): (&[&dyn Widget], &[Scalar]),
+        verifier_circuit_key: &VerifierCircuitKey,
     ) -> Commitment {
         let mut scalars: Vec<_> = Vec::with_capacity(6);
         let mut points: Vec<G1Affine> = Vec::with_capacity(6);
 
-        preprocessed_circuit
+        verifier_circuit_key
             .arithmetic
             .compute_linearisation_commitment(&mut scalars, &mut points, &self.evaluations);
 
-        preprocessed_circuit.range.compute_linearisation_commitment(
+        verifier_circuit_key.range.compute_linearisation_commitment(
             &mut scalars,
             &mut points,
             &self.evaluations,
         );
 
-        preprocessed_circuit.logic.compute_linearisation_commitment(
+        verifier_circuit_key.logic.compute_linearisation_commitment(
             &mut scalars,
             &mut points,
             &self.evaluations,
         );
 
-        preprocessed_circuit
+        verifier_circuit_key
+            .ecc_add
+            .compute_linearisation_commitment(
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+                ecc_separation_challenge,
+            );
+
+        verifier_circuit_key
+            .fixed_base
+            .compute_linearisation_commitment(
+                &verifier_circuit_key.arithmetic,
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+                fixed_base_separation_challenge,
+            );
+
+        if let Some(((lookup_key, lookup), ((zeta, delta, epsilon), separation_challenge))) = lookup
+        {
+            lookup_key.compute_linearisation_commitment(
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+                &lookup.evaluations,
+                (zeta, delta, epsilon),
+                separation_challenge,
+                (z_challenge, &verifier_circuit_key.domain.group_gen_inv),
+                (&l1_eval, &ln_eval),
+                (lookup.h_2_comm.0, lookup.z_2_comm.0),
+            );
+        }
+
+        for (custom, (widget, challenge)) in verifier_circuit_key
+            .custom
+            .iter()
+            .zip(widgets.iter().zip(custom_separation_challenges.iter()))
+        {
+            custom.compute_linearisation_commitment(
+                *widget,
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+                challenge,
+            );
+        }
+
+        verifier_circuit_key
             .permutation
             .compute_linearisation_commitment(
                 &mut scalars,
@@ -474,47 +1037,101 @@ fn compute_first_lagrange_evaluation(
     let denom = n_fr * (z_challenge - Scalar::one());
     z_h_eval * denom.invert().unwrap()
 }
-#[warn(clippy::needless_range_loop)]
+
+// The last element of the domain is `g^{n-1} = g^{-1}`, so
+// L_n(z) = g^{-1} * Z_H(z) / (n * (z - g^{-1}))
+fn compute_last_lagrange_evaluation(
+    domain: &EvaluationDomain,
+    z_h_eval: &Scalar,
+    z_challenge: &Scalar,
+) -> Scalar {
+    let n_fr = Scalar::from(domain.size() as u64);
+    let denom = n_fr * (z_challenge - domain.group_gen_inv);
+    domain.group_gen_inv * z_h_eval * denom.invert().unwrap()
+}
+// The challenges `(zeta, delta, epsilon)` of the lookup argument, followed by
+// its separation challenge.
+type LookupChallenges = ((Scalar, Scalar, Scalar), Scalar);
+
+// Length of a compressed `G1Affine` point.
+const COMMITMENT_SIZE: usize = 48;
+// Length of a `Scalar`.
+const SCALAR_SIZE: usize = 32;
+
+// Decodes a compressed point, checking that it lies on the curve and in the
+// prime order subgroup.
+fn read_commitment(bytes: &[u8]) -> Result<Commitment, Error> {
+    let mut compressed = [0u8; COMMITMENT_SIZE];
+    compressed.copy_from_slice(bytes);
+
+    let point = G1Affine::from_compressed_unchecked(&compressed);
+    if bool::from(point.is_none()) {
+        return Err(Error::PointNotOnCurve);
+    }
+    let point = point.unwrap();
+    if !bool::from(point.is_torsion_free()) {
+        return Err(Error::PointNotInSubgroup);
+    }
+
+    Ok(Commitment::from_affine(point))
+}
+
+// Decodes a `Scalar`, rejecting any value that is not reduced.
+fn read_scalar(bytes: &[u8]) -> Result<Scalar, Error> {
+    let mut repr = [0u8; SCALAR_SIZE];
+    repr.copy_from_slice(bytes);
+
+    let scalar = Scalar::from_bytes(&repr);
+    if bool::from(scalar.is_none()) {
+        return Err(Error::NonCanonicalScalar);
+    }
+
+    Ok(scalar.unwrap())
+}
+
+// Decodes `num_comms` commitments followed by as many scalars as fill the
+// rest of `bytes`.
+fn read_section(bytes: &[u8], num_comms: usize) -> Result<(Vec<Commitment>, Vec<Scalar>), Error> {
+    let (comm_bytes, eval_bytes) = bytes.split_at(num_comms * COMMITMENT_SIZE);
+    let comms = comm_bytes
+        .chunks(COMMITMENT_SIZE)
+        .map(read_commitment)
+        .collect::<Result<Vec<Commitment>, Error>>()?;
+    let evals = eval_bytes
+        .chunks(SCALAR_SIZE)
+        .map(read_scalar)
+        .collect::<Result<Vec<Scalar>, Error>>()?;
+
+    Ok((comms, evals))
+}
+
+/// Evaluates the public input polynomial at `point`, given only the values
+/// of the public inputs and the gates they sit at.
+///
+/// Since the polynomial is zero over every other element of the domain,
+/// only `pi_pos.len()` terms of the barycentric formula are computed.
 fn compute_barycentric_eval(
-    evaluations: &[Scalar],
+    pi_pos: &[usize],
+    pub_inputs: &[Scalar],
     point: &Scalar,
     domain: &EvaluationDomain,
 ) -> Scalar {
     use crate::util::batch_inversion;
-    use rayon::iter::IntoParallelIterator;
     use rayon::prelude::*;
 
     let numerator = (point.pow(&[domain.size() as u64, 0, 0, 0]) - Scalar::one()) * domain.size_inv;
 
-    // Indices with non-zero evaluations
-    let non_zero_evaluations: Vec<usize> = (0..evaluations.len())
-        .into_par_iter()
-        .filter(|&i| {
-            let evaluation = &evaluations[i];
-            evaluation != &Scalar::zero()
-        })
-        .collect();
-
-    // Only compute the denominators with non-zero evaluations
-    let mut denominators: Vec<Scalar> = (0..non_zero_evaluations.len())
-        .into_par_iter()
-        .map(|i| {
-            // index of non-zero evaluation
-            let index = non_zero_evaluations[i];
-
-            (domain.group_gen_inv.pow(&[index as u64, 0, 0, 0]) * point) - Scalar::one()
-        })
+    // Only compute the denominators of the gates holding a public input
+    let mut denominators: Vec<Scalar> = pi_pos
+        .par_iter()
+        .map(|index| (domain.group_gen_inv.pow(&[*index as u64, 0, 0, 0]) * point) - Scalar::one())
         .collect();
     batch_inversion(&mut denominators);
 
-    let result: Scalar = (0..non_zero_evaluations.len())
-        .into_par_iter()
-        .map(|i| {
-            let eval_index = non_zero_evaluations[i];
-            let eval = evaluations[eval_index];
-
-            denominators[i] * eval
-        })
+    let result: Scalar = denominators
+        .par_iter()
+        .zip(pub_inputs.par_iter())
+        .map(|(denominator, eval)| denominator * eval)
         .sum();
 
     result * numerator
@@ -540,15 +1157,26 @@ mod tests {
             d_eval: one,
             a_next_eval: one,
             b_next_eval: one,
+            c_next_eval: one,
             d_next_eval: one,
             q_arith_eval: one,
             q_c_eval: one,
+            q_fixed_base_eval: one,
             left_sigma_eval: one,
             right_sigma_eval: one,
             out_sigma_eval: one,
             lin_poly_eval: one,
             perm_eval: one,
         };
+        let lookup_evals = LookupEvaluations {
+            f_eval: one,
+            h_1_eval: one,
+            h_1_next_eval: one,
+            h_2_next_eval: one,
+            table_eval: one,
+            table_next_eval: one,
+            lookup_perm_eval: one,
+        };
 
         // Build directly the widget since there's not any `new()` impl
         // dor any other check and correctness methodology for the inputs.
@@ -565,11 +1193,150 @@ mod tests {
             w_z_comm: comm,
             w_zw_comm: comm,
             evaluations: proof_evals,
+            lookup: None,
         };
 
         // Roundtrip with evals
         let ser = bincode::serialize(&proof).unwrap();
         let deser: Proof = bincode::deserialize(&ser).unwrap();
         assert_eq!(proof, deser);
+
+        // Roundtrip with a lookup argument
+        let mut proof = proof;
+        proof.lookup = Some(LookupProof {
+            f_comm: comm,
+            h_1_comm: comm,
+            h_2_comm: comm,
+            z_2_comm: comm,
+            evaluations: lookup_evals,
+        });
+        let ser = bincode::serialize(&proof).unwrap();
+        let deser: Proof = bincode::deserialize(&ser).unwrap();
+        assert_eq!(proof, deser);
+    }
+
+    #[test]
+    fn test_sparse_barycentric_eval() {
+        use crate::fft::Polynomial;
+
+        let domain = EvaluationDomain::new(64).unwrap();
+        let pi_pos = vec![0, 5, 17, 63];
+        let pub_inputs = vec![
+            Scalar::from(7u64),
+            -Scalar::one(),
+            Scalar::from(1234u64),
+            Scalar::from(2u64),
+        ];
+
+        let mut dense_pi = vec![Scalar::zero(); domain.size()];
+        for (pos, value) in pi_pos.iter().zip(pub_inputs.iter()) {
+            dense_pi[*pos] = *value;
+        }
+        let pi_poly = Polynomial::from_coefficients_vec(domain.ifft(&dense_pi));
+
+        let point = Scalar::from(0xdead_beefu64);
+        assert_eq!(
+            compute_barycentric_eval(&pi_pos, &pub_inputs, &point, &domain),
+            pi_poly.evaluate(&point)
+        );
+    }
+
+    // Builds a proof with distinct commitments and evaluations.
+    fn dummy_proof() -> Proof {
+        let comms: Vec<Commitment> = (1..16u64)
+            .map(|i| Commitment::from_projective(G1Affine::generator() * Scalar::from(i)))
+            .collect();
+        let evals: Vec<Scalar> = (0..23u64).map(|i| -Scalar::from(i)).collect();
+
+        Proof {
+            a_comm: comms[0],
+            b_comm: comms[1],
+            c_comm: comms[2],
+            d_comm: comms[3],
+            z_comm: comms[4],
+            t_1_comm: comms[5],
+            t_2_comm: comms[6],
+            t_3_comm: comms[7],
+            t_4_comm: comms[8],
+            w_z_comm: comms[9],
+            w_zw_comm: comms[10],
+            evaluations: ProofEvaluations::from_slice(&evals[..16]),
+            lookup: Some(LookupProof {
+                f_comm: comms[11],
+                h_1_comm: comms[12],
+                h_2_comm: comms[13],
+                z_2_comm: comms[14],
+                evaluations: LookupEvaluations::from_slice(&evals[16..]),
+            }),
+        }
+    }
+
+    #[test]
+    fn proof_bytes_roundtrip() {
+        let proof = dummy_proof();
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), Proof::SIZE + Proof::LOOKUP_SIZE);
+        assert_eq!(Proof::from_bytes(&bytes).unwrap(), proof);
+
+        // Without a lookup argument
+        let mut proof = dummy_proof();
+        proof.lookup = None;
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), Proof::SIZE);
+        assert_eq!(Proof::from_bytes(&bytes).unwrap(), proof);
+
+        // The identity is a valid commitment
+        let mut proof = dummy_proof();
+        proof.z_comm = Commitment::empty();
+        assert_eq!(Proof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+    }
+
+    #[test]
+    fn proof_from_invalid_bytes() {
+        let bytes = dummy_proof().to_bytes();
+
+        // Wrong length
+        match Proof::from_bytes(&bytes[1..]) {
+            Err(Error::InvalidProofLength {
+                expected,
+                expected_with_lookup,
+            }) => {
+                assert_eq!(expected, Proof::SIZE);
+                assert_eq!(expected_with_lookup, Proof::SIZE + Proof::LOOKUP_SIZE);
+            }
+            _ => panic!("expected an invalid length error"),
+        }
+
+        // x = 1 is not the abscissa of any point on the curve
+        let mut off_curve = bytes.clone();
+        let mut x_one = [0u8; COMMITMENT_SIZE];
+        x_one[0] = 0x80;
+        x_one[COMMITMENT_SIZE - 1] = 1;
+        off_curve[..COMMITMENT_SIZE].copy_from_slice(&x_one);
+        match Proof::from_bytes(&off_curve) {
+            Err(Error::PointNotOnCurve) => {}
+            _ => panic!("expected a point not on curve error"),
+        }
+
+        // (0, 2) lies on the curve but not in the prime order subgroup
+        let mut low_order = bytes.clone();
+        let mut x_zero = [0u8; COMMITMENT_SIZE];
+        x_zero[0] = 0x80;
+        low_order[COMMITMENT_SIZE..2 * COMMITMENT_SIZE].copy_from_slice(&x_zero);
+        match Proof::from_bytes(&low_order) {
+            Err(Error::PointNotInSubgroup) => {}
+            _ => panic!("expected a point not in subgroup error"),
+        }
+
+        // The modulus itself is not a canonical scalar
+        let mut non_canonical = bytes;
+        let mut modulus = (-Scalar::one()).to_bytes();
+        modulus[0] += 1;
+        let len = non_canonical.len();
+        non_canonical[len - SCALAR_SIZE..].copy_from_slice(&modulus);
+        match Proof::from_bytes(&non_canonical) {
+            Err(Error::NonCanonicalScalar) => {}
+            _ => panic!("expected a non canonical scalar error"),
+        }
     }
 }
@@ -1,94 +1,119 @@
 use crate::fft::{EvaluationDomain, Polynomial};
 /// This quotient polynomial can only be used for the standard composer
 /// Each composer will need to implement their own method for computing the quotient polynomial
-use crate::proof_system::PreProcessedCircuit;
+use crate::proof_system::widget::lookup::LookupPolynomials;
+use crate::proof_system::widget::{LookupWidget, Widget, WireEvaluations};
+use crate::proof_system::ProverCircuitKey;
 use bls12_381::Scalar;
 use rayon::prelude::*;
 
 /// Computes the quotient polynomial
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn compute(
     domain: &EvaluationDomain,
-    preprocessed_circuit: &PreProcessedCircuit,
+    preprocessed_circuit: &ProverCircuitKey,
     z_poly: &Polynomial,
     (w_l_poly, w_r_poly, w_o_poly, w_4_poly): (&Polynomial, &Polynomial, &Polynomial, &Polynomial),
     public_inputs_poly: &Polynomial,
     (alpha, beta, gamma): &(Scalar, Scalar, Scalar),
+    ecc_separation_challenge: &Scalar,
+    fixed_base_separation_challenge: &Scalar,
+    lookup: Option<&LookupPolynomials>,
+    (widgets, custom_separation_challenges): (&[Box<dyn Widget>], &[Scalar]),
 ) -> Polynomial {
-    // Compute 4n eval of z(X)
-    let domain_4n = EvaluationDomain::new(4 * domain.size()).unwrap();
-    let mut z_eval_4n = domain_4n.coset_fft(&z_poly);
-    z_eval_4n.push(z_eval_4n[0]);
-    z_eval_4n.push(z_eval_4n[1]);
-    z_eval_4n.push(z_eval_4n[2]);
-    z_eval_4n.push(z_eval_4n[3]);
-
-    // Compute 4n evaluations of the wire polynomials
-    let mut wl_eval_4n = domain_4n.coset_fft(&w_l_poly);
-    wl_eval_4n.push(wl_eval_4n[0]);
-    wl_eval_4n.push(wl_eval_4n[1]);
-    wl_eval_4n.push(wl_eval_4n[2]);
-    wl_eval_4n.push(wl_eval_4n[3]);
-    let mut wr_eval_4n = domain_4n.coset_fft(&w_r_poly);
-    wr_eval_4n.push(wr_eval_4n[0]);
-    wr_eval_4n.push(wr_eval_4n[1]);
-    wr_eval_4n.push(wr_eval_4n[2]);
-    wr_eval_4n.push(wr_eval_4n[3]);
-    let wo_eval_4n = domain_4n.coset_fft(&w_o_poly);
-    let mut w4_eval_4n = domain_4n.coset_fft(&w_4_poly);
-    w4_eval_4n.push(w4_eval_4n[0]);
-    w4_eval_4n.push(w4_eval_4n[1]);
-    w4_eval_4n.push(w4_eval_4n[2]);
-    w4_eval_4n.push(w4_eval_4n[3]);
+    // Compute 8n eval of z(X)
+    //
+    // On the 8n coset, the evaluation of `p(X * omega)` at index `i` is the
+    // evaluation of `p(X)` at index `i + 8`, so we wrap the first 8 entries
+    // around for every polynomial that is queried at the next row.
+    let domain_8n = EvaluationDomain::new(8 * domain.size()).unwrap();
+    let mut z_eval_8n = domain_8n.coset_fft(&z_poly);
+    z_eval_8n.extend_from_slice(&z_eval_8n[0..8].to_vec());
+
+    // Compute 8n evaluations of the wire polynomials
+    let mut wl_eval_8n = domain_8n.coset_fft(&w_l_poly);
+    wl_eval_8n.extend_from_slice(&wl_eval_8n[0..8].to_vec());
+    let mut wr_eval_8n = domain_8n.coset_fft(&w_r_poly);
+    wr_eval_8n.extend_from_slice(&wr_eval_8n[0..8].to_vec());
+    let mut wo_eval_8n = domain_8n.coset_fft(&w_o_poly);
+    wo_eval_8n.extend_from_slice(&wo_eval_8n[0..8].to_vec());
+    let mut w4_eval_8n = domain_8n.coset_fft(&w_4_poly);
+    w4_eval_8n.extend_from_slice(&w4_eval_8n[0..8].to_vec());
 
     let t_1 = compute_circuit_satisfiability_equation(
         domain,
         preprocessed_circuit,
-        (&wl_eval_4n, &wr_eval_4n, &wo_eval_4n, &w4_eval_4n),
+        (&wl_eval_8n, &wr_eval_8n, &wo_eval_8n, &w4_eval_8n),
         public_inputs_poly,
+        ecc_separation_challenge,
+        fixed_base_separation_challenge,
     );
 
     let t_2 = compute_permutation_checks(
         domain,
         preprocessed_circuit,
-        (&wl_eval_4n, &wr_eval_4n, &wo_eval_4n, &w4_eval_4n),
-        &z_eval_4n,
+        (&wl_eval_8n, &wr_eval_8n, &wo_eval_8n, &w4_eval_8n),
+        &z_eval_8n,
         (alpha, beta, gamma),
     );
 
-    let quotient: Vec<_> = (0..domain_4n.size())
+    // The lookup argument only exists for circuits with lookup tables
+    let t_3 = match (&preprocessed_circuit.lookup, lookup) {
+        (Some(lookup_widget), Some(lookup)) => compute_lookup_checks(
+            domain,
+            preprocessed_circuit,
+            lookup_widget,
+            (&wl_eval_8n, &wr_eval_8n, &wo_eval_8n),
+            lookup,
+        ),
+        _ => vec![Scalar::zero(); domain_8n.size()],
+    };
+
+    let t_4 = compute_custom_checks(
+        domain,
+        preprocessed_circuit,
+        (&wl_eval_8n, &wr_eval_8n, &wo_eval_8n, &w4_eval_8n),
+        widgets,
+        custom_separation_challenges,
+    );
+
+    let quotient: Vec<_> = (0..domain_8n.size())
         .into_par_iter()
         .map(|i| {
-            let numerator = t_1[i] + t_2[i];
-            let denominator = preprocessed_circuit.v_h_coset_4n()[i];
+            let numerator = t_1[i] + t_2[i] + t_3[i] + t_4[i];
+            let denominator = preprocessed_circuit.v_h_coset_8n()[i];
             numerator * denominator.invert().unwrap()
         })
         .collect();
 
-    Polynomial::from_coefficients_vec(domain_4n.coset_ifft(&quotient))
+    Polynomial::from_coefficients_vec(domain_8n.coset_ifft(&quotient))
 }
 
 // Ensures that the circuit is satisfied
 fn compute_circuit_satisfiability_equation(
     domain: &EvaluationDomain,
-    preprocessed_circuit: &PreProcessedCircuit,
-    (wl_eval_4n, wr_eval_4n, wo_eval_4n, w4_eval_4n): (&[Scalar], &[Scalar], &[Scalar], &[Scalar]),
+    preprocessed_circuit: &ProverCircuitKey,
+    (wl_eval_8n, wr_eval_8n, wo_eval_8n, w4_eval_8n): (&[Scalar], &[Scalar], &[Scalar], &[Scalar]),
     pi_poly: &Polynomial,
+    ecc_separation_challenge: &Scalar,
+    fixed_base_separation_challenge: &Scalar,
 ) -> Vec<Scalar> {
-    let domain_4n = EvaluationDomain::new(4 * domain.size()).unwrap();
+    let domain_8n = EvaluationDomain::new(8 * domain.size()).unwrap();
 
-    let pi_eval_4n = domain_4n.coset_fft(pi_poly);
+    let pi_eval_8n = domain_8n.coset_fft(pi_poly);
 
-    let t: Vec<_> = (0..domain_4n.size())
+    let t: Vec<_> = (0..domain_8n.size())
         .into_par_iter()
         .map(|i| {
-            let wl = &wl_eval_4n[i];
-            let wr = &wr_eval_4n[i];
-            let wo = &wo_eval_4n[i];
-            let w4 = &w4_eval_4n[i];
-            let wl_next = &wl_eval_4n[i + 4];
-            let wr_next = &wr_eval_4n[i + 4];
-            let w4_next = &w4_eval_4n[i + 4];
-            let pi = &pi_eval_4n[i];
+            let wl = &wl_eval_8n[i];
+            let wr = &wr_eval_8n[i];
+            let wo = &wo_eval_8n[i];
+            let w4 = &w4_eval_8n[i];
+            let wl_next = &wl_eval_8n[i + 8];
+            let wr_next = &wr_eval_8n[i + 8];
+            let wo_next = &wo_eval_8n[i + 8];
+            let w4_next = &w4_eval_8n[i + 8];
+            let pi = &pi_eval_8n[i];
 
             let a = preprocessed_circuit
                 .arithmetic
@@ -102,7 +127,33 @@ fn compute_circuit_satisfiability_equation(
                 .logic
                 .compute_quotient_i(i, &wl, &wl_next, &wr, &wr_next, &wo, &w4, &w4_next);
 
-            a + b + c + pi
+            let d = preprocessed_circuit.ecc_add.compute_quotient_i(
+                i,
+                wl,
+                wl_next,
+                wr,
+                wr_next,
+                wo,
+                wo_next,
+                w4,
+                w4_next,
+                ecc_separation_challenge,
+            );
+
+            let e = preprocessed_circuit.fixed_base.compute_quotient_i(
+                &preprocessed_circuit.arithmetic,
+                i,
+                wl,
+                wl_next,
+                wr,
+                wr_next,
+                wo,
+                w4,
+                w4_next,
+                fixed_base_separation_challenge,
+            );
+
+            a + b + c + d + e + pi
         })
         .collect();
     t
@@ -110,27 +161,27 @@ fn compute_circuit_satisfiability_equation(
 
 fn compute_permutation_checks(
     domain: &EvaluationDomain,
-    preprocessed_circuit: &PreProcessedCircuit,
-    (wl_eval_4n, wr_eval_4n, wo_eval_4n, w4_eval_4n): (&[Scalar], &[Scalar], &[Scalar], &[Scalar]),
-    z_eval_4n: &[Scalar],
+    preprocessed_circuit: &ProverCircuitKey,
+    (wl_eval_8n, wr_eval_8n, wo_eval_8n, w4_eval_8n): (&[Scalar], &[Scalar], &[Scalar], &[Scalar]),
+    z_eval_8n: &[Scalar],
     (alpha, beta, gamma): (&Scalar, &Scalar, &Scalar),
 ) -> Vec<Scalar> {
-    let domain_4n = EvaluationDomain::new(4 * domain.size()).unwrap();
+    let domain_8n = EvaluationDomain::new(8 * domain.size()).unwrap();
 
     let l1_poly_alpha = compute_first_lagrange_poly_scaled(domain, alpha.square());
-    let l1_alpha_sq_evals = domain_4n.coset_fft(&l1_poly_alpha.coeffs);
+    let l1_alpha_sq_evals = domain_8n.coset_fft(&l1_poly_alpha.coeffs);
 
-    let t: Vec<_> = (0..domain_4n.size())
+    let t: Vec<_> = (0..domain_8n.size())
         .into_par_iter()
         .map(|i| {
             preprocessed_circuit.permutation.compute_quotient_i(
                 i,
-                &wl_eval_4n[i],
-                &wr_eval_4n[i],
-                &wo_eval_4n[i],
-                &w4_eval_4n[i],
-                &z_eval_4n[i],
-                &z_eval_4n[i + 4],
+                &wl_eval_8n[i],
+                &wr_eval_8n[i],
+                &wo_eval_8n[i],
+                &w4_eval_8n[i],
+                &z_eval_8n[i],
+                &z_eval_8n[i + 8],
                 &alpha,
                 &l1_alpha_sq_evals[i],
                 &beta,
@@ -140,9 +191,109 @@ fn compute_permutation_checks(
         .collect();
     t
 }
+// Ensures that the lookup queries are rows of the lookup tables
+fn compute_lookup_checks(
+    domain: &EvaluationDomain,
+    preprocessed_circuit: &ProverCircuitKey,
+    lookup_widget: &LookupWidget,
+    (wl_eval_8n, wr_eval_8n, wo_eval_8n): (&[Scalar], &[Scalar], &[Scalar]),
+    lookup: &LookupPolynomials,
+) -> Vec<Scalar> {
+    let domain_8n = EvaluationDomain::new(8 * domain.size()).unwrap();
+    let (zeta, delta, epsilon) = &lookup.challenges;
+
+    // Compute 8n evaluations of the lookup polynomials
+    let f_eval_8n = domain_8n.coset_fft(&lookup.f_poly);
+    let mut h1_eval_8n = domain_8n.coset_fft(&lookup.h_1_poly);
+    h1_eval_8n.extend_from_slice(&h1_eval_8n[0..8].to_vec());
+    let mut h2_eval_8n = domain_8n.coset_fft(&lookup.h_2_poly);
+    h2_eval_8n.extend_from_slice(&h2_eval_8n[0..8].to_vec());
+    let mut z2_eval_8n = domain_8n.coset_fft(&lookup.z_2_poly);
+    z2_eval_8n.extend_from_slice(&z2_eval_8n[0..8].to_vec());
+
+    // The accumulator is checked at the first and the last rows of the domain
+    let l1_evals =
+        domain_8n.coset_fft(&compute_lagrange_poly_scaled(domain, 0, Scalar::one()).coeffs);
+    let ln_evals = domain_8n
+        .coset_fft(&compute_lagrange_poly_scaled(domain, domain.size() - 1, Scalar::one()).coeffs);
+    let last_element = domain.group_gen_inv;
+
+    let t: Vec<_> = (0..domain_8n.size())
+        .into_par_iter()
+        .map(|i| {
+            lookup_widget.compute_quotient_i(
+                i,
+                &wl_eval_8n[i],
+                &wr_eval_8n[i],
+                &wo_eval_8n[i],
+                (
+                    &f_eval_8n[i],
+                    &h1_eval_8n[i],
+                    &h1_eval_8n[i + 8],
+                    &h2_eval_8n[i],
+                    &h2_eval_8n[i + 8],
+                ),
+                (&z2_eval_8n[i], &z2_eval_8n[i + 8]),
+                (
+                    &preprocessed_circuit.permutation.linear_evaluations[i],
+                    &last_element,
+                ),
+                (&l1_evals[i], &ln_evals[i]),
+                (zeta, delta, epsilon),
+                &lookup.separation_challenge,
+            )
+        })
+        .collect();
+    t
+}
+
+// Ensures that the identities of the custom widgets hold
+fn compute_custom_checks(
+    domain: &EvaluationDomain,
+    preprocessed_circuit: &ProverCircuitKey,
+    (wl_eval_8n, wr_eval_8n, wo_eval_8n, w4_eval_8n): (&[Scalar], &[Scalar], &[Scalar], &[Scalar]),
+    widgets: &[Box<dyn Widget>],
+    separation_challenges: &[Scalar],
+) -> Vec<Scalar> {
+    let domain_8n = EvaluationDomain::new(8 * domain.size()).unwrap();
+
+    let t: Vec<_> = (0..domain_8n.size())
+        .into_par_iter()
+        .map(|i| {
+            let wires = WireEvaluations {
+                a: wl_eval_8n[i],
+                a_next: wl_eval_8n[i + 8],
+                b: wr_eval_8n[i],
+                b_next: wr_eval_8n[i + 8],
+                c: wo_eval_8n[i],
+                d: w4_eval_8n[i],
+                d_next: w4_eval_8n[i + 8],
+            };
+            preprocessed_circuit
+                .custom
+                .iter()
+                .zip(widgets.iter().zip(separation_challenges.iter()))
+                .fold(Scalar::zero(), |acc, (custom, (widget, challenge))| {
+                    acc + custom.compute_quotient_i(widget.as_ref(), i, &wires, challenge)
+                })
+        })
+        .collect();
+    t
+}
+
 fn compute_first_lagrange_poly_scaled(domain: &EvaluationDomain, scale: Scalar) -> Polynomial {
+    compute_lagrange_poly_scaled(domain, 0, scale)
+}
+
+// Computes the Lagrange polynomial which is `scale` at the given row of the
+// domain and zero at every other row
+fn compute_lagrange_poly_scaled(
+    domain: &EvaluationDomain,
+    index: usize,
+    scale: Scalar,
+) -> Polynomial {
     let mut x_evals = vec![Scalar::zero(); domain.size()];
-    x_evals[0] = scale;
+    x_evals[index] = scale;
     domain.ifft_in_place(&mut x_evals);
     Polynomial::from_coefficients_vec(x_evals)
 }
@@ -140,6 +140,176 @@ impl<'de> Deserialize<'de> for ArithmeticWidget {
     }
 }
 
+/// Commitments to the selector polynomials of the `ArithmeticWidget`.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct ArithmeticVerifierKey {
+    pub q_m: Commitment,
+    pub q_l: Commitment,
+    pub q_r: Commitment,
+    pub q_o: Commitment,
+    pub q_c: Commitment,
+    pub q_4: Commitment,
+    pub q_arith: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ArithmeticVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut arithmetic_verifier_key =
+            serializer.serialize_struct("struct ArithmeticVerifierKey", 7)?;
+        arithmetic_verifier_key.serialize_field("q_m", &self.q_m)?;
+        arithmetic_verifier_key.serialize_field("q_l", &self.q_l)?;
+        arithmetic_verifier_key.serialize_field("q_r", &self.q_r)?;
+        arithmetic_verifier_key.serialize_field("q_o", &self.q_o)?;
+        arithmetic_verifier_key.serialize_field("q_c", &self.q_c)?;
+        arithmetic_verifier_key.serialize_field("q_4", &self.q_4)?;
+        arithmetic_verifier_key.serialize_field("q_arith", &self.q_arith)?;
+        arithmetic_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ArithmeticVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Qm,
+            Ql,
+            Qr,
+            Qo,
+            Qc,
+            Q4,
+            Qarith,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct ArithmeticVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_m" => Ok(Field::Qm),
+                            "q_l" => Ok(Field::Ql),
+                            "q_r" => Ok(Field::Qr),
+                            "q_o" => Ok(Field::Qo),
+                            "q_c" => Ok(Field::Qc),
+                            "q_4" => Ok(Field::Q4),
+                            "q_arith" => Ok(Field::Qarith),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct ArithmeticVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for ArithmeticVerifierKeyVisitor {
+            type Value = ArithmeticVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct ArithmeticVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<ArithmeticVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_m = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let q_l = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                let q_r = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
+                let q_o = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(3, &self))?;
+                let q_c = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(4, &self))?;
+                let q_4 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(5, &self))?;
+                let q_arith = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(6, &self))?;
+                Ok(ArithmeticVerifierKey {
+                    q_m,
+                    q_l,
+                    q_r,
+                    q_o,
+                    q_c,
+                    q_4,
+                    q_arith,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_m", "q_l", "q_r", "q_o", "q_c", "q_4", "q_arith"];
+        deserializer.deserialize_struct(
+            "ArithmeticVerifierKey",
+            FIELDS,
+            ArithmeticVerifierKeyVisitor,
+        )
+    }
+}
+
+impl ArithmeticVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+    ) {
+        let q_arith_eval = evaluations.q_arith_eval;
+        scalars.push(evaluations.a_eval * evaluations.b_eval * q_arith_eval);
+        points.push(self.q_m.0);
+
+        scalars.push(evaluations.a_eval * q_arith_eval);
+        points.push(self.q_l.0);
+
+        scalars.push(evaluations.b_eval * q_arith_eval);
+        points.push(self.q_r.0);
+
+        scalars.push(evaluations.c_eval * q_arith_eval);
+        points.push(self.q_o.0);
+
+        scalars.push(evaluations.d_eval * q_arith_eval);
+        points.push(self.q_4.0);
+
+        scalars.push(q_arith_eval);
+        points.push(self.q_c.0);
+    }
+}
+
 impl ArithmeticWidget {
     #[allow(clippy::type_complexity)]
     pub(crate) fn new(
@@ -163,6 +333,20 @@ impl ArithmeticWidget {
             q_arith: PreProcessedPolynomial::new(selectors.6),
         }
     }
+
+    /// Returns the commitments that the verifier needs to check the
+    /// arithmetic gates of this widget.
+    pub(crate) fn verifier_key(&self) -> ArithmeticVerifierKey {
+        ArithmeticVerifierKey {
+            q_m: self.q_m.commitment,
+            q_l: self.q_l.commitment,
+            q_r: self.q_r.commitment,
+            q_o: self.q_o.commitment,
+            q_c: self.q_c.commitment,
+            q_4: self.q_4.commitment,
+            q_arith: self.q_arith.commitment,
+        }
+    }
     pub(crate) fn compute_quotient_i(
         &self,
         index: usize,
@@ -232,32 +416,6 @@ impl ArithmeticWidget {
 
         a
     }
-
-    pub(crate) fn compute_linearisation_commitment(
-        &self,
-        scalars: &mut Vec<Scalar>,
-        points: &mut Vec<G1Affine>,
-        evaluations: &ProofEvaluations,
-    ) {
-        let q_arith_eval = evaluations.q_arith_eval;
-        scalars.push(evaluations.a_eval * evaluations.b_eval * q_arith_eval);
-        points.push(self.q_m.commitment.0);
-
-        scalars.push(evaluations.a_eval * q_arith_eval);
-        points.push(self.q_l.commitment.0);
-
-        scalars.push(evaluations.b_eval * q_arith_eval);
-        points.push(self.q_r.commitment.0);
-
-        scalars.push(evaluations.c_eval * q_arith_eval);
-        points.push(self.q_o.commitment.0);
-
-        scalars.push(evaluations.d_eval * q_arith_eval);
-        points.push(self.q_4.commitment.0);
-
-        scalars.push(q_arith_eval);
-        points.push(self.q_c.commitment.0);
-    }
 }
 
 #[cfg(test)]
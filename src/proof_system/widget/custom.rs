@@ -0,0 +1,426 @@
+//! Custom gates defined outside of this crate.
+//!
+//! A custom gate is described by a type implementing `Widget`, which gives
+//! the identity of the gate in terms of its own selector polynomials and of
+//! the wire values at the current and the next rows. It is registered with
+//! `StandardComposer::add_widget`, which allocates its selectors, and the same
+//! widgets must be handed to `Proof::verify_with_widgets` or
+//! `Proof::batch_verify`, or returned by `Circuit::widgets`. The verifier
+//! checks them against the names stored in the circuit key.
+//!
+//! The identity of every custom gate is scaled by its own separation
+//! challenge, so widgets never need to take care of it.
+
+use super::PreProcessedPolynomial;
+use crate::commitment_scheme::kzg10::Commitment;
+use crate::fft::{Evaluations, Polynomial};
+use crate::proof_system::linearisation_poly::ProofEvaluations;
+use bls12_381::{G1Affine, Scalar};
+#[cfg(feature = "serde")]
+use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Debug;
+
+/// Values of the wires that the identity of a custom gate can refer to.
+///
+/// When computing the quotient polynomial they are the evaluations at a row
+/// of the 8n coset, and when computing the linearisation polynomial they are
+/// the evaluations at `z` and `z * root of unity` held in the proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireEvaluations {
+    /// Value of the left wire.
+    pub a: Scalar,
+    /// Value of the left wire at the next row.
+    pub a_next: Scalar,
+    /// Value of the right wire.
+    pub b: Scalar,
+    /// Value of the right wire at the next row.
+    pub b_next: Scalar,
+    /// Value of the output wire.
+    pub c: Scalar,
+    /// Value of the fourth wire.
+    pub d: Scalar,
+    /// Value of the fourth wire at the next row.
+    pub d_next: Scalar,
+}
+
+impl WireEvaluations {
+    pub(crate) fn from_proof_evaluations(evaluations: &ProofEvaluations) -> WireEvaluations {
+        WireEvaluations {
+            a: evaluations.a_eval,
+            a_next: evaluations.a_next_eval,
+            b: evaluations.b_eval,
+            b_next: evaluations.b_next_eval,
+            c: evaluations.c_eval,
+            d: evaluations.d_eval,
+            d_next: evaluations.d_next_eval,
+        }
+    }
+}
+
+/// The identity of a custom gate.
+///
+/// The identity must be a linear combination of the selectors of the gate,
+/// whose coefficients only depend on the wires. This is what allows the
+/// verifier to check it against the commitments to the selectors, and it
+/// makes the identity hold on every row where all of the selectors are zero.
+///
+/// The three methods must compute that same combination: over the
+/// evaluations of the selectors, over their polynomials and over their
+/// commitments.
+pub trait Widget: Debug + Send + Sync {
+    /// Returns the name that identifies the gate. It is stored in the
+    /// circuit keys and checked against the widgets given to the verifier.
+    ///
+    /// The name must not change between builds, and widgets whose identity
+    /// depends on their fields must include them in it.
+    fn name(&self) -> &'static str;
+
+    /// Returns the number of selector polynomials of the gate.
+    fn num_selectors(&self) -> usize;
+
+    /// Evaluates the identity given the values of the selectors and the
+    /// wires at the same row.
+    fn compute_quotient_i(&self, selectors: &[Scalar], wires: &WireEvaluations) -> Scalar;
+
+    /// Combines the selector polynomials with the coefficients that the
+    /// wire evaluations give them.
+    fn compute_linearisation(
+        &self,
+        selectors: &[&Polynomial],
+        wires: &WireEvaluations,
+    ) -> Polynomial;
+
+    /// Pushes every selector commitment into `points` along with the
+    /// coefficient that the wire evaluations give it into `scalars`.
+    fn compute_linearisation_commitment(
+        &self,
+        selectors: &[Commitment],
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        wires: &WireEvaluations,
+    );
+}
+
+/// The selector polynomials of a custom gate.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CustomWidget {
+    /// The name of the `Widget` that the selectors belong to.
+    pub name: String,
+    pub selectors: Vec<PreProcessedPolynomial>,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CustomWidget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut custom_widget = serializer.serialize_struct("struct CustomWidget", 2)?;
+        custom_widget.serialize_field("name", &self.name)?;
+        custom_widget.serialize_field("selectors", &self.selectors)?;
+        custom_widget.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CustomWidget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Name,
+            Selectors,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct CustomWidget")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "name" => Ok(Field::Name),
+                            "selectors" => Ok(Field::Selectors),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct CustomWidgetVisitor;
+
+        impl<'de> Visitor<'de> for CustomWidgetVisitor {
+            type Value = CustomWidget;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct CustomWidget")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<CustomWidget, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let name = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let selectors = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(CustomWidget { name, selectors })
+            }
+        }
+
+        const FIELDS: &[&str] = &["name", "selectors"];
+        deserializer.deserialize_struct("CustomWidget", FIELDS, CustomWidgetVisitor)
+    }
+}
+
+/// Commitments to the selector polynomials of a custom gate.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct CustomVerifierKey {
+    /// The name of the `Widget` that the selectors belong to.
+    pub name: String,
+    pub selectors: Vec<Commitment>,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CustomVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut custom_verifier_key = serializer.serialize_struct("struct CustomVerifierKey", 2)?;
+        custom_verifier_key.serialize_field("name", &self.name)?;
+        custom_verifier_key.serialize_field("selectors", &self.selectors)?;
+        custom_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CustomVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Name,
+            Selectors,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct CustomVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "name" => Ok(Field::Name),
+                            "selectors" => Ok(Field::Selectors),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct CustomVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for CustomVerifierKeyVisitor {
+            type Value = CustomVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct CustomVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<CustomVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let name = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let selectors = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(CustomVerifierKey { name, selectors })
+            }
+        }
+
+        const FIELDS: &[&str] = &["name", "selectors"];
+        deserializer.deserialize_struct("CustomVerifierKey", FIELDS, CustomVerifierKeyVisitor)
+    }
+}
+
+impl CustomVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        widget: &dyn Widget,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+        separation_challenge: &Scalar,
+    ) {
+        let first = scalars.len();
+        widget.compute_linearisation_commitment(
+            &self.selectors,
+            scalars,
+            points,
+            &WireEvaluations::from_proof_evaluations(evaluations),
+        );
+        for scalar in scalars[first..].iter_mut() {
+            *scalar *= separation_challenge;
+        }
+    }
+}
+
+impl CustomWidget {
+    pub(crate) fn new(
+        name: &str,
+        selectors: Vec<(Polynomial, Commitment, Option<Evaluations>)>,
+    ) -> CustomWidget {
+        CustomWidget {
+            name: name.to_string(),
+            selectors: selectors
+                .into_iter()
+                .map(PreProcessedPolynomial::new)
+                .collect(),
+        }
+    }
+
+    /// Returns the commitments that the verifier needs to check the
+    /// gates of this widget.
+    pub(crate) fn verifier_key(&self) -> CustomVerifierKey {
+        CustomVerifierKey {
+            name: self.name.clone(),
+            selectors: self
+                .selectors
+                .iter()
+                .map(|selector| selector.commitment)
+                .collect(),
+        }
+    }
+
+    pub(crate) fn compute_quotient_i(
+        &self,
+        widget: &dyn Widget,
+        index: usize,
+        wires: &WireEvaluations,
+        separation_challenge: &Scalar,
+    ) -> Scalar {
+        let selectors: Vec<Scalar> = self
+            .selectors
+            .iter()
+            .map(|selector| selector.evaluations.as_ref().unwrap()[index])
+            .collect();
+        widget.compute_quotient_i(&selectors, wires) * separation_challenge
+    }
+
+    pub(crate) fn compute_linearisation(
+        &self,
+        widget: &dyn Widget,
+        evaluations: &ProofEvaluations,
+        separation_challenge: &Scalar,
+    ) -> Polynomial {
+        let selectors: Vec<&Polynomial> = self
+            .selectors
+            .iter()
+            .map(|selector| &selector.polynomial)
+            .collect();
+        &widget.compute_linearisation(
+            &selectors,
+            &WireEvaluations::from_proof_evaluations(evaluations),
+        ) * separation_challenge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::EvaluationDomain;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn custom_widget_serde_roundtrip() {
+        use bincode;
+        let coeffs = vec![
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+        ];
+        let dom = EvaluationDomain::new(coeffs.len()).unwrap();
+        let evals = Evaluations::from_vec_and_domain(coeffs.clone(), dom);
+        let poly = Polynomial::from_coefficients_vec(coeffs);
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let custom_widget = CustomWidget {
+            name: "custom".to_string(),
+            selectors: vec![
+                PreProcessedPolynomial {
+                    polynomial: poly.clone(),
+                    commitment: comm,
+                    evaluations: Some(evals),
+                },
+                PreProcessedPolynomial {
+                    polynomial: poly,
+                    commitment: comm,
+                    evaluations: None,
+                },
+            ],
+        };
+
+        // Roundtrip with evals
+        let ser = bincode::serialize(&custom_widget).unwrap();
+        let deser: CustomWidget = bincode::deserialize(&ser).unwrap();
+        assert_eq!(custom_widget, deser);
+
+        let verifier_key = custom_widget.verifier_key();
+        let ser = bincode::serialize(&verifier_key).unwrap();
+        let deser: CustomVerifierKey = bincode::deserialize(&ser).unwrap();
+        assert_eq!(verifier_key, deser);
+    }
+}
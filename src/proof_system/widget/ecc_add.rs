@@ -0,0 +1,374 @@
+#![allow(clippy::too_many_arguments)]
+use super::PreProcessedPolynomial;
+use crate::commitment_scheme::kzg10::Commitment;
+use crate::fft::{Evaluations, Polynomial};
+use crate::proof_system::linearisation_poly::ProofEvaluations;
+
+use bls12_381::{G1Affine, Scalar};
+use jubjub::EDWARDS_D;
+#[cfg(feature = "serde")]
+use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct EccAddWidget {
+    pub q_ecc_add: PreProcessedPolynomial,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EccAddWidget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ecc_add_widget = serializer.serialize_struct("struct EccAddWidget", 1)?;
+        ecc_add_widget.serialize_field("q_ecc_add", &self.q_ecc_add)?;
+        ecc_add_widget.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EccAddWidget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            QeccAdd,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct EccAddWidget")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_ecc_add" => Ok(Field::QeccAdd),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct EccAddWidgetVisitor;
+
+        impl<'de> Visitor<'de> for EccAddWidgetVisitor {
+            type Value = EccAddWidget;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct EccAddWidget")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<EccAddWidget, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_ecc_add = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(EccAddWidget { q_ecc_add })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_ecc_add"];
+        deserializer.deserialize_struct("EccAddWidget", FIELDS, EccAddWidgetVisitor)
+    }
+}
+
+/// Commitments to the selector polynomials of the `EccAddWidget`.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct EccAddVerifierKey {
+    pub q_ecc_add: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for EccAddVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut ecc_add_verifier_key =
+            serializer.serialize_struct("struct EccAddVerifierKey", 1)?;
+        ecc_add_verifier_key.serialize_field("q_ecc_add", &self.q_ecc_add)?;
+        ecc_add_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for EccAddVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            QeccAdd,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct EccAddVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_ecc_add" => Ok(Field::QeccAdd),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct EccAddVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for EccAddVerifierKeyVisitor {
+            type Value = EccAddVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct EccAddVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<EccAddVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_ecc_add = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(EccAddVerifierKey { q_ecc_add })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_ecc_add"];
+        deserializer.deserialize_struct("EccAddVerifierKey", FIELDS, EccAddVerifierKeyVisitor)
+    }
+}
+
+impl EccAddVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+        ecc_separation_challenge: &Scalar,
+    ) {
+        let identity = ecc_add_identity(
+            &evaluations.a_eval,
+            &evaluations.a_next_eval,
+            &evaluations.b_eval,
+            &evaluations.b_next_eval,
+            &evaluations.c_eval,
+            &evaluations.c_next_eval,
+            &evaluations.d_eval,
+            &evaluations.d_next_eval,
+            ecc_separation_challenge,
+        );
+
+        scalars.push(identity);
+        points.push(self.q_ecc_add.0);
+    }
+}
+
+impl EccAddWidget {
+    pub(crate) fn new(selector: (Polynomial, Commitment, Option<Evaluations>)) -> EccAddWidget {
+        EccAddWidget {
+            q_ecc_add: PreProcessedPolynomial::new(selector),
+        }
+    }
+
+    /// Returns the commitments that the verifier needs to check the
+    /// point addition gates of this widget.
+    pub(crate) fn verifier_key(&self) -> EccAddVerifierKey {
+        EccAddVerifierKey {
+            q_ecc_add: self.q_ecc_add.commitment,
+        }
+    }
+
+    pub(crate) fn compute_quotient_i(
+        &self,
+        index: usize,
+        w_l_i: &Scalar,
+        w_l_i_next: &Scalar,
+        w_r_i: &Scalar,
+        w_r_i_next: &Scalar,
+        w_o_i: &Scalar,
+        w_o_i_next: &Scalar,
+        w_4_i: &Scalar,
+        w_4_i_next: &Scalar,
+        ecc_separation_challenge: &Scalar,
+    ) -> Scalar {
+        let q_ecc_add_i = &self.q_ecc_add.evaluations.as_ref().unwrap()[index];
+
+        let identity = ecc_add_identity(
+            w_l_i,
+            w_l_i_next,
+            w_r_i,
+            w_r_i_next,
+            w_o_i,
+            w_o_i_next,
+            w_4_i,
+            w_4_i_next,
+            ecc_separation_challenge,
+        );
+        identity * q_ecc_add_i
+    }
+
+    pub(crate) fn compute_linearisation(
+        &self,
+        a_eval: &Scalar,
+        a_next_eval: &Scalar,
+        b_eval: &Scalar,
+        b_next_eval: &Scalar,
+        c_eval: &Scalar,
+        c_next_eval: &Scalar,
+        d_eval: &Scalar,
+        d_next_eval: &Scalar,
+        ecc_separation_challenge: &Scalar,
+    ) -> Polynomial {
+        let q_ecc_add_poly = &self.q_ecc_add.polynomial;
+
+        let identity = ecc_add_identity(
+            a_eval,
+            a_next_eval,
+            b_eval,
+            b_next_eval,
+            c_eval,
+            c_next_eval,
+            d_eval,
+            d_next_eval,
+            ecc_separation_challenge,
+        );
+        q_ecc_add_poly * &identity
+    }
+}
+
+// The addition of `(x_1, y_1)` and `(x_2, y_2)` on the twisted Edwards
+// curve `-x^2 + y^2 = 1 + d * x^2 * y^2` spans two gates:
+//
+// | a   | b   | c   | d                     |
+// | x_1 | y_1 | x_2 | y_2                   |
+// | x_3 | y_3 | s   | (1 - s) * x_1 * y_2   |
+//
+// where the bit `s` in the output wire of the second gate skips the addition,
+// so that `(x_3, y_3) = (x_1, y_1) + (1 - s) * (x_2, y_2)`. Writing
+// `x_1 * x_2 * y_1 * y_2 = d(Xg) * b(X) * c(X)`, we check
+// C_1 = (1 - c(Xg)) * a(X) * d(X) - d(Xg)
+// C_2 = a(Xg) * (1 + d * d(Xg) * b(X) * c(X))
+//       - (c(Xg) * a(X) + d(Xg) + (1 - c(Xg)) * b(X) * c(X))
+// C_3 = b(Xg) * (1 - d * d(Xg) * b(X) * c(X))
+//       - (c(Xg) * b(X) + (1 - c(Xg)) * (b(X) * d(X) + a(X) * c(X)))
+// C_4 = c(Xg)^2 - c(Xg)
+//
+// With `s = 0` this is the plain addition. The four of them are combined with
+// powers of the separation challenge.
+fn ecc_add_identity(
+    a: &Scalar,
+    a_next: &Scalar,
+    b: &Scalar,
+    b_next: &Scalar,
+    c: &Scalar,
+    c_next: &Scalar,
+    d: &Scalar,
+    d_next: &Scalar,
+    separation_challenge: &Scalar,
+) -> Scalar {
+    let kappa = separation_challenge;
+    let kappa_sq = kappa.square();
+    let kappa_cu = kappa_sq * kappa;
+
+    let skip = c_next;
+    let add = Scalar::one() - skip;
+
+    let x1_y2 = d_next;
+    let y1_x2 = b * c;
+    let x1_y1_x2_y2 = x1_y2 * y1_x2 * EDWARDS_D;
+
+    let c_1 = add * a * d - x1_y2;
+    let c_2 = a_next * (Scalar::one() + x1_y1_x2_y2) - (skip * a + x1_y2 + add * y1_x2);
+    let c_3 = b_next * (Scalar::one() - x1_y1_x2_y2) - (skip * b + add * (b * d + a * c));
+    let c_4 = skip.square() - skip;
+
+    (c_1 + c_2 * kappa + c_3 * kappa_sq + c_4 * kappa_cu) * kappa
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::EvaluationDomain;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ecc_add_widget_serde_roundtrip() {
+        use bincode;
+        let coeffs = vec![
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+        ];
+        let dom = EvaluationDomain::new(coeffs.len()).unwrap();
+        let evals = Evaluations::from_vec_and_domain(coeffs.clone(), dom);
+        let poly = Polynomial::from_coefficients_vec(coeffs);
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let prep_poly_w_evals = PreProcessedPolynomial {
+            polynomial: poly.clone(),
+            commitment: comm,
+            evaluations: Some(evals),
+        };
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let ecc_add_widget = EccAddWidget {
+            q_ecc_add: prep_poly_w_evals,
+        };
+
+        // Roundtrip with evals
+        let ser = bincode::serialize(&ecc_add_widget).unwrap();
+        let deser: EccAddWidget = bincode::deserialize(&ser).unwrap();
+        assert_eq!(ecc_add_widget, deser);
+    }
+}
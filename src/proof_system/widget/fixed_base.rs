@@ -0,0 +1,433 @@
+#![allow(clippy::too_many_arguments)]
+use super::{ArithmeticVerifierKey, ArithmeticWidget, PreProcessedPolynomial};
+use crate::commitment_scheme::kzg10::Commitment;
+use crate::fft::{Evaluations, Polynomial};
+use crate::proof_system::linearisation_poly::ProofEvaluations;
+
+use bls12_381::{G1Affine, Scalar};
+use jubjub::EDWARDS_D;
+#[cfg(feature = "serde")]
+use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The fixed base scalar multiplication gates keep a table of points in
+/// the arithmetic selectors, which are only read when `q_fixed_base` is set.
+/// The widget only holds `q_fixed_base`, and the table selectors are read
+/// from the `ArithmeticWidget`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FixedBaseWidget {
+    pub q_fixed_base: PreProcessedPolynomial,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FixedBaseWidget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut fixed_base_widget = serializer.serialize_struct("struct FixedBaseWidget", 1)?;
+        fixed_base_widget.serialize_field("q_fixed_base", &self.q_fixed_base)?;
+        fixed_base_widget.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FixedBaseWidget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            QfixedBase,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct FixedBaseWidget")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_fixed_base" => Ok(Field::QfixedBase),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct FixedBaseWidgetVisitor;
+
+        impl<'de> Visitor<'de> for FixedBaseWidgetVisitor {
+            type Value = FixedBaseWidget;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct FixedBaseWidget")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<FixedBaseWidget, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_fixed_base = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(FixedBaseWidget { q_fixed_base })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_fixed_base"];
+        deserializer.deserialize_struct("FixedBaseWidget", FIELDS, FixedBaseWidgetVisitor)
+    }
+}
+
+/// Commitment to the selector polynomial of the `FixedBaseWidget`.
+///
+/// This is the only part of the widget that the verifier needs. The
+/// commitments to the table selectors are the ones of the
+/// `ArithmeticVerifierKey`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct FixedBaseVerifierKey {
+    pub q_fixed_base: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for FixedBaseVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut fixed_base_verifier_key =
+            serializer.serialize_struct("struct FixedBaseVerifierKey", 1)?;
+        fixed_base_verifier_key.serialize_field("q_fixed_base", &self.q_fixed_base)?;
+        fixed_base_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for FixedBaseVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            QfixedBase,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct FixedBaseVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_fixed_base" => Ok(Field::QfixedBase),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct FixedBaseVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for FixedBaseVerifierKeyVisitor {
+            type Value = FixedBaseVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct FixedBaseVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<FixedBaseVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_fixed_base = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(FixedBaseVerifierKey { q_fixed_base })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_fixed_base"];
+        deserializer.deserialize_struct("FixedBaseVerifierKey", FIELDS, FixedBaseVerifierKeyVisitor)
+    }
+}
+
+impl FixedBaseVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        arithmetic: &ArithmeticVerifierKey,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+        fixed_base_separation_challenge: &Scalar,
+    ) {
+        let (constant, coefficients) = fixed_base_terms(
+            &evaluations.a_eval,
+            &evaluations.a_next_eval,
+            &evaluations.b_eval,
+            &evaluations.b_next_eval,
+            &evaluations.c_eval,
+            &evaluations.d_eval,
+            &evaluations.d_next_eval,
+            fixed_base_separation_challenge,
+        );
+
+        scalars.push(constant);
+        points.push(self.q_fixed_base.0);
+
+        let selectors = [
+            arithmetic.q_m,
+            arithmetic.q_l,
+            arithmetic.q_r,
+            arithmetic.q_o,
+            arithmetic.q_c,
+            arithmetic.q_4,
+        ];
+        for (coefficient, selector) in coefficients.iter().zip(selectors.iter()) {
+            scalars.push(coefficient * evaluations.q_fixed_base_eval);
+            points.push(selector.0);
+        }
+    }
+}
+
+impl FixedBaseWidget {
+    pub(crate) fn new(
+        q_fixed_base: (Polynomial, Commitment, Option<Evaluations>),
+    ) -> FixedBaseWidget {
+        FixedBaseWidget {
+            q_fixed_base: PreProcessedPolynomial::new(q_fixed_base),
+        }
+    }
+
+    /// Returns the commitment that the verifier needs to check the
+    /// fixed base scalar multiplication gates of this widget.
+    pub(crate) fn verifier_key(&self) -> FixedBaseVerifierKey {
+        FixedBaseVerifierKey {
+            q_fixed_base: self.q_fixed_base.commitment,
+        }
+    }
+
+    pub(crate) fn compute_quotient_i(
+        &self,
+        arithmetic: &ArithmeticWidget,
+        index: usize,
+        w_l_i: &Scalar,
+        w_l_i_next: &Scalar,
+        w_r_i: &Scalar,
+        w_r_i_next: &Scalar,
+        w_o_i: &Scalar,
+        w_4_i: &Scalar,
+        w_4_i_next: &Scalar,
+        fixed_base_separation_challenge: &Scalar,
+    ) -> Scalar {
+        let q_fixed_base_i = &self.q_fixed_base.evaluations.as_ref().unwrap()[index];
+
+        let (constant, coefficients) = fixed_base_terms(
+            w_l_i,
+            w_l_i_next,
+            w_r_i,
+            w_r_i_next,
+            w_o_i,
+            w_4_i,
+            w_4_i_next,
+            fixed_base_separation_challenge,
+        );
+        let identity = table_selectors(arithmetic)
+            .iter()
+            .zip(coefficients.iter())
+            .fold(constant, |acc, (selector, coefficient)| {
+                acc + selector.evaluations.as_ref().unwrap()[index] * coefficient
+            });
+        identity * q_fixed_base_i
+    }
+
+    pub(crate) fn compute_linearisation(
+        &self,
+        arithmetic: &ArithmeticWidget,
+        a_eval: &Scalar,
+        a_next_eval: &Scalar,
+        b_eval: &Scalar,
+        b_next_eval: &Scalar,
+        c_eval: &Scalar,
+        d_eval: &Scalar,
+        d_next_eval: &Scalar,
+        q_fixed_base_eval: &Scalar,
+        fixed_base_separation_challenge: &Scalar,
+    ) -> Polynomial {
+        let q_fixed_base_poly = &self.q_fixed_base.polynomial;
+
+        let (constant, coefficients) = fixed_base_terms(
+            a_eval,
+            a_next_eval,
+            b_eval,
+            b_next_eval,
+            c_eval,
+            d_eval,
+            d_next_eval,
+            fixed_base_separation_challenge,
+        );
+        table_selectors(arithmetic)
+            .iter()
+            .zip(coefficients.iter())
+            .fold(
+                q_fixed_base_poly * &constant,
+                |acc, (selector, coefficient)| {
+                    &acc + &(&selector.polynomial * &(coefficient * q_fixed_base_eval))
+                },
+            )
+    }
+}
+
+// The table selectors, in the order of the coefficients of `fixed_base_terms`
+fn table_selectors(arithmetic: &ArithmeticWidget) -> [&PreProcessedPolynomial; 6] {
+    [
+        &arithmetic.q_m,
+        &arithmetic.q_l,
+        &arithmetic.q_r,
+        &arithmetic.q_o,
+        &arithmetic.q_c,
+        &arithmetic.q_4,
+    ]
+}
+
+// Each gate adds the point of a 2-bit window of the scalar to the
+// accumulated point. As in the range gate, the scalar is accumulated in the
+// fourth wire, one quad per gate, and the quad is mapped to the signed digit
+// `delta = 2 * (d(Xg) - 4 * d(X)) - 3`, one of `-3, -1, 1, 3`.
+//
+// | a     | b     | c          | d            |
+// | acc_x | acc_y | x_a * y_a  | scalar acc   |
+//
+// The four points of the window, `delta * P`, are stored in the selectors.
+// Since `-P = (-x, y)`, they only take three values each:
+// y_a = q_l + q_r * delta^2
+// x_a = q_m * c(X) + q_c * delta
+// and `c(X) = x_a * y_a` must be `delta * (q_o + q_4 * delta^2)`. We check that
+// C_1 = (delta^2 - 1) * (delta^2 - 9)
+// C_2 = c(X) - delta * (q_o + q_4 * delta^2)
+// C_3 = a(Xg) * (1 + d * c(X) * a(X) * b(X)) - (a(X) * y_a + b(X) * x_a)
+// C_4 = b(Xg) * (1 - d * c(X) * a(X) * b(X)) - (b(X) * y_a + a(X) * x_a)
+//
+// The four of them are combined with powers of the separation challenge.
+// The combination is linear in the table selectors, so it is returned as
+// the term without them and the coefficients of `q_m, q_l, q_r, q_o, q_c, q_4`.
+fn fixed_base_terms(
+    acc_x: &Scalar,
+    acc_x_next: &Scalar,
+    acc_y: &Scalar,
+    acc_y_next: &Scalar,
+    xy_alpha: &Scalar,
+    accumulated_quad: &Scalar,
+    accumulated_quad_next: &Scalar,
+    separation_challenge: &Scalar,
+) -> (Scalar, [Scalar; 6]) {
+    let kappa = separation_challenge;
+    let kappa_sq = kappa.square();
+    let kappa_cu = kappa_sq * kappa;
+    let kappa_qu = kappa_cu * kappa;
+
+    let quad = accumulated_quad_next - accumulated_quad * Scalar::from(4u64);
+    let delta = quad + quad - Scalar::from(3u64);
+    let delta_sq = delta.square();
+    let x1_y1_x2_y2 = xy_alpha * acc_x * acc_y * EDWARDS_D;
+
+    let c_1 = (delta_sq - Scalar::one()) * (delta_sq - Scalar::from(9u64));
+    let c_3 = acc_x_next * (Scalar::one() + x1_y1_x2_y2);
+    let c_4 = acc_y_next * (Scalar::one() - x1_y1_x2_y2);
+    let constant = (c_1 + xy_alpha * kappa + c_3 * kappa_sq + c_4 * kappa_cu) * kappa;
+
+    // Coefficients of `y_a`, `x_a` and `x_a * y_a` in the combination
+    let y_coeff = -(acc_x * kappa_cu + acc_y * kappa_qu);
+    let x_coeff = -(acc_y * kappa_cu + acc_x * kappa_qu);
+    let xy_coeff = -kappa_sq;
+
+    (
+        constant,
+        [
+            x_coeff * xy_alpha,
+            y_coeff,
+            y_coeff * delta_sq,
+            xy_coeff * delta,
+            x_coeff * delta,
+            xy_coeff * delta * delta_sq,
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::EvaluationDomain;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn fixed_base_widget_serde_roundtrip() {
+        use bincode;
+        let coeffs = vec![
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+        ];
+        let dom = EvaluationDomain::new(coeffs.len()).unwrap();
+        let evals = Evaluations::from_vec_and_domain(coeffs.clone(), dom);
+        let poly = Polynomial::from_coefficients_vec(coeffs);
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+
+        let prep_poly_w_evals = PreProcessedPolynomial {
+            polynomial: poly,
+            commitment: comm,
+            evaluations: Some(evals),
+        };
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let fixed_base_widget = FixedBaseWidget {
+            q_fixed_base: prep_poly_w_evals,
+        };
+
+        // Roundtrip with evals
+        let ser = bincode::serialize(&fixed_base_widget).unwrap();
+        let deser: FixedBaseWidget = bincode::deserialize(&ser).unwrap();
+        assert_eq!(fixed_base_widget, deser);
+    }
+}
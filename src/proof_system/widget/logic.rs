@@ -99,6 +99,123 @@ impl<'de> Deserialize<'de> for LogicWidget {
     }
 }
 
+/// Commitment to the selector polynomial of the `LogicWidget`.
+///
+/// This is the only part of the widget that the verifier needs. The
+/// evaluation of `q_c` is opened against the commitment of the
+/// `ArithmeticVerifierKey`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct LogicVerifierKey {
+    pub q_logic: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LogicVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut logic_verifier_key = serializer.serialize_struct("struct LogicVerifierKey", 1)?;
+        logic_verifier_key.serialize_field("q_logic", &self.q_logic)?;
+        logic_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LogicVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Qlogic,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct LogicVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_logic" => Ok(Field::Qlogic),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct LogicVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for LogicVerifierKeyVisitor {
+            type Value = LogicVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct LogicVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<LogicVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_logic = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(LogicVerifierKey { q_logic })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_logic"];
+        deserializer.deserialize_struct("LogicVerifierKey", FIELDS, LogicVerifierKeyVisitor)
+    }
+}
+
+impl LogicVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+    ) {
+        let four = Scalar::from(4);
+
+        let a = evaluations.a_next_eval - four * evaluations.a_eval;
+        let c_0 = delta(a);
+
+        let b = evaluations.b_next_eval - four * evaluations.b_eval;
+        let c_1 = delta(b);
+
+        let d = evaluations.d_next_eval - four * evaluations.d_eval;
+        let c_2 = delta(d);
+
+        let w = evaluations.c_eval;
+
+        let c_3 = w - a * b;
+
+        let c_4 = delta_xor_and(&a, &b, &w, &d, &evaluations.q_c_eval);
+        scalars.push(c_0 + c_1 + c_2 + c_3 + c_4);
+        points.push(self.q_logic.0);
+    }
+}
+
 impl LogicWidget {
     pub(crate) fn new(
         q_c: (Polynomial, Commitment, Option<Evaluations>),
@@ -110,6 +227,14 @@ impl LogicWidget {
         }
     }
 
+    /// Returns the commitment that the verifier needs to check the
+    /// logic gates of this widget.
+    pub(crate) fn verifier_key(&self) -> LogicVerifierKey {
+        LogicVerifierKey {
+            q_logic: self.q_logic.commitment,
+        }
+    }
+
     pub(crate) fn compute_quotient_i(
         &self,
         index: usize,
@@ -176,32 +301,6 @@ impl LogicWidget {
 
         q_logic_poly * &(c_0 + c_1 + c_2 + c_3 + c_4)
     }
-
-    pub(crate) fn compute_linearisation_commitment(
-        &self,
-        scalars: &mut Vec<Scalar>,
-        points: &mut Vec<G1Affine>,
-        evaluations: &ProofEvaluations,
-    ) {
-        let four = Scalar::from(4);
-
-        let a = evaluations.a_next_eval - four * evaluations.a_eval;
-        let c_0 = delta(a);
-
-        let b = evaluations.b_next_eval - four * evaluations.b_eval;
-        let c_1 = delta(b);
-
-        let d = evaluations.d_next_eval - four * evaluations.d_eval;
-        let c_2 = delta(d);
-
-        let w = evaluations.c_eval;
-
-        let c_3 = w - a * b;
-
-        let c_4 = delta_xor_and(&a, &b, &w, &d, &evaluations.q_c_eval);
-        scalars.push(c_0 + c_1 + c_2 + c_3 + c_4);
-        points.push(self.q_logic.commitment.0);
-    }
 }
 
 // Computes f(f-1)(f-2)(f-3)
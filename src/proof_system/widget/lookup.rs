@@ -0,0 +1,705 @@
+#![allow(clippy::too_many_arguments)]
+use super::PreProcessedPolynomial;
+use crate::commitment_scheme::kzg10::Commitment;
+use crate::fft::{Evaluations, Polynomial};
+use crate::proof_system::linearisation_poly::{LookupEvaluations, ProofEvaluations};
+use crate::util::batch_inversion;
+use bls12_381::{G1Affine, Scalar};
+#[cfg(feature = "serde")]
+use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LookupWidget {
+    pub q_lookup: PreProcessedPolynomial,
+    pub q_c: PreProcessedPolynomial,
+    // Columns of the lookup tables. The fourth one holds the id of the
+    // table that each row belongs to.
+    pub table_1: PreProcessedPolynomial,
+    pub table_2: PreProcessedPolynomial,
+    pub table_3: PreProcessedPolynomial,
+    pub table_4: PreProcessedPolynomial,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LookupWidget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut lookup_widget = serializer.serialize_struct("struct LookupWidget", 6)?;
+        lookup_widget.serialize_field("q_lookup", &self.q_lookup)?;
+        lookup_widget.serialize_field("q_c", &self.q_c)?;
+        lookup_widget.serialize_field("table_1", &self.table_1)?;
+        lookup_widget.serialize_field("table_2", &self.table_2)?;
+        lookup_widget.serialize_field("table_3", &self.table_3)?;
+        lookup_widget.serialize_field("table_4", &self.table_4)?;
+        lookup_widget.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LookupWidget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Qlookup,
+            Qc,
+            Table1,
+            Table2,
+            Table3,
+            Table4,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct LookupWidget")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_lookup" => Ok(Field::Qlookup),
+                            "q_c" => Ok(Field::Qc),
+                            "table_1" => Ok(Field::Table1),
+                            "table_2" => Ok(Field::Table2),
+                            "table_3" => Ok(Field::Table3),
+                            "table_4" => Ok(Field::Table4),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct LookupWidgetVisitor;
+
+        impl<'de> Visitor<'de> for LookupWidgetVisitor {
+            type Value = LookupWidget;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct LookupWidget")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<LookupWidget, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_lookup = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let q_c = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_1 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_2 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_3 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_4 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(LookupWidget {
+                    q_lookup,
+                    q_c,
+                    table_1,
+                    table_2,
+                    table_3,
+                    table_4,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &[
+            "q_lookup", "q_c", "table_1", "table_2", "table_3", "table_4",
+        ];
+        deserializer.deserialize_struct("LookupWidget", FIELDS, LookupWidgetVisitor)
+    }
+}
+
+/// Commitments to the lookup selector and to the columns of the lookup
+/// tables of the `LookupWidget`.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct LookupVerifierKey {
+    pub q_lookup: Commitment,
+    pub table_1: Commitment,
+    pub table_2: Commitment,
+    pub table_3: Commitment,
+    pub table_4: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for LookupVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut lookup_verifier_key = serializer.serialize_struct("struct LookupVerifierKey", 5)?;
+        lookup_verifier_key.serialize_field("q_lookup", &self.q_lookup)?;
+        lookup_verifier_key.serialize_field("table_1", &self.table_1)?;
+        lookup_verifier_key.serialize_field("table_2", &self.table_2)?;
+        lookup_verifier_key.serialize_field("table_3", &self.table_3)?;
+        lookup_verifier_key.serialize_field("table_4", &self.table_4)?;
+        lookup_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LookupVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Qlookup,
+            Table1,
+            Table2,
+            Table3,
+            Table4,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct LookupVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_lookup" => Ok(Field::Qlookup),
+                            "table_1" => Ok(Field::Table1),
+                            "table_2" => Ok(Field::Table2),
+                            "table_3" => Ok(Field::Table3),
+                            "table_4" => Ok(Field::Table4),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct LookupVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for LookupVerifierKeyVisitor {
+            type Value = LookupVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct LookupVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<LookupVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_lookup = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_1 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_2 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_3 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let table_4 = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(LookupVerifierKey {
+                    q_lookup,
+                    table_1,
+                    table_2,
+                    table_3,
+                    table_4,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_lookup", "table_1", "table_2", "table_3", "table_4"];
+        deserializer.deserialize_struct("LookupVerifierKey", FIELDS, LookupVerifierKeyVisitor)
+    }
+}
+
+impl LookupVerifierKey {
+    /// Computes the commitment to the compressed table polynomial
+    /// `t_1(X) + zeta * t_2(X) + zeta^2 * t_3(X) + zeta^3 * t_4(X)`.
+    pub(crate) fn compute_table_commitment(&self, zeta: &Scalar) -> Commitment {
+        let zeta_sq = zeta.square();
+        let zeta_cu = zeta_sq * zeta;
+        Commitment::from_projective(
+            self.table_1.0
+                + (self.table_2.0 * zeta)
+                + (self.table_3.0 * zeta_sq)
+                + (self.table_4.0 * zeta_cu),
+        )
+    }
+
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+        lookup_evaluations: &LookupEvaluations,
+        lookup_challenges: (&Scalar, &Scalar, &Scalar),
+        lookup_separation_challenge: &Scalar,
+        (z_challenge, last_element): (&Scalar, &Scalar),
+        lagrange_evals: (&Scalar, &Scalar),
+        (h_2_comm, z_2_comm): (G1Affine, G1Affine),
+    ) {
+        let (zeta, _, _) = lookup_challenges;
+
+        let query = query_identity(
+            &evaluations.a_eval,
+            &evaluations.b_eval,
+            &evaluations.c_eval,
+            &evaluations.q_c_eval,
+            &lookup_evaluations.f_eval,
+            zeta,
+        );
+        scalars.push(query * lookup_separation_challenge);
+        points.push(self.q_lookup.0);
+
+        let (z_2_scalar, h_2_scalar, _) = accumulator_terms(
+            lookup_evaluations,
+            lookup_challenges,
+            lookup_separation_challenge,
+            (z_challenge, last_element),
+            lagrange_evals,
+        );
+        scalars.push(z_2_scalar);
+        points.push(z_2_comm);
+        scalars.push(h_2_scalar);
+        points.push(h_2_comm);
+    }
+}
+
+impl LookupWidget {
+    pub(crate) fn new(
+        q_lookup: (Polynomial, Commitment, Option<Evaluations>),
+        q_c: (Polynomial, Commitment, Option<Evaluations>),
+        table_1: (Polynomial, Commitment, Option<Evaluations>),
+        table_2: (Polynomial, Commitment, Option<Evaluations>),
+        table_3: (Polynomial, Commitment, Option<Evaluations>),
+        table_4: (Polynomial, Commitment, Option<Evaluations>),
+    ) -> LookupWidget {
+        LookupWidget {
+            q_lookup: PreProcessedPolynomial::new(q_lookup),
+            q_c: PreProcessedPolynomial::new(q_c),
+            table_1: PreProcessedPolynomial::new(table_1),
+            table_2: PreProcessedPolynomial::new(table_2),
+            table_3: PreProcessedPolynomial::new(table_3),
+            table_4: PreProcessedPolynomial::new(table_4),
+        }
+    }
+
+    /// Returns the commitments that the verifier needs to check the
+    /// lookup gates of this widget.
+    pub(crate) fn verifier_key(&self) -> LookupVerifierKey {
+        LookupVerifierKey {
+            q_lookup: self.q_lookup.commitment,
+            table_1: self.table_1.commitment,
+            table_2: self.table_2.commitment,
+            table_3: self.table_3.commitment,
+            table_4: self.table_4.commitment,
+        }
+    }
+
+    /// Computes the compressed table polynomial
+    /// `t_1(X) + zeta * t_2(X) + zeta^2 * t_3(X) + zeta^3 * t_4(X)`.
+    pub(crate) fn compute_table_poly(&self, zeta: &Scalar) -> Polynomial {
+        let zeta_sq = zeta.square();
+        let zeta_cu = zeta_sq * zeta;
+
+        let mut table_poly = self.table_1.polynomial.clone();
+        table_poly += (*zeta, &self.table_2.polynomial);
+        table_poly += (zeta_sq, &self.table_3.polynomial);
+        table_poly += (zeta_cu, &self.table_4.polynomial);
+        table_poly
+    }
+
+    pub(crate) fn compute_quotient_i(
+        &self,
+        index: usize,
+        w_l_i: &Scalar,
+        w_r_i: &Scalar,
+        w_o_i: &Scalar,
+        (f_i, h_1_i, h_1_i_next, h_2_i, h_2_i_next): (&Scalar, &Scalar, &Scalar, &Scalar, &Scalar),
+        (z_2_i, z_2_i_next): (&Scalar, &Scalar),
+        (x_i, last_element): (&Scalar, &Scalar),
+        (l1_i, ln_i): (&Scalar, &Scalar),
+        (zeta, delta, epsilon): (&Scalar, &Scalar, &Scalar),
+        lookup_separation_challenge: &Scalar,
+    ) -> Scalar {
+        let q_lookup_i = &self.q_lookup.evaluations.as_ref().unwrap()[index];
+        let q_c_i = &self.q_c.evaluations.as_ref().unwrap()[index];
+        let table_i = self.compute_table_i(index, zeta);
+        let table_i_next = self.compute_table_i(index + 8, zeta);
+
+        let kappa = lookup_separation_challenge;
+        let kappa_sq = kappa.square();
+        let kappa_cu = kappa_sq * kappa;
+        let kappa_qu = kappa_cu * kappa;
+
+        let one_plus_delta = Scalar::one() + delta;
+        let epsilon_one_plus_delta = epsilon * one_plus_delta;
+
+        let c_1 = query_identity(w_l_i, w_r_i, w_o_i, q_c_i, f_i, zeta) * q_lookup_i;
+        let c_2 = (x_i - last_element)
+            * (z_2_i
+                * one_plus_delta
+                * (epsilon + f_i)
+                * (epsilon_one_plus_delta + table_i + delta * table_i_next)
+                - z_2_i_next
+                    * (epsilon_one_plus_delta + h_1_i + delta * h_1_i_next)
+                    * (epsilon_one_plus_delta + h_2_i + delta * h_2_i_next));
+        let c_3 = (z_2_i - Scalar::one()) * l1_i;
+        let c_4 = (h_1_i - h_2_i_next) * ln_i;
+        let c_5 = (z_2_i - Scalar::one()) * ln_i;
+
+        (c_1 + c_2 * kappa + c_3 * kappa_sq + c_4 * kappa_cu + c_5 * kappa_qu) * kappa
+    }
+
+    pub(crate) fn compute_linearisation(
+        &self,
+        evaluations: &ProofEvaluations,
+        lookup_evaluations: &LookupEvaluations,
+        lookup_challenges: (&Scalar, &Scalar, &Scalar),
+        lookup_separation_challenge: &Scalar,
+        (z_challenge, last_element): (&Scalar, &Scalar),
+        lagrange_evals: (&Scalar, &Scalar),
+        (h_2_poly, z_2_poly): (&Polynomial, &Polynomial),
+    ) -> Polynomial {
+        let (zeta, _, _) = lookup_challenges;
+
+        let query = query_identity(
+            &evaluations.a_eval,
+            &evaluations.b_eval,
+            &evaluations.c_eval,
+            &evaluations.q_c_eval,
+            &lookup_evaluations.f_eval,
+            zeta,
+        );
+        let (z_2_scalar, h_2_scalar, _) = accumulator_terms(
+            lookup_evaluations,
+            lookup_challenges,
+            lookup_separation_challenge,
+            (z_challenge, last_element),
+            lagrange_evals,
+        );
+
+        let a = &self.q_lookup.polynomial * &(query * lookup_separation_challenge);
+        let b = z_2_poly * &z_2_scalar;
+        let c = h_2_poly * &h_2_scalar;
+        &(&a + &b) + &c
+    }
+
+    // Evaluation of the compressed table at the given index of the 8n coset
+    fn compute_table_i(&self, index: usize, zeta: &Scalar) -> Scalar {
+        let column = |table: &PreProcessedPolynomial| {
+            let evals = &table.evaluations.as_ref().unwrap().evals;
+            evals[index % evals.len()]
+        };
+        compress(
+            &column(&self.table_1),
+            &column(&self.table_2),
+            &column(&self.table_3),
+            &column(&self.table_4),
+            zeta,
+        )
+    }
+}
+
+/// The polynomials that the prover builds for the lookup argument of a proof,
+/// along with the challenges they were built with.
+pub struct LookupPolynomials {
+    pub(crate) f_poly: Polynomial,
+    pub(crate) h_1_poly: Polynomial,
+    pub(crate) h_2_poly: Polynomial,
+    pub(crate) z_2_poly: Polynomial,
+    // The compressed lookup table
+    pub(crate) table_poly: Polynomial,
+    // The challenges `(zeta, delta, epsilon)`
+    pub(crate) challenges: (Scalar, Scalar, Scalar),
+    pub(crate) separation_challenge: Scalar,
+}
+
+/// Returns the part of the lookup identity which is constant once the
+/// evaluations of the proof are known. It does not appear in the
+/// linearisation polynomial, so the verifier adds it to the evaluation of
+/// the quotient polynomial.
+pub(crate) fn compute_constant_term(
+    evaluations: &LookupEvaluations,
+    lookup_challenges: (&Scalar, &Scalar, &Scalar),
+    lookup_separation_challenge: &Scalar,
+    (z_challenge, last_element): (&Scalar, &Scalar),
+    lagrange_evals: (&Scalar, &Scalar),
+) -> Scalar {
+    let (_, _, constant) = accumulator_terms(
+        evaluations,
+        lookup_challenges,
+        lookup_separation_challenge,
+        (z_challenge, last_element),
+        lagrange_evals,
+    );
+    constant
+}
+
+/// Compresses a row of the lookup tables, or the wires of a lookup gate,
+/// into a single `Scalar`.
+pub(crate) fn compress(a: &Scalar, b: &Scalar, c: &Scalar, d: &Scalar, zeta: &Scalar) -> Scalar {
+    a + zeta * (b + zeta * (c + zeta * d))
+}
+
+/// Computes the sorted list `s` of the queries and the table, in the order
+/// in which the values appear in the table, and splits it into the two
+/// halves `h_1` and `h_2`, which share one element.
+///
+/// Only the first `n - 1` queries are sorted, since the last row of the
+/// domain is used to close the accumulator. Queries that are not in the
+/// table cannot be sorted, so they are appended at the end of `s`, which
+/// makes the proof fail.
+pub(crate) fn compute_sorted_evals(
+    queries: &[Scalar],
+    table: &[Scalar],
+) -> (Vec<Scalar>, Vec<Scalar>) {
+    let n = table.len();
+
+    let mut positions = HashMap::with_capacity(n);
+    for (i, t) in table.iter().enumerate() {
+        positions.entry(t.to_bytes()).or_insert(i);
+    }
+
+    let mut counts = vec![0usize; n];
+    let mut missing = Vec::new();
+    for f in queries[..n - 1].iter() {
+        match positions.get(&f.to_bytes()) {
+            Some(i) => counts[*i] += 1,
+            None => missing.push(*f),
+        }
+    }
+
+    let mut sorted = Vec::with_capacity(2 * n - 1);
+    for (t, count) in table.iter().zip(counts) {
+        sorted.extend(std::iter::repeat(*t).take(count + 1));
+    }
+    sorted.extend(missing);
+
+    (sorted[..n].to_vec(), sorted[n - 1..].to_vec())
+}
+
+/// Computes the evaluations of the Plookup accumulator `z_2(X)` over the
+/// domain, which is `1` at the first row and
+///
+/// z_2(g^{i+1}) = z_2(g^i) * (1 + delta) * (epsilon + f_i) * (epsilon * (1 + delta) + t_i + delta * t_{i+1})
+///              / ((epsilon * (1 + delta) + h_1_i + delta * h_1_{i+1}) * (epsilon * (1 + delta) + h_2_i + delta * h_2_{i+1}))
+pub(crate) fn compute_accumulator_evals(
+    queries: &[Scalar],
+    table: &[Scalar],
+    h_1: &[Scalar],
+    h_2: &[Scalar],
+    (delta, epsilon): (&Scalar, &Scalar),
+) -> Vec<Scalar> {
+    let n = table.len();
+    let one_plus_delta = Scalar::one() + delta;
+    let epsilon_one_plus_delta = epsilon * one_plus_delta;
+
+    let mut denominators: Vec<Scalar> = (0..n - 1)
+        .map(|i| {
+            (epsilon_one_plus_delta + h_1[i] + delta * h_1[i + 1])
+                * (epsilon_one_plus_delta + h_2[i] + delta * h_2[i + 1])
+        })
+        .collect();
+    batch_inversion(&mut denominators);
+
+    let mut z_2 = Vec::with_capacity(n);
+    z_2.push(Scalar::one());
+    for i in 0..n - 1 {
+        let numerator = one_plus_delta
+            * (epsilon + queries[i])
+            * (epsilon_one_plus_delta + table[i] + delta * table[i + 1]);
+        z_2.push(z_2[i] * numerator * denominators[i]);
+    }
+    z_2
+}
+
+// A lookup gate compresses its wires and `q_c`, which holds the id of the
+// table, into the query `f(X)`:
+//
+// C_1 = q_lookup(X) * (a(X) + zeta * b(X) + zeta^2 * c(X) + zeta^3 * q_c(X) - f(X))
+fn query_identity(
+    a: &Scalar,
+    b: &Scalar,
+    c: &Scalar,
+    q_c: &Scalar,
+    f: &Scalar,
+    zeta: &Scalar,
+) -> Scalar {
+    compress(a, b, c, q_c, zeta) - f
+}
+
+// The sorted list `s = (h_1, h_2)` is checked against the queries `f(X)` and
+// the compressed table `t(X)` with the accumulator `z_2(X)`, writing `g` for
+// the generator of the domain:
+//
+// C_2 = (X - g^{n-1}) * [z_2(X) * (1 + delta) * (epsilon + f(X)) * (epsilon * (1 + delta) + t(X) + delta * t(Xg))
+//       - z_2(Xg) * (epsilon * (1 + delta) + h_1(X) + delta * h_1(Xg)) * (epsilon * (1 + delta) + h_2(X) + delta * h_2(Xg))]
+// C_3 = L_1(X) * (z_2(X) - 1)
+// C_4 = L_n(X) * (h_1(X) - h_2(Xg))
+// C_5 = L_n(X) * (z_2(X) - 1)
+//
+// Along with C_1 they are combined with powers of the separation challenge.
+// Once evaluated at `z`, C_2, C_3 and C_5 are linear in `z_2(X)` and `h_2(X)`.
+// This returns the scalars that multiply them in the linearisation
+// polynomial and the remaining constant term.
+fn accumulator_terms(
+    evaluations: &LookupEvaluations,
+    (_, delta, epsilon): (&Scalar, &Scalar, &Scalar),
+    lookup_separation_challenge: &Scalar,
+    (z_challenge, last_element): (&Scalar, &Scalar),
+    (l1_eval, ln_eval): (&Scalar, &Scalar),
+) -> (Scalar, Scalar, Scalar) {
+    let kappa = lookup_separation_challenge;
+    let kappa_sq = kappa.square();
+    let kappa_cu = kappa_sq * kappa;
+    let kappa_qu = kappa_cu * kappa;
+
+    let one_plus_delta = Scalar::one() + delta;
+    let epsilon_one_plus_delta = epsilon * one_plus_delta;
+    let x_minus_last = z_challenge - last_element;
+
+    // (z - g^{n-1}) * (1 + delta) * (epsilon + f_eval) * (epsilon * (1 + delta) + t_eval + delta * t_next_eval)
+    let a = x_minus_last
+        * one_plus_delta
+        * (epsilon + evaluations.f_eval)
+        * (epsilon_one_plus_delta + evaluations.table_eval + delta * evaluations.table_next_eval);
+    // (z - g^{n-1}) * z_2_next_eval * (epsilon * (1 + delta) + h_1_eval + delta * h_1_next_eval)
+    let b = x_minus_last
+        * evaluations.lookup_perm_eval
+        * (epsilon_one_plus_delta + evaluations.h_1_eval + delta * evaluations.h_1_next_eval);
+
+    let z_2_scalar = (a * kappa + l1_eval * kappa_sq + ln_eval * kappa_qu) * kappa;
+    let h_2_scalar = -(b * kappa_sq);
+
+    let constant = (-(b * (epsilon_one_plus_delta + delta * evaluations.h_2_next_eval)) * kappa
+        - l1_eval * kappa_sq
+        + ln_eval * (evaluations.h_1_eval - evaluations.h_2_next_eval) * kappa_cu
+        - ln_eval * kappa_qu)
+        * kappa;
+
+    (z_2_scalar, h_2_scalar, constant)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::EvaluationDomain;
+
+    #[test]
+    fn test_accumulator_closes_on_sorted_list() {
+        let table: Vec<Scalar> = (0..8u64).map(Scalar::from).collect();
+        let queries: Vec<Scalar> = [3u64, 3, 0, 7, 5, 3, 1, 2]
+            .iter()
+            .map(|q| Scalar::from(*q))
+            .collect();
+        let (delta, epsilon) = (Scalar::from(5), Scalar::from(11));
+
+        let (h_1, h_2) = compute_sorted_evals(&queries, &table);
+        assert_eq!(h_1.len(), table.len());
+        assert_eq!(h_2.len(), table.len());
+        assert_eq!(h_1[table.len() - 1], h_2[0]);
+
+        let z_2 = compute_accumulator_evals(&queries, &table, &h_1, &h_2, (&delta, &epsilon));
+        assert_eq!(z_2[0], Scalar::one());
+        assert_eq!(z_2[table.len() - 1], Scalar::one());
+
+        // A query which is not in the table does not close the accumulator
+        let mut queries = queries;
+        queries[1] = Scalar::from(8);
+        let (h_1, h_2) = compute_sorted_evals(&queries, &table);
+        let z_2 = compute_accumulator_evals(&queries, &table, &h_1, &h_2, (&delta, &epsilon));
+        assert_ne!(z_2[table.len() - 1], Scalar::one());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn lookup_widget_serde_roundtrip() {
+        use bincode;
+        let coeffs = vec![
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+            Scalar::one(),
+        ];
+        let dom = EvaluationDomain::new(coeffs.len()).unwrap();
+        let evals = Evaluations::from_vec_and_domain(coeffs.clone(), dom);
+        let poly = Polynomial::from_coefficients_vec(coeffs);
+        let comm = crate::commitment_scheme::kzg10::Commitment::from_affine(G1Affine::generator());
+
+        // Build directly the widget since the `new()` impl doesn't check any
+        // correctness on the inputs.
+        let prep_poly_w_evals = PreProcessedPolynomial {
+            polynomial: poly,
+            commitment: comm,
+            evaluations: Some(evals),
+        };
+
+        let lookup_widget = LookupWidget {
+            q_lookup: prep_poly_w_evals.clone(),
+            q_c: prep_poly_w_evals.clone(),
+            table_1: prep_poly_w_evals.clone(),
+            table_2: prep_poly_w_evals.clone(),
+            table_3: prep_poly_w_evals.clone(),
+            table_4: prep_poly_w_evals,
+        };
+
+        // Roundtrip with evals
+        let ser = bincode::serialize(&lookup_widget).unwrap();
+        let deser: LookupWidget = bincode::deserialize(&ser).unwrap();
+        assert_eq!(lookup_widget, deser);
+    }
+}
@@ -2,14 +2,22 @@ use crate::commitment_scheme::kzg10::Commitment;
 use crate::fft::{Evaluations, Polynomial};
 
 pub mod arithmetic;
+pub mod custom;
+pub mod ecc_add;
+pub mod fixed_base;
 pub mod logic;
+pub mod lookup;
 pub mod permutation;
 pub mod range;
 
-pub use arithmetic::ArithmeticWidget;
-pub use logic::LogicWidget;
-pub use permutation::PermutationWidget;
-pub use range::RangeWidget;
+pub use arithmetic::{ArithmeticVerifierKey, ArithmeticWidget};
+pub use custom::{CustomVerifierKey, CustomWidget, Widget, WireEvaluations};
+pub use ecc_add::{EccAddVerifierKey, EccAddWidget};
+pub use fixed_base::{FixedBaseVerifierKey, FixedBaseWidget};
+pub use logic::{LogicVerifierKey, LogicWidget};
+pub use lookup::{LookupVerifierKey, LookupWidget};
+pub use permutation::{PermutationVerifierKey, PermutationWidget};
+pub use range::{RangeVerifierKey, RangeWidget};
 #[cfg(feature = "serde")]
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
@@ -17,7 +17,7 @@ pub struct PermutationWidget {
     pub right_sigma: PreProcessedPolynomial,
     pub out_sigma: PreProcessedPolynomial,
     pub fourth_sigma: PreProcessedPolynomial,
-    pub linear_evaluations: Evaluations, // Evaluations of f(x) = X
+    pub linear_evaluations: Evaluations, // Evaluations of f(x) = X over the 8n coset
 }
 
 #[cfg(feature = "serde")]
@@ -135,6 +135,180 @@ impl<'de> Deserialize<'de> for PermutationWidget {
     }
 }
 
+/// Commitments to the selector polynomials of the `PermutationWidget`.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct PermutationVerifierKey {
+    pub left_sigma: Commitment,
+    pub right_sigma: Commitment,
+    pub out_sigma: Commitment,
+    pub fourth_sigma: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PermutationVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut permutation_verifier_key =
+            serializer.serialize_struct("struct PermutationVerifierKey", 4)?;
+        permutation_verifier_key.serialize_field("left_sig", &self.left_sigma)?;
+        permutation_verifier_key.serialize_field("right_sig", &self.right_sigma)?;
+        permutation_verifier_key.serialize_field("out_sig", &self.out_sigma)?;
+        permutation_verifier_key.serialize_field("fourth_sig", &self.fourth_sigma)?;
+        permutation_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PermutationVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            LeftSig,
+            RightSig,
+            OutSig,
+            FourthSig,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct PermutationVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "left_sig" => Ok(Field::LeftSig),
+                            "right_sig" => Ok(Field::RightSig),
+                            "out_sig" => Ok(Field::OutSig),
+                            "fourth_sig" => Ok(Field::FourthSig),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct PermutationVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for PermutationVerifierKeyVisitor {
+            type Value = PermutationVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct PermutationVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<PermutationVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let left_sigma = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let right_sigma = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                let out_sigma = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
+                let fourth_sigma = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(3, &self))?;
+                Ok(PermutationVerifierKey {
+                    left_sigma,
+                    right_sigma,
+                    out_sigma,
+                    fourth_sigma,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["left_sig", "right_sig", "out_sig", "fourth_sig"];
+        deserializer.deserialize_struct(
+            "PermutationVerifierKey",
+            FIELDS,
+            PermutationVerifierKeyVisitor,
+        )
+    }
+}
+
+impl PermutationVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+        z_challenge: &Scalar,
+        (alpha, beta, gamma): (&Scalar, &Scalar, &Scalar),
+        l1_eval: &Scalar,
+        z_comm: G1Affine,
+    ) {
+        let alpha_sq = alpha * alpha;
+
+        // (a_eval + beta * z + gamma)(b_eval + beta * z * k1 + gamma)(c_eval + beta * k2 * z + gamma)(d_eval + beta * k3 * z + gamma) * alpha
+        let x = {
+            let beta_z = beta * z_challenge;
+            let q_0 = evaluations.a_eval + beta_z + gamma;
+
+            let beta_k1_z = beta * K1 * z_challenge;
+            let q_1 = evaluations.b_eval + beta_k1_z + gamma;
+
+            let beta_k2_z = beta * K2 * z_challenge;
+            let q_2 = evaluations.c_eval + beta_k2_z + gamma;
+
+            let beta_k3_z = beta * K3 * z_challenge;
+            let q_3 = (evaluations.d_eval + beta_k3_z + gamma) * alpha;
+
+            q_0 * q_1 * q_2 * q_3
+        };
+
+        // l1(z) * alpha^2
+        let r = l1_eval * alpha_sq;
+
+        scalars.push(x + r);
+        points.push(z_comm);
+
+        // -(a_eval + beta * sigma_1_eval + gamma)(b_eval + beta * sigma_2_eval + gamma)(c_eval + beta * sigma_3_eval + gamma) * alpha^2
+        let y = {
+            let beta_sigma_1 = beta * evaluations.left_sigma_eval;
+            let q_0 = evaluations.a_eval + beta_sigma_1 + gamma;
+
+            let beta_sigma_2 = beta * evaluations.right_sigma_eval;
+            let q_1 = evaluations.b_eval + beta_sigma_2 + gamma;
+
+            let beta_sigma_3 = beta * evaluations.out_sigma_eval;
+            let q_2 = evaluations.c_eval + beta_sigma_3 + gamma;
+
+            let q_3 = beta * evaluations.perm_eval * alpha;
+
+            -(q_0 * q_1 * q_2 * q_3)
+        };
+        scalars.push(y);
+        points.push(self.fourth_sigma.0);
+    }
+}
+
 impl PermutationWidget {
     pub(crate) fn new(
         left_sigma: (Polynomial, Commitment, Option<Evaluations>),
@@ -152,6 +326,17 @@ impl PermutationWidget {
         }
     }
 
+    /// Returns the commitments that the verifier needs to check the
+    /// copy constraints of this widget.
+    pub(crate) fn verifier_key(&self) -> PermutationVerifierKey {
+        PermutationVerifierKey {
+            left_sigma: self.left_sigma.commitment,
+            right_sigma: self.right_sigma.commitment,
+            out_sigma: self.out_sigma.commitment,
+            fourth_sigma: self.fourth_sigma.commitment,
+        }
+    }
+
     pub(crate) fn compute_quotient_i(
         &self,
         index: usize,
@@ -231,6 +416,7 @@ impl PermutationWidget {
 
     pub(crate) fn compute_linearisation(
         &self,
+        domain: &EvaluationDomain,
         z_challenge: &Scalar,
         (alpha, beta, gamma): (&Scalar, &Scalar, &Scalar),
         (a_eval, b_eval, c_eval, d_eval): (&Scalar, &Scalar, &Scalar, &Scalar),
@@ -254,8 +440,7 @@ impl PermutationWidget {
             &self.fourth_sigma.polynomial,
         );
 
-        let domain = EvaluationDomain::new(z_poly.degree()).unwrap();
-        let c = self.compute_lineariser_check_is_one(&domain, z_challenge, &alpha.square(), z_poly);
+        let c = self.compute_lineariser_check_is_one(domain, z_challenge, &alpha.square(), z_poly);
         &(&a + &b) + &c
     }
     // (a_eval + beta * z_challenge + gamma)(b_eval + beta * K1 * z_challenge + gamma)(c_eval + beta * K2 * z_challenge + gamma) * alpha z(X)
@@ -340,60 +525,6 @@ impl PermutationWidget {
 
         z_coeffs * &(l_1_z * alpha_sq)
     }
-
-    pub(crate) fn compute_linearisation_commitment(
-        &self,
-        scalars: &mut Vec<Scalar>,
-        points: &mut Vec<G1Affine>,
-        evaluations: &ProofEvaluations,
-        z_challenge: &Scalar,
-        (alpha, beta, gamma): (&Scalar, &Scalar, &Scalar),
-        l1_eval: &Scalar,
-        z_comm: G1Affine,
-    ) {
-        let alpha_sq = alpha * alpha;
-
-        // (a_eval + beta * z + gamma)(b_eval + beta * z * k1 + gamma)(c_eval + beta * k2 * z + gamma)(d_eval + beta * k3 * z + gamma) * alpha
-        let x = {
-            let beta_z = beta * z_challenge;
-            let q_0 = evaluations.a_eval + beta_z + gamma;
-
-            let beta_k1_z = beta * K1 * z_challenge;
-            let q_1 = evaluations.b_eval + beta_k1_z + gamma;
-
-            let beta_k2_z = beta * K2 * z_challenge;
-            let q_2 = evaluations.c_eval + beta_k2_z + gamma;
-
-            let beta_k3_z = beta * K3 * z_challenge;
-            let q_3 = (evaluations.d_eval + beta_k3_z + gamma) * alpha;
-
-            q_0 * q_1 * q_2 * q_3
-        };
-
-        // l1(z) * alpha^2
-        let r = l1_eval * alpha_sq;
-
-        scalars.push(x + r);
-        points.push(z_comm);
-
-        // -(a_eval + beta * sigma_1_eval + gamma)(b_eval + beta * sigma_2_eval + gamma)(c_eval + beta * sigma_3_eval + gamma) * alpha^2
-        let y = {
-            let beta_sigma_1 = beta * evaluations.left_sigma_eval;
-            let q_0 = evaluations.a_eval + beta_sigma_1 + gamma;
-
-            let beta_sigma_2 = beta * evaluations.right_sigma_eval;
-            let q_1 = evaluations.b_eval + beta_sigma_2 + gamma;
-
-            let beta_sigma_3 = beta * evaluations.out_sigma_eval;
-            let q_2 = evaluations.c_eval + beta_sigma_3 + gamma;
-
-            let q_3 = beta * evaluations.perm_eval * alpha;
-
-            -(q_0 * q_1 * q_2 * q_3)
-        };
-        scalars.push(y);
-        points.push(self.fourth_sigma.commitment.0);
-    }
 }
 
 #[allow(dead_code)]
@@ -407,21 +538,21 @@ fn compute_first_lagrange_poly_scaled(domain: &EvaluationDomain, scale: Scalar)
 /// Ensures that the polynomial evaluated at the first root of unity is one
 pub fn compute_is_one_polynomial(
     domain: &EvaluationDomain,
-    z_eval_4n: &[Scalar],
+    z_eval_8n: &[Scalar],
     alpha_sq: Scalar,
 ) -> Evaluations {
     let n = domain.size();
-    let domain_4n = EvaluationDomain::new(4 * n).unwrap();
+    let domain_8n = EvaluationDomain::new(8 * n).unwrap();
 
     let l1_poly_alpha = compute_first_lagrange_poly_scaled(domain, alpha_sq);
 
-    let alpha_sq_l1_evals = domain_4n.coset_fft(&l1_poly_alpha.coeffs);
+    let alpha_sq_l1_evals = domain_8n.coset_fft(&l1_poly_alpha.coeffs);
 
-    let t_4: Vec<_> = (0..domain_4n.size())
+    let t_4: Vec<_> = (0..domain_8n.size())
         .into_par_iter()
-        .map(|i| alpha_sq_l1_evals[i] * (z_eval_4n[i] - Scalar::one()))
+        .map(|i| alpha_sq_l1_evals[i] * (z_eval_8n[i] - Scalar::one()))
         .collect();
-    Evaluations::from_vec_and_domain(t_4, domain_4n)
+    Evaluations::from_vec_and_domain(t_4, domain_8n)
 }
 
 #[cfg(test)]
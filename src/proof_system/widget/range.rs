@@ -91,6 +91,112 @@ impl<'de> Deserialize<'de> for RangeWidget {
     }
 }
 
+/// Commitments to the selector polynomials of the `RangeWidget`.
+///
+/// This is the only part of the widget that the verifier needs.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct RangeVerifierKey {
+    pub q_range: Commitment,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for RangeVerifierKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut range_verifier_key = serializer.serialize_struct("struct RangeVerifierKey", 1)?;
+        range_verifier_key.serialize_field("q_range", &self.q_range)?;
+        range_verifier_key.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RangeVerifierKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        enum Field {
+            Qrange,
+        };
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct FieldVisitor;
+
+                impl<'de> Visitor<'de> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut ::core::fmt::Formatter,
+                    ) -> ::core::fmt::Result {
+                        formatter.write_str("struct RangeVerifierKey")
+                    }
+
+                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "q_range" => Ok(Field::Qrange),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct RangeVerifierKeyVisitor;
+
+        impl<'de> Visitor<'de> for RangeVerifierKeyVisitor {
+            type Value = RangeVerifierKey;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("struct RangeVerifierKey")
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<RangeVerifierKey, V::Error>
+            where
+                V: serde::de::SeqAccess<'de>,
+            {
+                let q_range = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                Ok(RangeVerifierKey { q_range })
+            }
+        }
+
+        const FIELDS: &[&str] = &["q_range"];
+        deserializer.deserialize_struct("RangeVerifierKey", FIELDS, RangeVerifierKeyVisitor)
+    }
+}
+
+impl RangeVerifierKey {
+    pub(crate) fn compute_linearisation_commitment(
+        &self,
+        scalars: &mut Vec<Scalar>,
+        points: &mut Vec<G1Affine>,
+        evaluations: &ProofEvaluations,
+    ) {
+        let four = Scalar::from(4);
+
+        let b_1 = delta(evaluations.c_eval - (four * evaluations.d_eval));
+        let b_2 = delta(evaluations.b_eval - four * evaluations.c_eval);
+        let b_3 = delta(evaluations.a_eval - four * evaluations.b_eval);
+        let b_4 = delta(evaluations.d_next_eval - (four * evaluations.a_eval));
+
+        scalars.push(b_1 + b_2 + b_3 + b_4);
+        points.push(self.q_range.0);
+    }
+}
+
 impl RangeWidget {
     pub(crate) fn new(selector: (Polynomial, Commitment, Option<Evaluations>)) -> RangeWidget {
         RangeWidget {
@@ -98,6 +204,14 @@ impl RangeWidget {
         }
     }
 
+    /// Returns the commitments that the verifier needs to check the
+    /// range gates of this widget.
+    pub(crate) fn verifier_key(&self) -> RangeVerifierKey {
+        RangeVerifierKey {
+            q_range: self.q_range.commitment,
+        }
+    }
+
     pub(crate) fn compute_quotient_i(
         &self,
         index: usize,
@@ -139,23 +253,6 @@ impl RangeWidget {
         let b_4 = delta(d_next_eval - four * a_eval);
         q_range_poly * &(b_1 + b_2 + b_3 + b_4)
     }
-
-    pub(crate) fn compute_linearisation_commitment(
-        &self,
-        scalars: &mut Vec<Scalar>,
-        points: &mut Vec<G1Affine>,
-        evaluations: &ProofEvaluations,
-    ) {
-        let four = Scalar::from(4);
-
-        let b_1 = delta(evaluations.c_eval - (four * evaluations.d_eval));
-        let b_2 = delta(evaluations.b_eval - four * evaluations.c_eval);
-        let b_3 = delta(evaluations.a_eval - four * evaluations.b_eval);
-        let b_4 = delta(evaluations.d_next_eval - (four * evaluations.a_eval));
-
-        scalars.push(b_1 + b_2 + b_3 + b_4);
-        points.push(self.q_range.commitment.0);
-    }
 }
 
 // Computes f(f-1)(f-2)(f-3)
@@ -0,0 +1,180 @@
+//! Schnorr signatures over the Jubjub curve, whose challenges are computed
+//! with Poseidon so that `StandardComposer::schnorr_verify` can verify them
+//! in a circuit.
+//!
+//! A signature of a message `m` under the public key `PK = sk * G` is a
+//! pair `(R, s)` such that `s * G = R + c * PK`, where the challenge `c` is
+//! the Poseidon hash of `R`, `PK` and `m` truncated to `250` bits. The hash
+//! must also fit in `254` bits, which keeps its split into the challenge and
+//! the bits above it unique in the circuit, so signing draws a new nonce
+//! whenever it does not.
+//!
+//! The types of this module only hold public keys of prime order and
+//! scalars `s` reduced modulo the order of the Jubjub scalar field, so the
+//! native verifier does not check them. The inputs of the gadget are free
+//! witnesses instead, and it constrains both.
+
+use crate::hash::Poseidon;
+use crate::util;
+use bls12_381::Scalar;
+use jubjub::{AffinePoint, ExtendedPoint, Fr as JubJubScalar, GENERATOR};
+use rand_core::{CryptoRng, RngCore};
+
+// Number of bits of the challenges
+pub(crate) const CHALLENGE_BITS: usize = 250;
+// Number of bits of the hashes above the challenge
+pub(crate) const HASH_HIGH_BITS: usize = 4;
+
+/// A secret key, which is a Jubjub scalar.
+#[derive(Debug, Clone)]
+pub struct SecretKey(JubJubScalar);
+
+/// A public key, which is the point of the Jubjub curve obtained by
+/// multiplying the generator by the secret key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PublicKey(AffinePoint);
+
+/// A signature `(R, s)`, made of a point of the Jubjub curve and a scalar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Signature {
+    r: AffinePoint,
+    s: JubJubScalar,
+}
+
+impl SecretKey {
+    /// Generates a random secret key.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        SecretKey(random_scalar(rng))
+    }
+
+    /// Signs `message`, drawing the nonce of the signature from `rng`.
+    pub fn sign<R: RngCore + CryptoRng>(&self, message: &Scalar, rng: &mut R) -> Signature {
+        let public_key = PublicKey::from(self);
+        loop {
+            let k = random_scalar(rng);
+            let r = AffinePoint::from(ExtendedPoint::from(GENERATOR) * k);
+            if let Some(c) = challenge(&r, &public_key, message) {
+                return Signature {
+                    r,
+                    s: k + c * self.0,
+                };
+            }
+        }
+    }
+}
+
+impl From<&SecretKey> for PublicKey {
+    fn from(secret_key: &SecretKey) -> Self {
+        PublicKey(AffinePoint::from(
+            ExtendedPoint::from(GENERATOR) * secret_key.0,
+        ))
+    }
+}
+
+impl PublicKey {
+    /// Returns the point of the public key.
+    pub fn point(&self) -> AffinePoint {
+        self.0
+    }
+
+    /// Returns true if `signature` is a valid signature of `message` under
+    /// this public key.
+    pub fn verify(&self, message: &Scalar, signature: &Signature) -> bool {
+        match challenge(&signature.r, self, message) {
+            Some(c) => {
+                ExtendedPoint::from(GENERATOR) * signature.s
+                    == ExtendedPoint::from(signature.r) + ExtendedPoint::from(self.0) * c
+            }
+            None => false,
+        }
+    }
+}
+
+impl Signature {
+    /// Returns the point `R` of the signature.
+    pub fn r(&self) -> AffinePoint {
+        self.r
+    }
+
+    /// Returns the scalar `s` of the signature.
+    pub fn s(&self) -> JubJubScalar {
+        self.s
+    }
+}
+
+// Computes the Poseidon hash that the challenge of a signature is taken
+// from
+fn challenge_hash(r: &AffinePoint, public_key: &AffinePoint, message: &Scalar) -> Scalar {
+    Poseidon::new().hash(&[
+        r.get_u(),
+        r.get_v(),
+        public_key.get_u(),
+        public_key.get_v(),
+        *message,
+    ])
+}
+
+/// Splits a hash in its bits above `CHALLENGE_BITS` and the challenge made
+/// of the bits below them.
+pub(crate) fn split_hash(hash: &Scalar) -> (Scalar, Scalar) {
+    let mut low = hash.to_bytes();
+    let high = low[CHALLENGE_BITS >> 3] >> (CHALLENGE_BITS & 7);
+    low[CHALLENGE_BITS >> 3] &= (1 << (CHALLENGE_BITS & 7)) - 1;
+    (
+        Scalar::from(u64::from(high)),
+        Scalar::from_bytes(&low).unwrap(),
+    )
+}
+
+// Computes the challenge of a signature, or returns `None` if the hash
+// does not fit in `CHALLENGE_BITS + HASH_HIGH_BITS` bits
+fn challenge(r: &AffinePoint, public_key: &PublicKey, message: &Scalar) -> Option<JubJubScalar> {
+    let hash = challenge_hash(r, &public_key.0, message);
+    if util::num_bits(&hash) > CHALLENGE_BITS + HASH_HIGH_BITS {
+        return None;
+    }
+    // The challenge is smaller than the order of the Jubjub scalar field
+    let (_, c) = split_hash(&hash);
+    Some(JubJubScalar::from_bytes(&c.to_bytes()).unwrap())
+}
+
+// Generates a random Jubjub scalar
+fn random_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> JubJubScalar {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    JubJubScalar::from_bytes_wide(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = rand::thread_rng();
+        let secret_key = SecretKey::random(&mut rng);
+        let public_key = PublicKey::from(&secret_key);
+        let message = Scalar::from(0xdead_beefu64);
+
+        let signature = secret_key.sign(&message, &mut rng);
+        assert!(public_key.verify(&message, &signature));
+
+        // The signature does not hold for any other message or key
+        assert!(!public_key.verify(&(message + Scalar::one()), &signature));
+        let other_key = PublicKey::from(&SecretKey::random(&mut rng));
+        assert!(!other_key.verify(&message, &signature));
+
+        let forged = Signature {
+            r: signature.r,
+            s: signature.s + JubJubScalar::one(),
+        };
+        assert!(!public_key.verify(&message, &forged));
+    }
+
+    #[test]
+    fn test_split_hash() {
+        let hash =
+            Scalar::from(5u64) * Scalar::from(2u64).pow(&[250, 0, 0, 0]) + Scalar::from(7u64);
+        assert_eq!(split_hash(&hash), (Scalar::from(5u64), Scalar::from(7u64)));
+    }
+}
@@ -18,6 +18,12 @@ pub trait TranscriptProtocol {
 
     /// Append domain separator for the circuit size.
     fn circuit_domain_sep(&mut self, n: u64);
+
+    /// Append the values of the public inputs of the circuit.
+    fn append_public_inputs(&mut self, pub_inputs: &[Scalar]);
+
+    /// Append the digest that identifies the circuit being proven.
+    fn append_circuit_id(&mut self, circuit_id: &[u8; 32]);
 }
 
 impl TranscriptProtocol for Transcript {
@@ -40,4 +46,16 @@ impl TranscriptProtocol for Transcript {
         self.append_message(b"dom-sep", b"circuit_size");
         self.append_u64(b"n", n);
     }
+
+    fn append_public_inputs(&mut self, pub_inputs: &[Scalar]) {
+        self.append_message(b"dom-sep", b"public_inputs");
+        self.append_u64(b"num_pi", pub_inputs.len() as u64);
+        for pi in pub_inputs {
+            self.append_scalar(b"pi", pi);
+        }
+    }
+
+    fn append_circuit_id(&mut self, circuit_id: &[u8; 32]) {
+        self.append_message(b"circuit_id", circuit_id);
+    }
 }
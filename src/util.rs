@@ -22,6 +22,16 @@ pub(crate) fn random_scalar<R: RngCore>(rng: &mut R) -> Scalar {
     ])
 }
 
+/// Returns the number of bits needed to represent a Scalar, ie. the
+/// position of its most significant set bit plus one.
+pub(crate) fn num_bits(scalar: &Scalar) -> usize {
+    let bytes = scalar.to_bytes();
+    bytes
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |i| 8 * (i + 1) - bytes[i].leading_zeros() as usize)
+}
+
 /// Generates a random G1 Point using an RNG seed.
 pub(crate) fn random_g1_point<R: RngCore>(rng: &mut R) -> G1Projective {
     G1Affine::generator() * random_scalar(rng)
@@ -94,4 +104,14 @@ mod test {
             assert_eq!(x.invert().unwrap(), *x_inv);
         }
     }
+
+    #[test]
+    fn test_num_bits() {
+        assert_eq!(num_bits(&Scalar::zero()), 0);
+        assert_eq!(num_bits(&Scalar::one()), 1);
+        assert_eq!(num_bits(&Scalar::from(255)), 8);
+        assert_eq!(num_bits(&Scalar::from(256)), 9);
+        assert_eq!(num_bits(&Scalar::from(u64::max_value())), 64);
+        assert_eq!(num_bits(&-Scalar::one()), 255);
+    }
 }